@@ -0,0 +1,65 @@
+//! Mesure du temps de filtrage (`ProblemFilter::matches`) sur un grand jeu de
+//! problèmes générés, avec et sans `--ignore-case`.
+//!
+//! Lancement : `cargo bench --bench filter`
+
+use vscode_problems_filtering::filter::{ProblemFilter, Synonyms, TextOptions};
+use vscode_problems_filtering::problem::Problem;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Nombre de problèmes générés
+const PROBLEM_COUNT: usize = 100_000;
+
+/// Nombre de mesures par scénario
+const ITERATIONS: usize = 20;
+
+/// Génère `n` problèmes synthétiques aux messages et fichiers variés
+fn make_problems(n: usize) -> Vec<Problem> {
+    const MESSAGES: [&str; 4] = [
+        "'{}' is deprecated since version 2.{}",
+        "Cannot find name '{}'. Did you mean 'Value{}'?",
+        "Unused variable '{}' in Test{}",
+        "Property '{}' does not exist on type 'Action{}Error'",
+    ];
+
+    (0..n)
+        .map(|i| Problem {
+            resource: format!("/workspace/packages/module{}/src/file{}.ts", i % 50, i % 1000),
+            start_line_number: (i % 500) as u32 + 1,
+            message: MESSAGES[i % MESSAGES.len()]
+                .replacen("{}", &format!("symbol{i}"), 1)
+                .replacen("{}", &(i % 10).to_string(), 1),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Mesure `ITERATIONS` passes de filtrage et affiche les durées minimale et moyenne
+fn bench(name: &str, problems: &[Problem], filter: &ProblemFilter) {
+    let mut durations = Vec::with_capacity(ITERATIONS);
+    let mut kept = 0;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        kept = problems.iter().filter(|problem| filter.matches(black_box(problem))).count();
+        durations.push(start.elapsed());
+    }
+
+    let min = durations.iter().min().copied().unwrap_or_default();
+    let mean = durations.iter().sum::<Duration>() / ITERATIONS as u32;
+    println!("{name:<24} min {min:>10.2?}  moyenne {mean:>10.2?}  ({kept} problèmes retenus)");
+}
+
+fn main() {
+    let problems = make_problems(PROBLEM_COUNT);
+    let include = ["Deprecated".to_string(), "version".to_string()];
+    let exclude = ["Test".to_string(), "ActionError".to_string()];
+
+    println!("Filtrage de {PROBLEM_COUNT} problèmes, {ITERATIONS} mesures");
+    for ignore_case in [false, true] {
+        let text = TextOptions { ignore_case, ..Default::default() };
+        let filter = ProblemFilter::new(&include, &exclude, text, &Synonyms::default());
+        let name = if ignore_case { "avec --ignore-case" } else { "sans --ignore-case" };
+        bench(name, &problems, &filter);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::problem::Problem;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Catégorie des problèmes ne correspondant à aucune règle
+pub const OTHER_KIND: &str = "other";
+
+/// Règles par défaut, vérifiées dans l'ordre : la première dont un mot-clé
+/// apparaît dans le message (sans tenir compte de la casse) donne la catégorie
+const DEFAULT_RULES: [(&str, &[&str]); 4] = [
+    ("unused", &["unused", "never used", "never read", "is declared but"]),
+    ("deprecated", &["deprecated", "obsolete"]),
+    (
+        "syntax",
+        &["syntax", "unexpected token", "unterminated", "parse error", "parsing error"],
+    ),
+    (
+        "type-error",
+        &[
+            "not assignable",
+            "mismatched types",
+            "type mismatch",
+            "incompatible type",
+            "does not exist on type",
+            "cannot find name",
+            "argument of type",
+        ],
+    ),
+];
+
+/// Règle de classification : une catégorie et les mots-clés qui la désignent
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub kind: String,
+    pub keywords: Vec<String>,
+}
+
+/// Fichier de règles de `--kind-rules`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+/// Classe les problèmes en catégories (`unused`, `deprecated`, ...) d'après des
+/// mots-clés de leur message
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    /// Règles dans leur ordre de priorité, mots-clés en minuscules
+    rules: Vec<Rule>,
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self::with_rules(Vec::new())
+    }
+}
+
+impl Classifier {
+    /// Classifieur utilisant les règles données, prioritaires sur les règles par défaut
+    pub fn with_rules(rules: Vec<Rule>) -> Self {
+        let defaults = DEFAULT_RULES.iter().map(|(kind, keywords)| Rule {
+            kind: kind.to_string(),
+            keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+        });
+        let rules = rules
+            .into_iter()
+            .chain(defaults)
+            .map(|rule| Rule {
+                keywords: rule.keywords.iter().map(|keyword| keyword.to_lowercase()).collect(),
+                ..rule
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Lit un fichier de règles TOML (tables `[[rule]]` avec `kind` et `keywords`)
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let file: RulesFile = toml::from_str(text).with_context(|| "Fichier de règles de classification invalide")?;
+        Ok(Self::with_rules(file.rules))
+    }
+
+    /// Catégorie du problème, `other` si aucune règle ne correspond
+    pub fn classify(&self, problem: &Problem) -> &str {
+        let message = problem.message.to_lowercase();
+        self.rules
+            .iter()
+            .find(|rule| rule.keywords.iter().any(|keyword| message.contains(keyword.as_str())))
+            .map_or(OTHER_KIND, |rule| rule.kind.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(message: &str) -> Problem {
+        Problem { message: message.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_classify_default_rules() {
+        let classifier = Classifier::default();
+        let kind = |message| classifier.classify(&problem(message)).to_string();
+
+        assert_eq!(kind("'foo' is declared but its value is never read."), "unused");
+        assert_eq!(kind("warning: unused variable: `x`"), "unused");
+        assert_eq!(kind("The method bar() from the type Foo is Deprecated"), "deprecated");
+        assert_eq!(kind("Unexpected token, expected \",\""), "syntax");
+        assert_eq!(kind("Type 'string' is not assignable to type 'number'."), "type-error");
+        assert_eq!(kind("Missing semicolon"), OTHER_KIND);
+    }
+
+    #[test]
+    fn test_classify_custom_rules_take_precedence() {
+        let classifier = Classifier::from_toml(
+            r#"
+            [[rule]]
+            kind = "style"
+            keywords = ["Semicolon", "unused import"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(classifier.classify(&problem("Missing semicolon")), "style");
+        assert_eq!(classifier.classify(&problem("unused import: `std::fs`")), "style");
+        assert_eq!(classifier.classify(&problem("unused variable")), "unused");
+    }
+
+    #[test]
+    fn test_rules_file_rejects_unknown_fields() {
+        let err = Classifier::from_toml("[[rule]]\nkind = \"a\"\nwords = [\"b\"]\n").unwrap_err();
+        assert_eq!(format!("{err}"), "Fichier de règles de classification invalide");
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+
+/// Critères de filtrage fournis en JSON par `--filter-json`.
+/// Les champs absents gardent la valeur des options de la ligne de commande.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterCriteria {
+    /// Termes à inclure (`--include`)
+    pub include: Vec<String>,
+    /// Termes à exclure (`--exclude`)
+    pub exclude: Vec<String>,
+    /// Nombre minimal de termes d'inclusion présents (`--include-min`)
+    pub include_min: Option<usize>,
+    /// Requête booléenne (`--query`)
+    pub query: Option<String>,
+    /// Comparaison insensible à la casse (`--ignore-case`)
+    pub ignore_case: bool,
+    /// Réduction des espaces avant la comparaison (`--normalize-whitespace`)
+    pub normalize_whitespace: bool,
+    /// Longueur minimale du message (`--min-msg-len`)
+    pub min_msg_len: Option<usize>,
+    /// Longueur maximale du message (`--max-msg-len`)
+    pub max_msg_len: Option<usize>,
+    /// Exclusion des messages vides (`--drop-empty`)
+    pub drop_empty: bool,
+    /// Messages vides uniquement (`--only-empty`)
+    pub only_empty: bool,
+    /// Termes de plusieurs mots recherchés mot à mot et dans l'ordre (`--loose-phrase`)
+    pub loose_phrase: bool,
+    /// Recherche des termes dans les N premiers caractères du message (`--message-prefix-len`)
+    pub message_prefix_len: Option<usize>,
+    /// Sévérités retenues, par nom (`--severity`)
+    pub severity: Vec<String>,
+    /// Catégories retenues (`--kind`)
+    pub kind: Vec<String>,
+    /// Expression régulière des fichiers retenus (`--resource-regex`)
+    pub resource_regex: Option<String>,
+    /// Expression régulière des fichiers exclus (`--exclude-resource-regex`)
+    pub exclude_resource_regex: Option<String>,
+    /// Comparaison des fichiers insensible à la casse (`--ignore-case-resource`)
+    pub ignore_case_resource: bool,
+    /// Exclusion des fichiers générés (`--ignore-generated`)
+    pub ignore_generated: bool,
+    /// Motifs supplémentaires de fichiers générés (`--generated-pattern`)
+    pub generated_pattern: Vec<String>,
+    /// Motifs de fichiers générés par défaut ignorés (`--no-default-generated`)
+    pub no_default_generated: bool,
+}
+
+impl FilterCriteria {
+    pub fn parse(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_criteria() {
+        let criteria = FilterCriteria::parse(
+            r#"{ "include": ["deprecated", "since"], "exclude": ["test"], "ignore_case": true, "max_msg_len": 80 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            criteria,
+            FilterCriteria {
+                include: vec!["deprecated".to_string(), "since".to_string()],
+                exclude: vec!["test".to_string()],
+                ignore_case: true,
+                max_msg_len: Some(80),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_criteria_all_fields() {
+        let criteria = FilterCriteria::parse(
+            r#"{ "only_empty": true, "loose_phrase": true, "message_prefix_len": 40, "severity": ["error"],
+                 "kind": ["type"], "resource_regex": "^src/", "exclude_resource_regex": "test",
+                 "ignore_case_resource": true, "ignore_generated": true, "generated_pattern": ["gen/"],
+                 "no_default_generated": true }"#,
+        )
+        .unwrap();
+
+        assert!(criteria.only_empty && criteria.loose_phrase && criteria.ignore_case_resource);
+        assert!(criteria.ignore_generated && criteria.no_default_generated);
+        assert_eq!(criteria.message_prefix_len, Some(40));
+        assert_eq!(criteria.severity, ["error"]);
+        assert_eq!(criteria.kind, ["type"]);
+        assert_eq!(criteria.resource_regex.as_deref(), Some("^src/"));
+        assert_eq!(criteria.exclude_resource_regex.as_deref(), Some("test"));
+        assert_eq!(criteria.generated_pattern, ["gen/"]);
+    }
+
+    #[test]
+    fn test_parse_criteria_rejects_unknown_fields() {
+        let err = FilterCriteria::parse(r#"{ "includes": ["deprecated"] }"#).unwrap_err();
+        assert!(err.to_string().contains("unknown field `includes`"));
+    }
+}
@@ -0,0 +1,466 @@
+use crate::problem::Problem;
+use clap::ValueEnum;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+
+/// Clé identifiant un problème lors de la déduplication
+#[derive(Debug)]
+pub struct DedupKey<'a> {
+    /// Fichier d'entrée, renseigné uniquement pour conserver les doublons entre fichiers
+    origin: Option<&'a Path>,
+    /// Fichier concerné, dans sa casse d'origine (celle de la première occurrence)
+    resource: Option<&'a str>,
+    /// Comparer les fichiers concernés sans tenir compte de la casse
+    resource_ci: bool,
+    line: Option<u32>,
+    message: &'a str,
+}
+
+impl DedupKey<'_> {
+    /// Caractères du fichier concerné tels qu'ils sont comparés
+    fn resource_chars(&self) -> Option<impl Iterator<Item = char> + '_> {
+        let resource_ci = self.resource_ci;
+        self.resource.map(move |resource| {
+            resource
+                .chars()
+                .flat_map(move |c| if resource_ci { EitherCase::Lower(c.to_lowercase()) } else { EitherCase::Same(Some(c)) })
+        })
+    }
+}
+
+/// Caractère éventuellement converti en minuscules
+enum EitherCase {
+    Lower(std::char::ToLowercase),
+    Same(Option<char>),
+}
+
+impl Iterator for EitherCase {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self {
+            EitherCase::Lower(chars) => chars.next(),
+            EitherCase::Same(c) => c.take(),
+        }
+    }
+}
+
+impl PartialEq for DedupKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let same_resource = match (self.resource_chars(), other.resource_chars()) {
+            (Some(a), Some(b)) => a.eq(b),
+            (None, None) => true,
+            _ => false,
+        };
+        self.origin == other.origin && self.line == other.line && self.message == other.message && same_resource
+    }
+}
+
+impl Eq for DedupKey<'_> {}
+
+impl Hash for DedupKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.origin.hash(state);
+        self.line.hash(state);
+        self.message.hash(state);
+        if let Some(chars) = self.resource_chars() {
+            chars.for_each(|c| c.hash(state));
+        }
+    }
+}
+
+/// Champs identifiant deux problèmes comme identiques
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum DistinctBy {
+    /// Même message
+    Message,
+    /// Même fichier et même message
+    ResourceMessage,
+    /// Même fichier, même ligne et même message
+    #[default]
+    Problem,
+}
+
+impl fmt::Display for DedupKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(resource) = self.resource {
+            write!(f, "{resource}:")?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "{line}:")?;
+        }
+        if self.resource.is_some() || self.line.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(origin) = self.origin {
+            write!(f, " ({})", origin.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Groupe de problèmes identiques rencontrés lors de la déduplication
+#[derive(Debug, PartialEq)]
+pub struct DuplicateGroup<'a> {
+    pub key: DedupKey<'a>,
+    /// Nombre d'occurrences, y compris celle qui est conservée
+    pub count: usize,
+}
+
+/// Construit les clés de déduplication des problèmes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyBuilder {
+    /// Inclure le fichier d'entrée dans la clé (`--keep-duplicates-across-files`)
+    pub per_file: bool,
+    /// Champs composant la clé
+    pub by: DistinctBy,
+    /// Rapprocher les fichiers concernés ne différant que par la casse (`--resource-ci`)
+    pub resource_ci: bool,
+}
+
+impl KeyBuilder {
+    pub fn key<'a>(&self, problem: &'a Problem) -> DedupKey<'a> {
+        DedupKey {
+            origin: if self.per_file { problem.origin.as_deref() } else { None },
+            resource: (self.by != DistinctBy::Message).then_some(problem.resource.as_str()),
+            resource_ci: self.resource_ci,
+            line: (self.by == DistinctBy::Problem).then_some(problem.start_line_number),
+            message: &problem.message,
+        }
+    }
+
+    /// Nombre de problèmes distincts selon la clé
+    pub fn count_distinct(&self, problems: &[&Problem]) -> usize {
+        problems.iter().map(|problem| self.key(problem)).collect::<HashSet<_>>().len()
+    }
+
+    /// Fichiers concernés distincts, dans la casse de leur première occurrence
+    pub fn distinct_resources<'a>(&self, problems: &[&'a Problem]) -> Vec<&'a str> {
+        let mut seen = HashSet::new();
+        problems
+            .iter()
+            .filter(|problem| {
+                seen.insert(DedupKey {
+                    origin: None,
+                    resource: Some(&problem.resource),
+                    resource_ci: self.resource_ci,
+                    line: None,
+                    message: "",
+                })
+            })
+            .map(|problem| problem.resource.as_str())
+            .collect()
+    }
+
+    /// Fusionne les problèmes d'une même ligne d'un même fichier (`--merge-same-line`) :
+    /// le premier problème de la ligne est conservé, avec les messages de tous
+    /// séparés par `; ` et la sévérité la plus grave. L'ordre des premières
+    /// occurrences est conservé.
+    pub fn merge_same_line(&self, problems: &[&Problem]) -> Vec<Problem> {
+        let mut rows: HashMap<DedupKey, usize> = HashMap::new();
+        let mut merged: Vec<Problem> = Vec::new();
+        for problem in problems {
+            let key = DedupKey {
+                origin: None,
+                resource: Some(&problem.resource),
+                resource_ci: self.resource_ci,
+                line: Some(problem.start_line_number),
+                message: "",
+            };
+            match rows.get(&key) {
+                Some(&row) => {
+                    let row = &mut merged[row];
+                    row.message = format!("{}; {}", row.message, problem.message);
+                    row.severity = row.severity.max(problem.severity);
+                }
+                None => {
+                    rows.insert(key, merged.len());
+                    merged.push((*problem).clone());
+                }
+            }
+        }
+        merged
+    }
+
+    /// Supprime les doublons en gardant la première occurrence, et renvoie les
+    /// groupes de doublons dans l'ordre de leur première occurrence
+    pub fn dedup_groups<'a>(&self, problems: &mut Vec<&'a Problem>) -> Vec<DuplicateGroup<'a>> {
+        let mut counts: HashMap<DedupKey<'a>, usize> = HashMap::new();
+        let mut order = Vec::new();
+        problems.retain(|problem| {
+            let key = self.key(problem);
+            let count = counts.entry(key).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                order.push(self.key(problem));
+                true
+            } else {
+                false
+            }
+        });
+
+        order
+            .into_iter()
+            .filter_map(|key| {
+                let count = counts[&key];
+                (count > 1).then_some(DuplicateGroup { key, count })
+            })
+            .collect()
+    }
+
+    /// Problèmes dont la clé apparaît au moins deux fois (`--duplicates-only`),
+    /// regroupés par clé dans l'ordre de leur première occurrence
+    pub fn duplicate_groups<'a>(&self, problems: &[&'a Problem]) -> Vec<Vec<&'a Problem>> {
+        let mut rows: HashMap<DedupKey<'a>, usize> = HashMap::new();
+        let mut groups: Vec<Vec<&'a Problem>> = Vec::new();
+        for problem in problems {
+            match rows.get(&self.key(problem)) {
+                Some(&row) => groups[row].push(problem),
+                None => {
+                    rows.insert(self.key(problem), groups.len());
+                    groups.push(vec![problem]);
+                }
+            }
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Supprime les doublons comme `dedup_groups`, la table des problèmes déjà vus
+    /// étant indexée par l'empreinte `u64` de leur clé (`--hashed-dedup`) plutôt que
+    /// par la clé elle-même. Le résultat est identique : deux clés de même empreinte
+    /// sont comparées, et seules les clés en collision sont conservées en entier.
+    pub fn dedup_groups_hashed<'a>(&self, problems: &mut Vec<&'a Problem>) -> Vec<DuplicateGroup<'a>> {
+        self.dedup_groups_with(problems, |key| {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+
+    fn dedup_groups_with<'a>(
+        &self,
+        problems: &mut Vec<&'a Problem>,
+        fingerprint: impl Fn(&DedupKey) -> u64,
+    ) -> Vec<DuplicateGroup<'a>> {
+        // Première occurrence de chaque clé distincte et nombre d'occurrences
+        let mut order: Vec<(&'a Problem, usize)> = Vec::new();
+        let mut by_fingerprint: HashMap<u64, usize> = HashMap::new();
+        let mut collisions: HashMap<DedupKey<'a>, usize> = HashMap::new();
+        problems.retain(|problem| {
+            let key = self.key(problem);
+            let index = match by_fingerprint.get(&fingerprint(&key)) {
+                Some(&index) if self.key(order[index].0) == key => Some(index),
+                Some(_) => collisions.get(&key).copied().or_else(|| {
+                    collisions.insert(key, order.len());
+                    None
+                }),
+                None => {
+                    by_fingerprint.insert(fingerprint(&key), order.len());
+                    None
+                }
+            };
+            match index {
+                Some(index) => {
+                    order[index].1 += 1;
+                    false
+                }
+                None => {
+                    order.push((problem, 1));
+                    true
+                }
+            }
+        });
+
+        order
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(problem, count)| DuplicateGroup { key: self.key(problem), count })
+            .collect()
+    }
+}
+
+/// Nombre de problèmes retirés par la déduplication
+pub fn removed_count(groups: &[DuplicateGroup]) -> usize {
+    groups.iter().map(|group| group.count - 1).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Severity;
+    use std::path::PathBuf;
+
+    fn problem(origin: &str, line: u32) -> Problem {
+        Problem {
+            resource: "src/file.ts".to_string(),
+            start_line_number: line,
+            message: "duplicated".to_string(),
+            origin: Some(PathBuf::from(origin)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dedup_across_files() {
+        let problems = [problem("a.json", 1), problem("b.json", 1), problem("a.json", 2)];
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+
+        let removed = removed_count(&KeyBuilder::default().dedup_groups(&mut refs));
+        assert_eq!(removed, 1);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].origin, Some(PathBuf::from("a.json")));
+    }
+
+    #[test]
+    fn test_dedup_keeps_duplicates_across_files() {
+        let problems = [problem("a.json", 1), problem("b.json", 1), problem("a.json", 1)];
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+
+        let removed = removed_count(&KeyBuilder { per_file: true, ..Default::default() }.dedup_groups(&mut refs));
+        assert_eq!(removed, 1);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[1].origin, Some(PathBuf::from("b.json")));
+    }
+
+    #[test]
+    fn test_dedup_groups_records_sizes() {
+        let problems = [problem("a.json", 2), problem("a.json", 1), problem("b.json", 2), problem("c.json", 2)];
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = KeyBuilder::default().dedup_groups(&mut refs);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].key.to_string(), "src/file.ts:2: duplicated");
+    }
+
+    /// Grand jeu de problèmes synthétiques comportant de nombreux doublons
+    fn synthetic_problems(n: usize) -> Vec<Problem> {
+        (0..n)
+            .map(|i| Problem {
+                resource: format!("src/file{}.ts", i % 97),
+                start_line_number: (i % 13) as u32,
+                message: format!("message {}", i % 7),
+                origin: Some(PathBuf::from(["a.json", "b.json"][i % 2])),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dedup_groups_hashed_matches_keys() {
+        let problems = synthetic_problems(50_000);
+        for key_builder in [
+            KeyBuilder::default(),
+            KeyBuilder { per_file: true, ..Default::default() },
+            KeyBuilder { by: DistinctBy::Message, ..Default::default() },
+        ] {
+            let mut expected: Vec<&Problem> = problems.iter().collect();
+            let expected_groups = key_builder.dedup_groups(&mut expected);
+            let mut refs: Vec<&Problem> = problems.iter().collect();
+            let groups = key_builder.dedup_groups_hashed(&mut refs);
+
+            assert!(refs.len() < problems.len());
+            assert_eq!(refs.len(), expected.len());
+            assert!(refs.iter().zip(&expected).all(|(a, b)| std::ptr::eq(*a, *b)));
+            assert_eq!(groups, expected_groups);
+        }
+    }
+
+    #[test]
+    fn test_dedup_groups_hashed_collisions() {
+        // Avec une empreinte constante, toutes les clés sont en collision
+        let problems = synthetic_problems(2_000);
+        let mut expected: Vec<&Problem> = problems.iter().collect();
+        let expected_groups = KeyBuilder::default().dedup_groups(&mut expected);
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+        let groups = KeyBuilder::default().dedup_groups_with(&mut refs, |_| 0);
+
+        assert_eq!(refs.len(), expected.len());
+        assert_eq!(groups, expected_groups);
+    }
+
+    #[test]
+    fn test_duplicate_groups() {
+        let problems = [problem("a.json", 1), problem("a.json", 2), problem("b.json", 3), problem("b.json", 1), problem("c.json", 1)];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = KeyBuilder::default().duplicate_groups(&refs);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 3);
+        assert!(groups[0].iter().all(|problem| problem.start_line_number == 1));
+        assert_eq!(groups[0][2].origin, Some(PathBuf::from("c.json")));
+
+        let groups = KeyBuilder { per_file: true, ..Default::default() }.duplicate_groups(&refs);
+        assert!(groups.is_empty());
+        assert_eq!(KeyBuilder { by: DistinctBy::Message, ..Default::default() }.duplicate_groups(&refs)[0].len(), 5);
+    }
+
+    #[test]
+    fn test_merge_same_line() {
+        let with = |line, message: &str, severity| Problem {
+            message: message.to_string(),
+            severity,
+            ..problem("a.json", line)
+        };
+        let problems = [
+            with(3, "first", Some(Severity::Warning)),
+            with(4, "other line", None),
+            with(3, "second", Some(Severity::Error)),
+            Problem { resource: "SRC/FILE.TS".to_string(), ..with(3, "third", None) },
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let merged = KeyBuilder::default().merge_same_line(&refs);
+        let rows: Vec<(&str, Option<Severity>)> = merged.iter().map(|p| (p.message.as_str(), p.severity)).collect();
+        assert_eq!(
+            rows,
+            [("first; second", Some(Severity::Error)), ("other line", None), ("third", None)]
+        );
+
+        let merged = KeyBuilder { resource_ci: true, ..Default::default() }.merge_same_line(&refs);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].message, "first; second; third");
+    }
+
+    #[test]
+    fn test_dedup_resource_case_insensitive() {
+        let mut upper = problem("a.json", 1);
+        upper.resource = "Src/File.ts".to_string();
+        let problems = [upper, problem("a.json", 1), problem("a.json", 2)];
+
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+        KeyBuilder::default().dedup_groups(&mut refs);
+        assert_eq!(refs.len(), 3);
+
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+        let groups = KeyBuilder { resource_ci: true, ..Default::default() }.dedup_groups(&mut refs);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].resource, "Src/File.ts");
+        assert_eq!(groups[0].key.to_string(), "Src/File.ts:1: duplicated");
+
+        let refs: Vec<&Problem> = problems.iter().collect();
+        assert_eq!(KeyBuilder::default().distinct_resources(&refs), vec!["Src/File.ts", "src/file.ts"]);
+        assert_eq!(KeyBuilder { resource_ci: true, ..Default::default() }.distinct_resources(&refs), vec!["Src/File.ts"]);
+    }
+
+    #[test]
+    fn test_count_distinct_by_key() {
+        let mut other_file = problem("a.json", 1);
+        other_file.resource = "src/other.ts".to_string();
+        let problems = [problem("a.json", 1), problem("a.json", 1), problem("a.json", 2), other_file];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let count = |by| KeyBuilder { by, ..Default::default() }.count_distinct(&refs);
+        assert_eq!(count(DistinctBy::Problem), 3);
+        assert_eq!(count(DistinctBy::ResourceMessage), 2);
+        assert_eq!(count(DistinctBy::Message), 1);
+
+        let key = KeyBuilder { by: DistinctBy::Message, ..Default::default() }.key(&problems[0]);
+        assert_eq!(key.to_string(), "duplicated");
+    }
+}
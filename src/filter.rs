@@ -0,0 +1,483 @@
+use crate::normalize;
+use crate::problem::{Problem, Severity};
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Motifs de chemins identifiant les fichiers générés, utilisés par `--ignore-generated`
+pub const DEFAULT_GENERATED_PATTERNS: [&str; 6] = ["generated", "node_modules/", "/target/", "/dist/", ".d.ts", ".min.js"];
+
+/// Fichier de motifs d'exclusion lu par défaut dans le dossier courant
+pub const DEFAULT_IGNORE_FILE: &str = ".vpfignore";
+
+/// Lit des motifs de chemins au format `.gitignore` (`.vpfignore`, `--ignore-file`),
+/// négations `!motif` comprises, éventuellement sans tenir compte de la casse
+pub fn ignore_patterns(text: &str, ignore_case: bool) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    builder.case_insensitive(ignore_case).with_context(|| "Motifs d'exclusion invalides")?;
+    for line in text.lines() {
+        builder.add_line(None, line).with_context(|| format!("Motif d'exclusion invalide: {line}"))?;
+    }
+    builder.build().with_context(|| "Motifs d'exclusion invalides")
+}
+
+/// Indique si le fichier est exclu par les motifs. Le chemin est comparé sans
+/// son préfixe `file://` ni sa racine : un motif commençant par `/` désigne le
+/// début du chemin, les autres s'appliquent à n'importe quel niveau.
+fn is_ignored(patterns: &Gitignore, resource: &str) -> bool {
+    let path = resource.strip_prefix("file://").unwrap_or(resource).trim_start_matches('/');
+    !path.is_empty() && patterns.matched_path_or_any_parents(path, false).is_ignore()
+}
+
+/// Préparation des messages et des termes avant comparaison
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextOptions {
+    pub ignore_case: bool,
+    pub normalize_whitespace: bool,
+}
+
+impl TextOptions {
+    /// Prépare un message ou un terme pour la comparaison (casse, espaces)
+    pub fn prepare(&self, text: &str) -> String {
+        let text = if self.ignore_case {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        };
+
+        if self.normalize_whitespace {
+            normalize::collapse_whitespace(&text)
+        } else {
+            text
+        }
+    }
+}
+
+/// Groupes de synonymes (`--synonyms`) : un terme appartenant à un groupe
+/// correspond à n'importe quel terme du groupe
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Synonyms(Vec<Vec<String>>);
+
+impl Synonyms {
+    /// Lit un fichier TOML associant un terme à ses synonymes (`unused = ["never used", "is not used"]`)
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let map: BTreeMap<String, Vec<String>> =
+            toml::from_str(text).with_context(|| "Fichier de synonymes invalide (tableaux de termes attendus)")?;
+        Ok(Self(
+            map.into_iter()
+                .map(|(term, synonyms)| std::iter::once(term).chain(synonyms).collect())
+                .collect(),
+        ))
+    }
+
+    /// Le terme suivi de ses synonymes, sans doublon ; les groupes sont reconnus sans tenir compte de la casse
+    pub fn expand(&self, term: &str) -> Vec<String> {
+        let mut terms = vec![term.to_string()];
+        let groups = self
+            .0
+            .iter()
+            .filter(|group| group.iter().any(|member| member.trim().eq_ignore_ascii_case(term.trim())));
+        for member in groups.flatten() {
+            if !terms.iter().any(|known| known.eq_ignore_ascii_case(member)) {
+                terms.push(member.clone());
+            }
+        }
+        terms
+    }
+}
+
+/// Critères de filtrage d'un problème. Les termes sont préparés une seule fois
+/// à la construction, et non pour chaque problème.
+#[derive(Debug, Default)]
+pub struct ProblemFilter<'a> {
+    pub text: TextOptions,
+    /// Termes d'inclusion préparés, chacun avec ses synonymes (il suffit que l'un d'eux soit présent)
+    pub include: Vec<Vec<String>>,
+    /// Termes d'exclusion préparés, synonymes compris
+    pub exclude: Vec<String>,
+    /// Nombre minimal de termes d'inclusion présents (tous si absent)
+    pub include_min: Option<usize>,
+    /// Un terme de plusieurs mots correspond si ses mots apparaissent dans l'ordre
+    pub loose_phrase: bool,
+    pub min_msg_len: Option<usize>,
+    pub max_msg_len: Option<usize>,
+    /// Les termes ne sont recherchés que dans les N premiers caractères du message
+    pub message_prefix_len: Option<usize>,
+    /// Motifs des fichiers générés à exclure, en minuscules avec `ignore_case_resource`
+    /// (voir [`ProblemFilter::with_generated_patterns`])
+    pub generated_patterns: Vec<Cow<'a, str>>,
+    /// Comparer les motifs des fichiers générés sans tenir compte de la casse (`--ignore-case-resource`).
+    /// Les expressions régulières et les motifs `.gitignore` sont compilés en conséquence par l'appelant.
+    pub ignore_case_resource: bool,
+    pub drop_empty: bool,
+    pub only_empty: bool,
+    /// Sévérités acceptées (toutes si vide)
+    pub severities: Vec<Severity>,
+    /// Expression régulière que le fichier doit vérifier
+    pub resource_regex: Option<&'a Regex>,
+    /// Expression régulière que le fichier ne doit pas vérifier
+    pub exclude_resource_regex: Option<&'a Regex>,
+    /// Motifs `.gitignore` des fichiers à exclure
+    pub ignored: Option<&'a Gitignore>,
+}
+
+impl<'a> ProblemFilter<'a> {
+    /// Construit un filtre à partir des termes bruts, étendus à leurs synonymes
+    /// et préparés selon `text`
+    pub fn new(include: &[String], exclude: &[String], text: TextOptions, synonyms: &Synonyms) -> Self {
+        let expand = |term: &String| synonyms.expand(term).into_iter().map(move |term| text.prepare(&term));
+        ProblemFilter {
+            text,
+            include: include.iter().map(|term| expand(term).collect()).collect(),
+            exclude: exclude.iter().flat_map(expand).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Motifs des fichiers générés à exclure, mis en minuscules une seule fois
+    /// sans tenir compte de la casse
+    pub fn with_generated_patterns(self, patterns: Vec<&'a str>, ignore_case_resource: bool) -> Self {
+        let generated_patterns = patterns
+            .into_iter()
+            .map(|pattern| match ignore_case_resource {
+                true => Cow::Owned(pattern.to_lowercase()),
+                false => Cow::Borrowed(pattern),
+            })
+            .collect();
+        ProblemFilter { generated_patterns, ignore_case_resource, ..self }
+    }
+
+    /// Partie du message dans laquelle chercher les termes (les N premiers caractères
+    /// avec `message_prefix_len`), préparée selon `text`
+    fn searched_text(&self, message: &str) -> String {
+        match self.message_prefix_len {
+            Some(len) => {
+                let end = message.char_indices().nth(len).map_or(message.len(), |(pos, _)| pos);
+                self.text.prepare(&message[..end])
+            }
+            None => self.text.prepare(message),
+        }
+    }
+
+    /// Indique si le message (préparé) contient l'un des termes d'inclusion (préparés)
+    /// d'un groupe, ou ses mots dans l'ordre avec `loose_phrase`
+    fn contains_term(&self, message: &str, terms: &[String]) -> bool {
+        terms.iter().any(|term| {
+            if self.loose_phrase {
+                contains_words_in_order(message, term)
+            } else {
+                message.contains(term.as_str())
+            }
+        })
+    }
+
+    /// Nombre de problèmes contenant chaque terme d'inclusion, dans l'ordre des termes
+    pub fn term_counts(&self, problems: &[&Problem]) -> Vec<usize> {
+        let messages: Vec<String> = problems.iter().map(|problem| self.searched_text(&problem.message)).collect();
+
+        self.include
+            .iter()
+            .map(|term| messages.iter().filter(|message| self.contains_term(message, term)).count())
+            .collect()
+    }
+
+    /// Filtre un problème selon les critères d'inclusion et d'exclusion
+    /// (`run_app` utilise `decide` pour compter les rejets de `--profile-filter`)
+    pub fn matches(&self, problem: &Problem) -> bool {
+        self.decide(problem) == FilterDecision::Kept
+    }
+
+    /// Évalue les critères du filtre dans l'ordre de `Criterion::ALL` et indique le
+    /// premier qui rejette le problème (jamais `Query` ni `Kind`, évalués par l'appelant)
+    pub fn decide(&self, problem: &Problem) -> FilterDecision {
+        let message = self.searched_text(&problem.message);
+
+        // Vérifier que tous les termes d'inclusion sont présents (ou au moins K avec --include-min)
+        let all_include_present = match self.include_min {
+            Some(min) => self.include.iter().filter(|term| self.contains_term(&message, term)).count() >= min,
+            None => self.include.iter().all(|term| self.contains_term(&message, term)),
+        };
+        if !all_include_present {
+            return FilterDecision::Rejected(Criterion::Include);
+        }
+
+        // Vérifier qu'aucun terme d'exclusion n'est présent
+        if self.exclude.iter().any(|term| message.contains(term.as_str())) {
+            return FilterDecision::Rejected(Criterion::Exclude);
+        }
+
+        // Vérifier la longueur du message d'origine (avant troncature), en caractères
+        let length = problem.message.chars().count();
+        let length_in_bounds = self.min_msg_len.is_none_or(|min| length >= min)
+            && self.max_msg_len.is_none_or(|max| length <= max);
+        if !length_in_bounds {
+            return FilterDecision::Rejected(Criterion::Length);
+        }
+
+        // Vérifier que le fichier n'est pas un fichier généré
+        let generated = if self.generated_patterns.is_empty() {
+            false
+        } else if self.ignore_case_resource {
+            let resource = problem.resource.to_lowercase();
+            self.generated_patterns.iter().any(|pattern| resource.contains(pattern.as_ref()))
+        } else {
+            self.generated_patterns.iter().any(|pattern| problem.resource.contains(pattern.as_ref()))
+        };
+        if generated {
+            return FilterDecision::Rejected(Criterion::Generated);
+        }
+
+        // Vérifier le fichier concerné (--resource-regex, --exclude-resource-regex)
+        let resource_ok = self.resource_regex.is_none_or(|regex| regex.is_match(&problem.resource))
+            && !self.exclude_resource_regex.is_some_and(|regex| regex.is_match(&problem.resource))
+            && !self.ignored.is_some_and(|patterns| is_ignored(patterns, &problem.resource));
+        if !resource_ok {
+            return FilterDecision::Rejected(Criterion::Resource);
+        }
+
+        // Messages vides (--drop-empty, --only-empty)
+        let empty = problem.message.trim().is_empty();
+        if (self.drop_empty && empty) || (self.only_empty && !empty) {
+            return FilterDecision::Rejected(Criterion::Empty);
+        }
+
+        // Vérifier la sévérité (--severity)
+        let severity_ok = self.severities.is_empty()
+            || problem.severity.is_some_and(|severity| self.severities.contains(&severity));
+        if !severity_ok {
+            return FilterDecision::Rejected(Criterion::Severity);
+        }
+
+        FilterDecision::Kept
+    }
+}
+
+/// Critère de filtrage, dans l'ordre où les critères sont évalués
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    /// Requête `--query`, évaluée avant le filtre
+    Query,
+    /// Termes d'inclusion (`--include`, `--include-min`)
+    Include,
+    /// Termes d'exclusion (`--exclude`)
+    Exclude,
+    /// Longueur du message (`--min-msg-len`, `--max-msg-len`)
+    Length,
+    /// Fichiers générés (`--ignore-generated`)
+    Generated,
+    /// Chemin du fichier (`--resource-regex`, `--exclude-resource-regex`, `.vpfignore`)
+    Resource,
+    /// Messages vides (`--drop-empty`, `--only-empty`)
+    Empty,
+    /// Sévérité (`--severity`)
+    Severity,
+    /// Catégorie (`--kind`), évaluée après le filtre
+    Kind,
+}
+
+impl Criterion {
+    pub const ALL: [Criterion; 9] = [
+        Criterion::Query,
+        Criterion::Include,
+        Criterion::Exclude,
+        Criterion::Length,
+        Criterion::Generated,
+        Criterion::Resource,
+        Criterion::Empty,
+        Criterion::Severity,
+        Criterion::Kind,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Criterion::Query => "requête",
+            Criterion::Include => "termes d'inclusion",
+            Criterion::Exclude => "termes d'exclusion",
+            Criterion::Length => "longueur du message",
+            Criterion::Generated => "fichiers générés",
+            Criterion::Resource => "chemin du fichier",
+            Criterion::Empty => "messages vides",
+            Criterion::Severity => "sévérité",
+            Criterion::Kind => "catégorie",
+        }
+    }
+}
+
+/// Résultat du filtrage d'un problème
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterDecision {
+    Kept,
+    /// Rejeté par le premier critère non vérifié
+    Rejected(Criterion),
+}
+
+/// Nombre de problèmes rejetés par chaque critère (`--profile-filter`)
+#[derive(Debug, Default)]
+pub struct FilterFunnel {
+    /// Rejets par critère, dans l'ordre de `Criterion::ALL`
+    pub rejected: [usize; Criterion::ALL.len()],
+}
+
+impl FilterFunnel {
+    pub fn record(&mut self, decision: FilterDecision) {
+        if let FilterDecision::Rejected(criterion) = decision {
+            self.rejected[criterion as usize] += 1;
+        }
+    }
+
+    pub fn rejected_by(&self, criterion: Criterion) -> usize {
+        self.rejected[criterion as usize]
+    }
+}
+
+/// Indique si les mots de `phrase` apparaissent dans `text` dans le même ordre,
+/// éventuellement séparés par d'autres caractères
+fn contains_words_in_order(text: &str, phrase: &str) -> bool {
+    let mut rest = text;
+    for word in phrase.split_whitespace() {
+        match rest.find(word) {
+            Some(pos) => rest = &rest[pos + word.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(message: &str) -> Problem {
+        Problem { message: message.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_contains_words_in_order() {
+        assert!(contains_words_in_order("cannot find symbol foo", "cannot symbol"));
+        assert!(contains_words_in_order("cannot find symbol foo", "cannot find symbol"));
+        assert!(!contains_words_in_order("symbol foo cannot be found", "cannot symbol"));
+        assert!(!contains_words_in_order("cannot find", "cannot cannot"));
+        assert!(contains_words_in_order("anything", ""));
+    }
+
+    #[test]
+    fn test_new_prepares_terms_once() {
+        let text = TextOptions { ignore_case: true, normalize_whitespace: true };
+        let filter = ProblemFilter::new(&["Deprecated  API".to_string()], &["TEST".to_string()], text, &Synonyms::default());
+        assert_eq!(filter.include, vec![vec!["deprecated api"]]);
+        assert_eq!(filter.exclude, vec!["test"]);
+
+        assert!(filter.matches(&problem("DEPRECATED\tapi call")));
+        assert!(!filter.matches(&problem("deprecated api in test")));
+    }
+
+    #[test]
+    fn test_synonym_group_expansion() {
+        let synonyms = Synonyms::from_toml(r#"unused = ["never used", "is not used"]"#).unwrap();
+        assert_eq!(synonyms.expand("Never Used"), ["Never Used", "unused", "is not used"]);
+        assert_eq!(synonyms.expand("deprecated"), ["deprecated"]);
+
+        let text = TextOptions { ignore_case: true, ..Default::default() };
+        let filter = ProblemFilter::new(&["unused".to_string()], &[], text, &synonyms);
+        assert!(filter.matches(&problem("'x' is never used")));
+        assert!(filter.matches(&problem("variable is not used")));
+        assert!(filter.matches(&problem("unused import")));
+        assert!(!filter.matches(&problem("'x' is deprecated")));
+
+        let filter = ProblemFilter::new(&[], &["is not used".to_string()], text, &synonyms);
+        assert!(!filter.matches(&problem("unused import")));
+        assert!(filter.matches(&problem("'x' is deprecated")));
+
+        assert!(Synonyms::from_toml("unused = \"never used\"").is_err());
+    }
+
+    #[test]
+    fn test_message_prefix_len() {
+        let filter = ProblemFilter {
+            message_prefix_len: Some(10),
+            ..ProblemFilter::new(&["deprecated".to_string()], &["stack".to_string()], TextOptions::default(), &Synonyms::default())
+        };
+        assert!(filter.matches(&problem("deprecated: see stack trace")));
+        assert!(!filter.matches(&problem("the API is deprecated")));
+
+        // La découpe se fait sur les caractères, pas les octets
+        let filter = ProblemFilter {
+            message_prefix_len: Some(3),
+            ..ProblemFilter::new(&["éé".to_string()], &[], TextOptions::default(), &Synonyms::default())
+        };
+        assert!(filter.matches(&problem("éééé")));
+        assert!(!filter.matches(&problem("aaéé")));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let patterns = ignore_patterns("# fichiers générés\n*.d.ts\n!legacy.d.ts\n/vendor/\ngenerated/\n", false).unwrap();
+        let filter = ProblemFilter { ignored: Some(&patterns), ..Default::default() };
+        let matches = |resource: &str| filter.matches(&Problem { resource: resource.to_string(), ..Default::default() });
+
+        assert!(!matches("src/types/index.d.ts"));
+        assert!(!matches("/vendor/lib/a.js"));
+        assert!(matches("src/vendor/a.js"));
+        assert!(!matches("file:///src/generated/api.ts"));
+        assert!(matches("src/types/legacy.d.ts"));
+        assert!(matches("src/main.ts"));
+
+        assert!(ignore_patterns("src/{a,b", false).is_err());
+    }
+
+    #[test]
+    fn test_ignore_case_resource() {
+        let resource = |resource: &str| Problem { resource: resource.to_string(), ..Default::default() };
+        let sensitive = ProblemFilter::default().with_generated_patterns(vec!["/Dist/"], false);
+        assert!(!sensitive.matches(&resource("app/Dist/main.js")));
+        assert!(sensitive.matches(&resource("app/DIST/main.js")));
+
+        let insensitive = ProblemFilter::default().with_generated_patterns(vec!["/Dist/"], true);
+        assert_eq!(insensitive.generated_patterns, ["/dist/"]);
+        assert!(!insensitive.matches(&resource("app/DIST/main.js")));
+        assert!(insensitive.matches(&resource("app/distrib/main.js")));
+
+        let patterns = ignore_patterns("Vendor/\n*.D.TS\n", true).unwrap();
+        let filter = ProblemFilter { ignored: Some(&patterns), ..Default::default() };
+        assert!(!filter.matches(&resource("src/vendor/a.js")));
+        assert!(!filter.matches(&resource("src/types/index.d.ts")));
+        assert!(filter.matches(&resource("src/main.ts")));
+
+        let patterns = ignore_patterns("Vendor/\n", false).unwrap();
+        let filter = ProblemFilter { ignored: Some(&patterns), ..Default::default() };
+        assert!(filter.matches(&resource("src/vendor/a.js")));
+    }
+
+    #[test]
+    fn test_decide_reports_first_rejecting_criterion() {
+        let filter = ProblemFilter {
+            min_msg_len: Some(5),
+            severities: vec![Severity::Error],
+            ..ProblemFilter::new(&["a".to_string()], &["b".to_string()], TextOptions::default(), &Synonyms::default())
+        };
+        let error = |message: &str| Problem { severity: Some(Severity::Error), ..problem(message) };
+
+        assert_eq!(filter.decide(&error("c c c c")), FilterDecision::Rejected(Criterion::Include));
+        assert_eq!(filter.decide(&error("a b a b")), FilterDecision::Rejected(Criterion::Exclude));
+        assert_eq!(filter.decide(&error("a")), FilterDecision::Rejected(Criterion::Length));
+        assert_eq!(filter.decide(&problem("a a a a")), FilterDecision::Rejected(Criterion::Severity));
+        assert_eq!(filter.decide(&error("a a a a")), FilterDecision::Kept);
+
+        let mut funnel = FilterFunnel::default();
+        for message in ["c", "a b a b", "a", "a a a a", "c c"] {
+            funnel.record(filter.decide(&error(message)));
+        }
+        assert_eq!(funnel.rejected, [0, 2, 1, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(funnel.rejected_by(Criterion::Include), 2);
+    }
+
+    #[test]
+    fn test_term_counts() {
+        let filter = ProblemFilter::new(&["a".to_string(), "b".to_string()], &[], TextOptions::default(), &Synonyms::default());
+        let problems = [problem("a b"), problem("a"), problem("c")];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        assert_eq!(filter.term_counts(&refs), vec![2, 1]);
+    }
+}
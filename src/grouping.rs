@@ -0,0 +1,521 @@
+use crate::classify::Classifier;
+use crate::problem::{Problem, Severity};
+use crate::tree;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashMap;
+use tabled::Table;
+use tabled::builder::Builder;
+
+/// Clé utilisée pour les problèmes sans valeur pour le champ de regroupement
+const NONE_KEY: &str = "(none)";
+
+/// Champ utilisé par `--group-by`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum GroupBy {
+    /// Outil à l'origine du problème (champ `source`)
+    Source,
+    /// Premier dossier du chemin du fichier concerné
+    Module,
+    /// Code du diagnostic (champ `code`, ou sa `value` s'il s'agit d'un objet)
+    Code,
+    /// Nom du fichier concerné, quel que soit son dossier
+    Basename,
+    /// Tranche de numéros de ligne (1-50, 51-100, ... selon `--bucket-size`)
+    LineBucket,
+    /// Catégorie du problème selon les règles de classification (`--classify`, `--kind-rules`)
+    Kind,
+}
+
+impl GroupBy {
+    /// En-tête de la colonne contenant la clé de regroupement
+    pub fn header(&self) -> &'static str {
+        match self {
+            GroupBy::Source => "Source",
+            GroupBy::Module => "Module",
+            GroupBy::Code => "Code",
+            GroupBy::Basename => "Fichier",
+            GroupBy::LineBucket => "Lignes",
+            GroupBy::Kind => "Catégorie",
+        }
+    }
+
+    /// Clé de regroupement d'un problème, `bucket_size` étant la largeur des tranches de lignes
+    /// et `classifier` les règles donnant la catégorie
+    pub fn key(&self, problem: &Problem, bucket_size: u32, classifier: Option<&Classifier>) -> String {
+        match self {
+            GroupBy::Source => problem.source().unwrap_or(NONE_KEY).to_string(),
+            GroupBy::Module => match tree::path_segments(&problem.resource).as_slice() {
+                [module, _, ..] => module.to_string(),
+                _ => NONE_KEY.to_string(),
+            },
+            GroupBy::Code => problem.code().unwrap_or_else(|| NONE_KEY.to_string()),
+            GroupBy::Basename => tree::basename(&problem.resource).unwrap_or(NONE_KEY).to_string(),
+            GroupBy::LineBucket => line_bucket(problem.start_line_number, bucket_size),
+            GroupBy::Kind => classifier.map_or(NONE_KEY, |classifier| classifier.classify(problem)).to_string(),
+        }
+    }
+}
+
+/// Tranche `début-fin` de largeur `size` contenant la ligne (1-based, la ligne 0 allant dans la première)
+fn line_bucket(line: u32, size: u32) -> String {
+    let size = size.max(1);
+    let start = line.saturating_sub(1) / size * size + 1;
+    format!("{start}-{}", start + (size - 1))
+}
+
+/// Compare deux clés de groupe : les nombres en tête de clé (tranches `51-100`,
+/// codes numériques) sont comparés numériquement, le reste dans l'ordre alphabétique
+fn compare_keys(a: &str, b: &str) -> std::cmp::Ordering {
+    let number = |key: &str| {
+        let end = key.find(|c: char| !c.is_ascii_digit()).unwrap_or(key.len());
+        key[..end].parse::<u64>().ok()
+    };
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.cmp(b)),
+        _ => a.cmp(b),
+    }
+}
+
+/// Ordre des groupes utilisé par `--group-sort`
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum GroupSort {
+    /// Par nombre décroissant, puis par clé
+    #[default]
+    Count,
+    /// Par clé, dans l'ordre alphabétique (les nombres en tête de clé dans l'ordre numérique)
+    Name,
+}
+
+impl GroupSort {
+    /// Trie les groupes. `reverse` inverse le critère principal ; à nombre égal,
+    /// les groupes restent triés par clé croissante.
+    pub fn sort(&self, groups: &mut [Group], reverse: bool) {
+        groups.sort_by(|a, b| {
+            let ordering = match self {
+                GroupSort::Count => b.count.cmp(&a.count),
+                GroupSort::Name => compare_keys(&a.key, &b.key),
+            };
+            let ordering = if reverse { ordering.reverse() } else { ordering };
+            ordering.then_with(|| compare_keys(&a.key, &b.key))
+        });
+    }
+}
+
+/// Groupe de problèmes partageant une même clé
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Group {
+    pub key: String,
+    pub count: usize,
+}
+
+/// Regroupe les problèmes selon la clé calculée par `key_fn`.
+/// Les groupes sont triés par nombre décroissant, puis par clé.
+pub fn group_by<F>(problems: &[&Problem], key_fn: F) -> Vec<Group>
+where
+    F: Fn(&Problem) -> String,
+{
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for problem in problems {
+        *counts.entry(key_fn(problem)).or_insert(0) += 1;
+    }
+
+    let mut groups: Vec<Group> = counts
+        .into_iter()
+        .map(|(key, count)| Group { key, count })
+        .collect();
+    sort_groups(&mut groups);
+    groups
+}
+
+/// Trie les groupes par nombre décroissant, puis par clé
+pub fn sort_groups(groups: &mut [Group]) {
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+}
+
+/// Représentation JSON `{clé: nombre}` des groupes
+pub fn groups_map(groups: &[Group]) -> serde_json::Map<String, serde_json::Value> {
+    groups
+        .iter()
+        .map(|group| (group.key.clone(), group.count.into()))
+        .collect()
+}
+
+/// Poids par défaut des sévérités pour `--rank-files` (0 pour les problèmes sans sévérité)
+pub const DEFAULT_SEVERITY_WEIGHTS: [(Severity, u32); 4] =
+    [(Severity::Error, 10), (Severity::Warning, 3), (Severity::Info, 1), (Severity::Hint, 0)];
+
+/// Score d'un fichier pour `--rank-files`
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FileRank {
+    pub resource: String,
+    /// Somme des poids des sévérités de ses problèmes
+    pub score: u64,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Informations, suggestions et problèmes sans sévérité
+    pub others: usize,
+}
+
+/// Classe les fichiers par score pondéré selon la sévérité de leurs problèmes,
+/// du plus élevé au plus faible, puis par nombre de problèmes et par chemin.
+/// `weights` est prioritaire sur les poids par défaut.
+pub fn rank_files(problems: &[&Problem], weights: &[(Severity, u32)]) -> Vec<FileRank> {
+    let weight = |severity: Option<Severity>| {
+        severity.map_or(0, |severity| {
+            weights
+                .iter()
+                .rev()
+                .chain(&DEFAULT_SEVERITY_WEIGHTS)
+                .find_map(|(s, weight)| (*s == severity).then_some(*weight))
+                .unwrap_or(0)
+        })
+    };
+
+    let mut ranks: HashMap<&str, FileRank> = HashMap::new();
+    for problem in problems {
+        let rank = ranks.entry(problem.resource.as_str()).or_insert_with(|| FileRank {
+            resource: problem.resource.clone(),
+            score: 0,
+            errors: 0,
+            warnings: 0,
+            others: 0,
+        });
+        rank.score += u64::from(weight(problem.severity));
+        match problem.severity {
+            Some(Severity::Error) => rank.errors += 1,
+            Some(Severity::Warning) => rank.warnings += 1,
+            _ => rank.others += 1,
+        }
+    }
+
+    let mut ranks: Vec<FileRank> = ranks.into_values().collect();
+    let total = |rank: &FileRank| rank.errors + rank.warnings + rank.others;
+    ranks.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| total(b).cmp(&total(a)))
+            .then_with(|| a.resource.cmp(&b.resource))
+    });
+    ranks
+}
+
+/// Construit le tableau du classement des fichiers
+pub fn rank_table(ranks: &[FileRank]) -> Table {
+    let mut builder = Builder::default();
+    builder.push_record(["Fichier", "Score", "Erreurs", "Avertissements", "Autres"]);
+    for rank in ranks {
+        builder.push_record([
+            rank.resource.clone(),
+            rank.score.to_string(),
+            rank.errors.to_string(),
+            rank.warnings.to_string(),
+            rank.others.to_string(),
+        ]);
+    }
+    builder.build()
+}
+
+/// Largeur des tranches de longueur de message de `--length-histogram`
+const LENGTH_BUCKET: usize = 50;
+
+/// Largeur maximale des barres de l'histogramme, en caractères
+const HISTOGRAM_BAR: usize = 40;
+
+/// Répartit les problèmes par tranche de longueur du message (0-50, 51-100, ...),
+/// de la plus courte à la plus longue, sans omettre les tranches vides intermédiaires
+pub fn length_histogram(problems: &[&Problem]) -> Vec<Group> {
+    let bucket = |len: usize| len.saturating_sub(1) / LENGTH_BUCKET;
+    let lengths: Vec<usize> = problems.iter().map(|problem| problem.message.chars().count()).collect();
+    let Some(last) = lengths.iter().map(|&len| bucket(len)).max() else {
+        return Vec::new();
+    };
+
+    let mut counts = vec![0; last + 1];
+    for len in lengths {
+        counts[bucket(len)] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, count)| {
+            let start = if index == 0 { 0 } else { index * LENGTH_BUCKET + 1 };
+            Group { key: format!("{start}-{}", (index + 1) * LENGTH_BUCKET), count }
+        })
+        .collect()
+}
+
+/// Construit le tableau d'un histogramme : une barre proportionnelle au nombre par groupe
+pub fn histogram_table(groups: &[Group], key_header: &str) -> Table {
+    let max = groups.iter().map(|group| group.count).max().unwrap_or(0);
+    let mut builder = Builder::default();
+    builder.push_record([key_header, "Occurrences", ""]);
+    for group in groups {
+        let bar = (group.count * HISTOGRAM_BAR).div_ceil(max.max(1));
+        builder.push_record([group.key.clone(), group.count.to_string(), "#".repeat(bar)]);
+    }
+    builder.build()
+}
+
+/// Construit le tableau d'affichage des groupes
+pub fn groups_table(groups: &[Group], key_header: &str) -> Table {
+    let mut builder = Builder::default();
+    builder.push_record([key_header, "Occurrences"]);
+    for group in groups {
+        builder.push_record([group.key.clone(), group.count.to_string()]);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(message: &str) -> Problem {
+        Problem {
+            resource: "file.txt".to_string(),
+            start_line_number: 1,
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_by_sorts_by_count_then_key() {
+        let problems = [problem("b"), problem("a"), problem("c"), problem("c"), problem("b")];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = group_by(&refs, |p| p.message.clone());
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "b".to_string(), count: 2 },
+                Group { key: "c".to_string(), count: 2 },
+                Group { key: "a".to_string(), count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_source_with_missing_source() {
+        let mut eslint = problem("a");
+        eslint._other = serde_json::json!({ "source": "eslint" });
+        let problems = [eslint, problem("b"), problem("c")];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = group_by(&refs, |p| GroupBy::Source.key(p, 50, None));
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "(none)".to_string(), count: 2 },
+                Group { key: "eslint".to_string(), count: 1 },
+            ]
+        );
+        assert_eq!(groups_map(&groups)["(none)"], 2);
+    }
+
+    #[test]
+    fn test_group_by_module() {
+        let problems: Vec<Problem> = ["packages/front/a.ts", "/packages/back/b.ts", "apps/c.ts", "root.ts"]
+            .iter()
+            .map(|resource| Problem { resource: resource.to_string(), ..problem("m") })
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = group_by(&refs, |p| GroupBy::Module.key(p, 50, None));
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "packages".to_string(), count: 2 },
+                Group { key: "(none)".to_string(), count: 1 },
+                Group { key: "apps".to_string(), count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_code() {
+        let codes = [
+            serde_json::json!({ "code": "no-unused-vars" }),
+            serde_json::json!({ "code": { "value": "no-unused-vars", "target": "https://eslint.org" } }),
+            serde_json::json!({ "code": 2304 }),
+            serde_json::json!({}),
+        ];
+        let problems: Vec<Problem> = codes
+            .into_iter()
+            .map(|other| Problem { _other: other, ..problem("m") })
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = group_by(&refs, |p| GroupBy::Code.key(p, 50, None));
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "no-unused-vars".to_string(), count: 2 },
+                Group { key: "(none)".to_string(), count: 1 },
+                Group { key: "2304".to_string(), count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_windows_paths() {
+        let problems: Vec<Problem> = [r"packages\front\a.ts", r"C:\packages\back\a.ts", r"root.ts"]
+            .iter()
+            .map(|resource| Problem { resource: resource.to_string(), ..problem("m") })
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        assert_eq!(GroupBy::Module.key(refs[0], 50, None), "packages");
+        assert_eq!(GroupBy::Module.key(refs[2], 50, None), "(none)");
+        let groups = group_by(&refs, |p| GroupBy::Basename.key(p, 50, None));
+        assert_eq!(groups, vec![Group { key: "a.ts".to_string(), count: 2 }, Group { key: "root.ts".to_string(), count: 1 }]);
+    }
+
+    #[test]
+    fn test_group_by_kind() {
+        let problems = [problem("'x' is declared but never used"), problem("'y' is deprecated"), problem("'z' is never used")];
+        let refs: Vec<&Problem> = problems.iter().collect();
+        let classifier = Classifier::default();
+
+        let groups = group_by(&refs, |p| GroupBy::Kind.key(p, 50, Some(&classifier)));
+        assert_eq!(
+            groups,
+            vec![Group { key: "unused".to_string(), count: 2 }, Group { key: "deprecated".to_string(), count: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_group_by_basename() {
+        let problems: Vec<Problem> = ["src/a/mod.rs", "/src/b/mod.rs", "file:///c/mod.rs", "web/index.ts", "index.ts", ""]
+            .iter()
+            .map(|resource| Problem { resource: resource.to_string(), ..problem("m") })
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = group_by(&refs, |p| GroupBy::Basename.key(p, 50, None));
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "mod.rs".to_string(), count: 3 },
+                Group { key: "index.ts".to_string(), count: 2 },
+                Group { key: "(none)".to_string(), count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_line_bucket() {
+        let problems: Vec<Problem> = [1, 3, 50, 51, 120, 0]
+            .iter()
+            .map(|&line| Problem { start_line_number: line, ..problem("m") })
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let mut groups = group_by(&refs, |p| GroupBy::LineBucket.key(p, 50, None));
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "1-50".to_string(), count: 4 },
+                Group { key: "101-150".to_string(), count: 1 },
+                Group { key: "51-100".to_string(), count: 1 },
+            ]
+        );
+
+        // Les tranches sont triées par numéro de ligne, pas comme du texte
+        GroupSort::Name.sort(&mut groups, false);
+        let keys: Vec<&str> = groups.iter().map(|group| group.key.as_str()).collect();
+        assert_eq!(keys, ["1-50", "51-100", "101-150"]);
+
+        assert_eq!(line_bucket(10, 10), "1-10");
+        assert_eq!(line_bucket(11, 10), "11-20");
+        assert_eq!(line_bucket(7, 1), "7-7");
+    }
+
+    #[test]
+    fn test_rank_files_weighted_by_severity() {
+        let problems: Vec<Problem> = [
+            ("many-warnings.ts", Some(Severity::Warning)),
+            ("many-warnings.ts", Some(Severity::Warning)),
+            ("many-warnings.ts", Some(Severity::Warning)),
+            ("one-error.ts", Some(Severity::Error)),
+            ("hints.ts", Some(Severity::Hint)),
+            ("hints.ts", None),
+        ]
+        .iter()
+        .map(|(resource, severity)| Problem { resource: resource.to_string(), severity: *severity, ..problem("m") })
+        .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        // Une erreur pèse plus que trois avertissements
+        let ranks = rank_files(&refs, &[]);
+        assert_eq!(
+            ranks,
+            vec![
+                FileRank { resource: "one-error.ts".to_string(), score: 10, errors: 1, warnings: 0, others: 0 },
+                FileRank { resource: "many-warnings.ts".to_string(), score: 9, errors: 0, warnings: 3, others: 0 },
+                FileRank { resource: "hints.ts".to_string(), score: 0, errors: 0, warnings: 0, others: 2 },
+            ]
+        );
+
+        let ranks = rank_files(&refs, &[(Severity::Warning, 5), (Severity::Hint, 20)]);
+        let order: Vec<(&str, u64)> = ranks.iter().map(|rank| (rank.resource.as_str(), rank.score)).collect();
+        assert_eq!(order, [("hints.ts", 20), ("many-warnings.ts", 15), ("one-error.ts", 10)]);
+
+        let table = rank_table(&ranks).to_string();
+        assert!(table.contains("| hints.ts         | 20    | 0       | 0              | 2      |"), "unexpected table:\n{table}");
+    }
+
+    #[test]
+    fn test_length_histogram() {
+        let problems: Vec<Problem> = [0, 50, 51, 100, 160, 10]
+            .iter()
+            .map(|&len| problem(&"x".repeat(len)))
+            .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let groups = length_histogram(&refs);
+        assert_eq!(
+            groups,
+            vec![
+                Group { key: "0-50".to_string(), count: 3 },
+                Group { key: "51-100".to_string(), count: 2 },
+                Group { key: "101-150".to_string(), count: 0 },
+                Group { key: "151-200".to_string(), count: 1 },
+            ]
+        );
+        assert!(length_histogram(&[]).is_empty());
+
+        let table = histogram_table(&groups, "Longueur").to_string();
+        assert!(table.contains(&"#".repeat(HISTOGRAM_BAR)));
+        assert!(table.contains("| 151-200  | 1           | ##############"), "unexpected table:\n{table}");
+    }
+
+    #[test]
+    fn test_group_sort_orders() {
+        let group = |key: &str, count| Group { key: key.to_string(), count };
+        let keys = |groups: &[Group]| groups.iter().map(|group| group.key.clone()).collect::<Vec<_>>();
+        let mut groups = vec![group("c", 2), group("a", 1), group("d", 2), group("b", 3)];
+
+        GroupSort::Count.sort(&mut groups, false);
+        assert_eq!(keys(&groups), ["b", "c", "d", "a"]);
+
+        GroupSort::Count.sort(&mut groups, true);
+        assert_eq!(keys(&groups), ["a", "c", "d", "b"]);
+
+        GroupSort::Name.sort(&mut groups, false);
+        assert_eq!(keys(&groups), ["a", "b", "c", "d"]);
+
+        GroupSort::Name.sort(&mut groups, true);
+        assert_eq!(keys(&groups), ["d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_groups_table_header() {
+        let groups = vec![Group { key: "a".to_string(), count: 3 }];
+        let table = groups_table(&groups, "Message").to_string();
+        assert!(table.contains("Message"));
+        assert!(table.contains("Occurrences"));
+        assert!(table.contains('3'));
+    }
+}
@@ -0,0 +1,854 @@
+use crate::problem::{Problem, SeverityOptions};
+use anyhow::{Context, Result, anyhow, bail};
+use clap::ValueEnum;
+use encoding_rs::{Encoding, UTF_8, UTF_16LE, WINDOWS_1252};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Encodage des fichiers d'entrée
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum InputEncoding {
+    #[default]
+    Utf8,
+    /// UTF-16 (little endian par défaut, big endian si le fichier commence par le BOM correspondant)
+    Utf16,
+    Latin1,
+}
+
+impl InputEncoding {
+    fn encoding(&self) -> &'static Encoding {
+        match self {
+            InputEncoding::Utf8 => UTF_8,
+            InputEncoding::Utf16 => UTF_16LE,
+            // windows-1252 est un sur-ensemble de Latin-1 pour les caractères imprimables
+            InputEncoding::Latin1 => WINDOWS_1252,
+        }
+    }
+}
+
+/// Format des fichiers d'entrée (`--input-format`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    /// Détecté d'après le contenu de chaque fichier (voir `detect_format`)
+    #[default]
+    Auto,
+    /// Tableau JSON de problèmes, ou un seul objet
+    Json,
+    /// Suite d'objets JSON (comme `--json-seq`)
+    JsonSeq,
+    /// JSON avec commentaires et virgules finales (comme `--jsonc`)
+    Jsonc,
+    /// Archive zip dont les entrées `*.json` sont fusionnées
+    Zip,
+    /// Fichier compressé avec gzip, dont le contenu est lu selon son propre format
+    Gzip,
+}
+
+/// Détecte le format d'un fichier d'après son contenu : signature des archives
+/// zip et gzip, puis premiers caractères significatifs (`//` ou `/*` pour du JSONC,
+/// plusieurs lignes `{...}` pour une suite d'objets JSON). Le JSON est le format par défaut.
+pub fn detect_format(content: &[u8]) -> InputFormat {
+    if content.starts_with(b"PK\x03\x04") || content.starts_with(b"PK\x05\x06") {
+        return InputFormat::Zip;
+    }
+    if content.starts_with(&[0x1f, 0x8b]) {
+        return InputFormat::Gzip;
+    }
+
+    // Début du texte, sans BOM ni octets nuls (UTF-16) ni séparateurs RS
+    let start = &content[Encoding::for_bom(content).map_or(0, |(_, length)| length)..];
+    let text: String = start
+        .iter()
+        .take(64 * 1024)
+        .filter(|&&byte| byte != 0)
+        .map(|&byte| byte as char)
+        .collect();
+    let text = text.trim_start();
+    if text.starts_with("//") || text.starts_with("/*") {
+        return InputFormat::Jsonc;
+    }
+    if text.starts_with('\u{1e}') {
+        return InputFormat::JsonSeq;
+    }
+
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    match (lines.next(), lines.next()) {
+        (Some(first), Some(second)) if first.starts_with('{') && first.ends_with('}') && second.starts_with('{') => {
+            InputFormat::JsonSeq
+        }
+        _ => InputFormat::Json,
+    }
+}
+
+/// Options de lecture des fichiers d'entrée
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions<'a> {
+    pub format: InputFormat,
+    pub encoding: InputEncoding,
+    /// Accepter les commentaires `//`, `/* */` et les virgules finales (JSONC)
+    pub jsonc: bool,
+    /// L'entrée est une suite d'objets JSON séparés par des espaces plutôt qu'un tableau
+    pub json_seq: bool,
+    /// Pointeur JSON (RFC 6901) désignant le tableau des problèmes dans le document
+    pub json_pointer: Option<&'a str>,
+    /// Rejeter les objets contenant plusieurs fois la même clé (serde_json garde la dernière)
+    pub strict: bool,
+    pub severity: SeverityOptions<'a>,
+}
+
+/// Découpe une liste de fichiers, séparés par des retours à la ligne ou par
+/// des octets NUL (sortie de `find -print0`)
+pub fn parse_file_list(content: &str, null_data: bool) -> Vec<PathBuf> {
+    let entries: Vec<&str> = if null_data {
+        content.split('\0').collect()
+    } else {
+        content.lines().collect()
+    };
+
+    entries
+        .into_iter()
+        .filter(|entry| !entry.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Découpe une liste de termes (`--include-from`, `--exclude-from`) : un terme
+/// par ligne, les lignes vides étant ignorées
+pub fn parse_term_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Fichiers `*.json` d'un dossier (`--input-dir`), triés par chemin, en
+/// parcourant les sous-dossiers avec `recursive`. Les liens symboliques vers des
+/// dossiers ne sont pas suivis (boucles, dossiers lus deux fois) ; ceux vers des
+/// fichiers sont lus.
+pub fn list_json_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Impossible de lire le dossier: {dir:?}"))?;
+    for entry in entries {
+        let entry = entry.with_context(|| format!("Impossible de lire le dossier: {dir:?}"))?;
+        let path = entry.path();
+        let file_type = entry.file_type().with_context(|| format!("Impossible de lire le fichier: {path:?}"))?;
+        if file_type.is_dir() {
+            if recursive {
+                files.extend(list_json_files(&path, recursive)?);
+            }
+        } else if (file_type.is_file() || file_type.is_symlink() && path.is_file())
+            && path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Lit les problèmes d'un fichier d'entrée : un export JSON, une archive zip
+/// dont toutes les entrées `*.json` sont fusionnées, ou un fichier gzip. Avec
+/// le format `auto`, une extension `.zip` désigne une archive zip, sinon le
+/// format est détecté d'après le contenu.
+pub fn read_problems<E: Write>(
+    content: &[u8],
+    path: &Path,
+    options: &ParseOptions,
+    err: &mut E,
+) -> Result<Vec<Problem>> {
+    let format = match options.format {
+        InputFormat::Auto if is_zip(path) => InputFormat::Zip,
+        InputFormat::Auto => detect_format(content),
+        format => format,
+    };
+
+    match format {
+        InputFormat::Zip => read_zip(content, path, options, err),
+        InputFormat::Gzip => {
+            let mut decompressed = Vec::new();
+            flate2::read::GzDecoder::new(content)
+                .read_to_end(&mut decompressed)
+                .with_context(|| format!("Fichier gzip invalide: {path:?}"))?;
+            // Le contenu décompressé a son propre format (détecté avec `auto`)
+            let options = ParseOptions { format: InputFormat::Auto, ..*options };
+            let inner = path.with_extension("");
+            match detect_format(&decompressed) {
+                InputFormat::Gzip => bail!("Fichier gzip imbriqué non pris en charge: {path:?}"),
+                InputFormat::Zip => read_zip(&decompressed, &inner, &options, err),
+                _ => read_problems(&decompressed, path, &options, err),
+            }
+        }
+        format => parse_text(format, content, path, options),
+    }
+}
+
+/// Parse un contenu texte (JSON, JSONC ou suite de valeurs JSON) selon son format
+fn parse_text(format: InputFormat, content: &[u8], path: &Path, options: &ParseOptions) -> Result<Vec<Problem>> {
+    match format {
+        InputFormat::JsonSeq => parse_problems(content, path, &ParseOptions { json_seq: true, ..*options }),
+        InputFormat::Jsonc => parse_problems(content, path, &ParseOptions { jsonc: true, ..*options }),
+        _ => parse_problems(content, path, options),
+    }
+}
+
+/// Décode puis parse le contenu JSON d'un fichier d'export. Un export réduit
+/// à un seul objet `{...}` est traité comme un tableau d'un élément.
+/// Chaque problème retient `origin` comme fichier de provenance.
+pub fn parse_problems(content: &[u8], origin: &Path, options: &ParseOptions) -> Result<Vec<Problem>> {
+    let text = decode(content, options.encoding, origin)?;
+    let text = if options.jsonc {
+        std::borrow::Cow::Owned(strip_jsonc(&text))
+    } else {
+        text
+    };
+    let context = || format!("Erreur lors du parsing du JSON: {origin:?}");
+
+    if options.strict {
+        check_duplicate_keys(&text, options.json_seq, origin)?;
+    }
+
+    let mut problems: Vec<Problem> = if !options.severity.is_default() || options.json_pointer.is_some() {
+        let items = parse_items(&text, options, origin)?;
+        parse_with_severity(items, &options.severity).with_context(context)?
+    } else if options.json_seq {
+        parse_sequence(&text).with_context(context)?
+    } else if text.trim_start().starts_with('{') {
+        vec![serde_json::from_str(&text).with_context(context)?]
+    } else {
+        serde_json::from_str(&text).with_context(context)?
+    };
+
+    for problem in &mut problems {
+        problem.origin = Some(origin.to_path_buf());
+    }
+    Ok(problems)
+}
+
+/// Parse une suite de valeurs JSON (sortie de `jq`), éventuellement séparées
+/// par le caractère RS de la RFC 7464
+fn parse_sequence<T: serde::de::DeserializeOwned>(text: &str) -> serde_json::Result<Vec<T>> {
+    // Le caractère RS ne peut pas apparaître tel quel dans une chaîne JSON
+    let text = text.replace('\u{1e}', " ");
+    serde_json::Deserializer::from_str(&text).into_iter::<T>().collect()
+}
+
+/// Parse le document en valeurs JSON, une par problème, en suivant le
+/// pointeur JSON éventuel
+fn parse_items(text: &str, options: &ParseOptions, origin: &Path) -> Result<Vec<serde_json::Value>> {
+    let context = || format!("Erreur lors du parsing du JSON: {origin:?}");
+    if options.json_seq {
+        // Le format peut avoir été détecté : --json-pointer n'est pas refusé par la ligne de commande
+        if options.json_pointer.is_some() {
+            bail!("--json-pointer n'est pas compatible avec une suite de valeurs JSON: {origin:?}");
+        }
+        return parse_sequence(text).with_context(context);
+    }
+
+    let document: serde_json::Value = serde_json::from_str(text).with_context(context)?;
+    match options.json_pointer {
+        Some(pointer) => match document.pointer(pointer) {
+            Some(serde_json::Value::Array(items)) => Ok(items.clone()),
+            Some(_) => bail!("Le pointeur JSON '{pointer}' ne désigne pas un tableau dans {origin:?}"),
+            None => bail!("Le pointeur JSON '{pointer}' ne correspond à aucune valeur dans {origin:?}"),
+        },
+        None => match document {
+            serde_json::Value::Array(items) => Ok(items),
+            item => Ok(vec![item]),
+        },
+    }
+}
+
+/// Désérialise les problèmes en résolvant la sévérité selon `severity` : la
+/// valeur résolue remplace le champ `severity` d'origine
+fn parse_with_severity(mut items: Vec<serde_json::Value>, severity: &SeverityOptions) -> serde_json::Result<Vec<Problem>> {
+    if !severity.is_default() {
+        for item in &mut items {
+            if let Some(object) = item.as_object_mut() {
+                let resolved = object.get(severity.field()).and_then(|value| severity.resolve(value));
+                object.insert("severity".to_string(), resolved.map(|s| s.code()).into());
+            }
+        }
+    }
+
+    serde_json::from_value(serde_json::Value::Array(items))
+}
+
+/// Chemin vers la première clé dupliquée d'une valeur JSON : les segments menant
+/// à l'objet concerné, suivis de la clé
+struct DuplicateKey(Option<Vec<String>>);
+
+impl<'de> serde::Deserialize<'de> for DuplicateKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(DuplicateKeyVisitor)
+    }
+}
+
+struct DuplicateKeyVisitor;
+
+impl<'de> serde::de::Visitor<'de> for DuplicateKeyVisitor {
+    type Value = DuplicateKey;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("une valeur JSON")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> std::result::Result<DuplicateKey, A::Error> {
+        let mut keys = std::collections::HashSet::new();
+        let mut found = None;
+        while let Some(key) = map.next_key::<String>()? {
+            let DuplicateKey(nested) = map.next_value()?;
+            if found.is_some() {
+                continue;
+            }
+            found = match nested {
+                Some(mut path) => {
+                    path.insert(0, key.clone());
+                    Some(path)
+                }
+                None => None,
+            };
+            if !keys.insert(key.clone()) {
+                found = Some(vec![key]);
+            }
+        }
+        Ok(DuplicateKey(found))
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<DuplicateKey, A::Error> {
+        let mut found = None;
+        let mut index = 0;
+        while let Some(DuplicateKey(nested)) = seq.next_element()? {
+            if found.is_none()
+                && let Some(mut path) = nested
+            {
+                path.insert(0, index.to_string());
+                found = Some(path);
+            }
+            index += 1;
+        }
+        Ok(DuplicateKey(found))
+    }
+
+    fn visit_bool<E>(self, _: bool) -> std::result::Result<DuplicateKey, E> {
+        Ok(DuplicateKey(None))
+    }
+
+    fn visit_i64<E>(self, _: i64) -> std::result::Result<DuplicateKey, E> {
+        Ok(DuplicateKey(None))
+    }
+
+    fn visit_u64<E>(self, _: u64) -> std::result::Result<DuplicateKey, E> {
+        Ok(DuplicateKey(None))
+    }
+
+    fn visit_f64<E>(self, _: f64) -> std::result::Result<DuplicateKey, E> {
+        Ok(DuplicateKey(None))
+    }
+
+    fn visit_str<E>(self, _: &str) -> std::result::Result<DuplicateKey, E> {
+        Ok(DuplicateKey(None))
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<DuplicateKey, E> {
+        Ok(DuplicateKey(None))
+    }
+}
+
+/// Vérifie qu'aucun objet du document ne contient deux fois la même clé
+/// (`--strict-json`). L'erreur indique la clé et l'index du problème concerné.
+fn check_duplicate_keys(text: &str, json_seq: bool, origin: &Path) -> Result<()> {
+    let context = || format!("Erreur lors du parsing du JSON: {origin:?}");
+    let (path, indexed) = if json_seq {
+        let values: Vec<DuplicateKey> = parse_sequence(text).with_context(context)?;
+        let path = values.into_iter().enumerate().find_map(|(index, DuplicateKey(path))| {
+            path.map(|mut path| {
+                path.insert(0, index.to_string());
+                path
+            })
+        });
+        (path, true)
+    } else {
+        let DuplicateKey(path) = serde_json::from_str(text).with_context(context)?;
+        (path, text.trim_start().starts_with('['))
+    };
+
+    let Some(mut path) = path else {
+        return Ok(());
+    };
+    let key = path.pop().unwrap_or_default();
+    let pointer: String = path
+        .iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect();
+    match path.first() {
+        Some(index) if indexed => {
+            bail!("Clé JSON dupliquée \"{key}\" dans le problème d'index {index} de {origin:?} (emplacement {pointer})")
+        }
+        _ => bail!("Clé JSON dupliquée \"{key}\" dans {origin:?} (emplacement {pointer})"),
+    }
+}
+
+/// Convertit du JSONC en JSON : les commentaires sont remplacés par des
+/// espaces (les retours à la ligne sont conservés) et les virgules finales
+/// supprimées. Le contenu des chaînes n'est pas modifié.
+fn strip_jsonc(text: &str) -> String {
+    let mut without_comments = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => copy_string(&mut chars, &mut without_comments),
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    without_comments.push(if c == '\n' { '\n' } else { ' ' });
+                    previous = c;
+                }
+            }
+            _ => without_comments.push(c),
+        }
+    }
+
+    let mut output = String::with_capacity(without_comments.len());
+    let mut chars = without_comments.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => copy_string(&mut chars, &mut output),
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some(']' | '}')) {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Recopie une chaîne JSON dont le guillemet ouvrant vient d'être lu
+fn copy_string(chars: &mut std::iter::Peekable<std::str::Chars>, output: &mut String) {
+    output.push('"');
+    while let Some(c) = chars.next() {
+        output.push(c);
+        match c {
+            '\\' => output.extend(chars.next()),
+            '"' => break,
+            _ => {}
+        }
+    }
+}
+
+/// Décode le contenu selon l'encodage demandé. Un BOM éventuel est retiré et
+/// prévaut sur l'encodage demandé.
+fn decode<'a>(content: &'a [u8], encoding: InputEncoding, origin: &Path) -> Result<std::borrow::Cow<'a, str>> {
+    let (encoding, bom_length) = Encoding::for_bom(content).unwrap_or((encoding.encoding(), 0));
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&content[bom_length..])
+        .ok_or_else(|| anyhow!("Impossible de décoder {origin:?} en {}", encoding.name()))
+}
+
+fn is_zip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+fn read_zip<E: Write>(content: &[u8], path: &Path, options: &ParseOptions, err: &mut E) -> Result<Vec<Problem>> {
+    let corrupt = || format!("Archive zip invalide: {path:?}");
+    let mut archive = ZipArchive::new(Cursor::new(content)).with_context(corrupt)?;
+
+    let mut problems = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).with_context(corrupt)?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().with_context(corrupt)?.into_owned();
+        if !name.to_lowercase().ends_with(".json") {
+            writeln!(err, "Avertissement: entrée non JSON ignorée dans {path:?}: {name}")?;
+            continue;
+        }
+
+        let mut entry_content = Vec::new();
+        entry.read_to_end(&mut entry_content).with_context(corrupt)?;
+        // Le format de chaque entrée est détecté comme pour un fichier lu seul
+        let entry_path = path.join(&name);
+        let format = detect_format(&entry_content);
+        if matches!(format, InputFormat::Zip | InputFormat::Gzip) {
+            bail!("Archive imbriquée non prise en charge: {entry_path:?}");
+        }
+        problems.extend(parse_text(format, &entry_content, &entry_path, options)?);
+    }
+
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Severity;
+
+    #[test]
+    fn test_parse_file_list_newline() {
+        let files = parse_file_list("a.json\nb/c.json\n\n", false);
+        assert_eq!(files, vec![PathBuf::from("a.json"), PathBuf::from("b/c.json")]);
+    }
+
+    #[test]
+    fn test_parse_term_list() {
+        assert_eq!(parse_term_list("deprecated\r\n\n  \nnever used\n"), ["deprecated", "never used"]);
+    }
+
+    #[test]
+    fn test_list_json_files() {
+        let dir = std::env::temp_dir().join(format!("vpf-list-json-files-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["b.json", "a.JSON", "notes.txt", "nested/c.json"] {
+            std::fs::write(dir.join(name), "[]").unwrap();
+        }
+
+        let names = |recursive| {
+            let files = list_json_files(&dir, recursive).unwrap();
+            files.iter().map(|file| file.strip_prefix(&dir).unwrap().to_path_buf()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(false), [PathBuf::from("a.JSON"), PathBuf::from("b.json")]);
+        assert_eq!(names(true), [PathBuf::from("a.JSON"), PathBuf::from("b.json"), PathBuf::from("nested/c.json")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(list_json_files(&dir, false).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_json_files_symlinks() {
+        let dir = std::env::temp_dir().join(format!("vpf-list-json-symlinks-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("nested/a.json"), "[]").unwrap();
+        // Boucle vers le dossier parent, dossier et fichier liés
+        std::os::unix::fs::symlink("..", dir.join("nested/loop")).unwrap();
+        std::os::unix::fs::symlink("nested", dir.join("alias")).unwrap();
+        std::os::unix::fs::symlink("nested/a.json", dir.join("b.json")).unwrap();
+
+        let files = list_json_files(&dir, true).unwrap();
+        let names: Vec<_> = files.iter().map(|file| file.strip_prefix(&dir).unwrap().to_path_buf()).collect();
+        assert_eq!(names, [PathBuf::from("b.json"), PathBuf::from("nested/a.json")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_file_list_null_data() {
+        let files = parse_file_list("a.json\0dir with\nnewline.json\0", true);
+        assert_eq!(files, vec![PathBuf::from("a.json"), PathBuf::from("dir with\nnewline.json")]);
+    }
+
+    fn make_zip(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_parse_problems_error_mentions_file() {
+        let err = read_problems(b"{", Path::new("broken.json"), &ParseOptions::default(), &mut std::io::sink()).unwrap_err();
+        assert!(format!("{err}").contains("broken.json"));
+    }
+
+    #[test]
+    fn test_strict_json_rejects_duplicate_keys() {
+        let content = br#"[
+            { "resource": "a", "startLineNumber": 1, "message": "first" },
+            { "resource": "b", "startLineNumber": 2, "message": "m", "source": "old", "source": "new" }
+        ]"#;
+        let strict = ParseOptions { strict: true, ..Default::default() };
+
+        // Par défaut, la dernière valeur d'un champ non reconnu est gardée
+        let problems = parse_problems(content, Path::new("dup.json"), &ParseOptions::default()).unwrap();
+        assert_eq!(problems[1].source(), Some("new"));
+
+        let err = parse_problems(content, Path::new("dup.json"), &strict).unwrap_err();
+        assert_eq!(
+            format!("{err}"),
+            r#"Clé JSON dupliquée "source" dans le problème d'index 1 de "dup.json" (emplacement /1)"#
+        );
+
+        // Clés dupliquées dans un objet imbriqué, et dans une suite d'objets
+        let nested = br#"{ "resource": "a", "code": { "value": 1, "value": 2 } }"#;
+        let err = parse_problems(nested, Path::new("n.json"), &strict).unwrap_err();
+        assert_eq!(format!("{err}"), r#"Clé JSON dupliquée "value" dans "n.json" (emplacement /code)"#);
+
+        let seq = br#"{ "message": "a" } { "message": "b", "resource": "x", "resource": "y" }"#;
+        let options = ParseOptions { json_seq: true, ..strict };
+        let err = parse_problems(seq, Path::new("s.json"), &options).unwrap_err();
+        assert!(format!("{err}").contains(r#""resource" dans le problème d'index 1"#), "unexpected error: {err}");
+
+        // Les mêmes clés dans des objets différents sont acceptées
+        let valid = br#"[{ "resource": "a", "startLineNumber": 1, "message": "m" }, { "resource": "b", "startLineNumber": 2, "message": "m" }]"#;
+        assert_eq!(parse_problems(valid, Path::new("ok.json"), &strict).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let gzip = {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(b"[]").unwrap();
+            encoder.finish().unwrap()
+        };
+        let utf16: Vec<u8> = "\u{feff}// c\n[]".encode_utf16().flat_map(u16::to_le_bytes).collect();
+
+        assert_eq!(detect_format(&make_zip(&[("a.json", "[]")])), InputFormat::Zip);
+        assert_eq!(detect_format(&gzip), InputFormat::Gzip);
+        assert_eq!(detect_format(b"  [\n  { \"message\": \"m\" }\n]"), InputFormat::Json);
+        assert_eq!(detect_format(b"{ \"message\": \"alone\" }\n"), InputFormat::Json);
+        assert_eq!(detect_format(b"{\n  \"message\": \"a\"\n}\n{\n  \"message\": \"b\"\n}"), InputFormat::Json);
+        assert_eq!(detect_format(b"\xef\xbb\xbf// export\n[]"), InputFormat::Jsonc);
+        assert_eq!(detect_format(b"/* export */ []"), InputFormat::Jsonc);
+        assert_eq!(detect_format(&utf16), InputFormat::Jsonc);
+        assert_eq!(detect_format(b"{ \"message\": \"a\" }\n{ \"message\": \"b\" }\n"), InputFormat::JsonSeq);
+        assert_eq!(detect_format(b"\x1e{ \"message\": \"a\" }"), InputFormat::JsonSeq);
+        assert_eq!(detect_format(b""), InputFormat::Json);
+    }
+
+    #[test]
+    fn test_read_problems_auto_format() {
+        let read = |content: &[u8], path: &str, format| {
+            let options = ParseOptions { format, ..Default::default() };
+            read_problems(content, Path::new(path), &options, &mut std::io::sink())
+        };
+        let problem = r#"{ "resource": "a", "startLineNumber": 1, "message": "m" }"#;
+
+        let jsonc = format!("// export\n[{problem},]");
+        assert_eq!(read(jsonc.as_bytes(), "a.json", InputFormat::Auto).unwrap().len(), 1);
+        let seq = format!("{problem}\n{problem}\n");
+        assert_eq!(read(seq.as_bytes(), "a.json", InputFormat::Auto).unwrap().len(), 2);
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(seq.as_bytes()).unwrap();
+        let gzip = encoder.finish().unwrap();
+        let problems = read(&gzip, "a.json.gz", InputFormat::Auto).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].origin, Some(PathBuf::from("a.json.gz")));
+
+        let archive = make_zip(&[("a.json", &format!("[{problem}]"))]);
+        assert_eq!(read(&archive, "export.bin", InputFormat::Auto).unwrap().len(), 1);
+
+        // Un format explicite désactive la détection
+        assert!(read(jsonc.as_bytes(), "a.json", InputFormat::Json).is_err());
+        assert!(format!("{:#}", read(b"[]", "a.gz", InputFormat::Gzip).unwrap_err()).contains("Fichier gzip invalide"));
+    }
+
+    #[test]
+    fn test_parse_single_object() {
+        let content = br#"  { "resource": "a", "startLineNumber": 1, "message": "alone" }"#;
+        let problems = read_problems(content, Path::new("single.json"), &ParseOptions::default(), &mut std::io::sink())
+            .unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].message, "alone");
+    }
+
+    #[test]
+    fn test_read_zip_merges_json_entries() {
+        let archive = make_zip(&[
+            ("front.json", r#"[{ "resource": "a", "startLineNumber": 1, "message": "m1" }]"#),
+            ("README.txt", "not json"),
+            ("nested/back.json", r#"[{ "resource": "b", "startLineNumber": 2, "message": "m2" }]"#),
+        ]);
+
+        let mut err = Vec::new();
+        let problems = read_problems(&archive, Path::new("export.zip"), &ParseOptions::default(), &mut err).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[1].message, "m2");
+        assert_eq!(problems[1].origin, Some(PathBuf::from("export.zip/nested/back.json")));
+        let warnings = String::from_utf8(err).unwrap();
+        assert!(warnings.contains("README.txt"), "unexpected warnings: {warnings}");
+    }
+
+    #[test]
+    fn test_read_zip_detects_entry_formats() {
+        let archive = make_zip(&[
+            ("commented.json", "// export\n[{ \"resource\": \"a\", \"startLineNumber\": 1, \"message\": \"m1\" }]"),
+            (
+                "lines.json",
+                "{ \"resource\": \"b\", \"startLineNumber\": 2, \"message\": \"m2\" }\n\
+                 { \"resource\": \"c\", \"startLineNumber\": 3, \"message\": \"m3\" }\n",
+            ),
+        ]);
+
+        let problems = read_problems(&archive, Path::new("export.zip"), &ParseOptions::default(), &mut std::io::sink()).unwrap();
+        let messages: Vec<&str> = problems.iter().map(|problem| problem.message.as_str()).collect();
+        assert_eq!(messages, ["m1", "m2", "m3"]);
+    }
+
+    #[test]
+    fn test_json_pointer_rejects_detected_sequence() {
+        let content = b"{ \"resource\": \"a\", \"startLineNumber\": 1, \"message\": \"m\" }\n{ \"resource\": \"b\", \"startLineNumber\": 2, \"message\": \"m\" }\n";
+        let options = ParseOptions { json_pointer: Some("/diagnostics"), ..Default::default() };
+        let err = read_problems(content, Path::new("export.json"), &options, &mut std::io::sink()).unwrap_err();
+        assert!(err.to_string().contains("--json-pointer n'est pas compatible"), "{err}");
+    }
+
+    #[test]
+    fn test_read_zip_corrupt_archive() {
+        let err = read_problems(b"not a zip", Path::new("export.zip"), &ParseOptions::default(), &mut std::io::sink()).unwrap_err();
+        assert!(format!("{err}").contains("Archive zip invalide"));
+    }
+
+    #[test]
+    fn test_read_zip_invalid_inner_json() {
+        let archive = make_zip(&[("broken.json", "[{")]);
+
+        let err = read_problems(&archive, Path::new("export.zip"), &ParseOptions::default(), &mut std::io::sink()).unwrap_err();
+        let msg = format!("{err}");
+        assert!(msg.contains("Erreur lors du parsing du JSON"), "unexpected error message: {msg}");
+        assert!(msg.contains("broken.json"), "unexpected error message: {msg}");
+    }
+
+    #[test]
+    fn test_parse_severity_field_and_map() {
+        let content = br#"[
+            { "resource": "a", "startLineNumber": 1, "message": "m", "level": 3, "severity": 1 },
+            { "resource": "b", "startLineNumber": 2, "message": "m", "level": "warning" },
+            { "resource": "c", "startLineNumber": 3, "message": "m" }
+        ]"#;
+        let map = [("3".to_string(), Severity::Error)];
+        let options = ParseOptions {
+            severity: SeverityOptions { field: Some("level"), map: &map },
+            ..Default::default()
+        };
+
+        let problems = read_problems(content, Path::new("export.json"), &options, &mut std::io::sink()).unwrap();
+        assert_eq!(problems[0].severity, Some(Severity::Error));
+        assert_eq!(problems[1].severity, Some(Severity::Warning));
+        assert_eq!(problems[2].severity, None);
+        assert_eq!(problems[0]._other.get("level"), Some(&serde_json::json!(3)));
+    }
+
+    #[test]
+    fn test_strip_jsonc() {
+        let content = r#"[
+            // commentaire de ligne
+            { "resource": "src/a.ts", /* bloc */ "message": "see https://example.com/*x*/", "n": "a\"//b", },
+            /* bloc
+               sur plusieurs lignes */
+        ]"#;
+        let json = strip_jsonc(content);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["message"], "see https://example.com/*x*/");
+        assert_eq!(value[0]["n"], "a\"//b");
+        assert_eq!(value.as_array().unwrap().len(), 1);
+        assert_eq!(json.lines().count(), content.lines().count());
+    }
+
+    #[test]
+    fn test_parse_jsonc_option() {
+        let content = br#"[
+            { "resource": "a", "startLineNumber": 1, "message": "m1" }, // premier
+            { "resource": "b", "startLineNumber": 2, "message": "m2" },
+        ]"#;
+        let path = Path::new("export.jsonc");
+
+        assert!(read_problems(content, path, &ParseOptions::default(), &mut std::io::sink()).is_err());
+
+        let options = ParseOptions { jsonc: true, ..Default::default() };
+        let problems = read_problems(content, path, &options, &mut std::io::sink()).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[1].message, "m2");
+    }
+
+    #[test]
+    fn test_parse_json_seq() {
+        let content = br#"{ "resource": "a", "startLineNumber": 1, "message": "m1" }
+        {
+            "resource": "b",
+            "startLineNumber": 2,
+            "message": "m2"
+        }{ "resource": "c", "startLineNumber": 3, "message": "m3" }
+"#;
+        let options = ParseOptions { json_seq: true, ..Default::default() };
+        let problems = read_problems(content, Path::new("-"), &options, &mut std::io::sink()).unwrap();
+        assert_eq!(problems.len(), 3);
+        assert_eq!(problems[1].message, "m2");
+        assert_eq!(problems[2].resource, "c");
+
+        let rfc7464 = "\u{1e}{ \"resource\": \"a\", \"startLineNumber\": 1, \"message\": \"m\" }\n";
+        let problems = read_problems(rfc7464.as_bytes(), Path::new("-"), &options, &mut std::io::sink()).unwrap();
+        assert_eq!(problems.len(), 1);
+
+        let map = [("m".to_string(), Severity::Error)];
+        let options = ParseOptions {
+            json_seq: true,
+            severity: SeverityOptions { field: Some("message"), map: &map },
+            ..Default::default()
+        };
+        let problems = read_problems(rfc7464.as_bytes(), Path::new("-"), &options, &mut std::io::sink()).unwrap();
+        assert_eq!(problems[0].severity, Some(Severity::Error));
+    }
+
+    #[test]
+    fn test_parse_json_pointer() {
+        let content = br#"{ "report": { "diagnostics": [
+            { "resource": "a", "startLineNumber": 1, "message": "m1" },
+            { "resource": "b", "startLineNumber": 2, "message": "m2" }
+        ], "count": 2 } }"#;
+        let read = |pointer| {
+            let options = ParseOptions { json_pointer: Some(pointer), ..Default::default() };
+            read_problems(content, Path::new("nested.json"), &options, &mut std::io::sink())
+        };
+
+        let problems = read("/report/diagnostics").unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[1].message, "m2");
+
+        let err = read("/report/count").unwrap_err();
+        assert!(format!("{err}").contains("ne désigne pas un tableau"));
+        let err = read("/diagnostics").unwrap_err();
+        assert!(format!("{err}").contains("ne correspond à aucune valeur"));
+    }
+
+    fn read_with_encoding(content: &[u8], encoding: InputEncoding) -> Result<Vec<Problem>> {
+        let options = ParseOptions { encoding, ..Default::default() };
+        read_problems(content, Path::new("export.json"), &options, &mut std::io::sink())
+    }
+
+    #[test]
+    fn test_decode_utf8_with_bom() {
+        let content = "\u{feff}[{ \"resource\": \"a\", \"startLineNumber\": 1, \"message\": \"é\" }]";
+        let problems = read_with_encoding(content.as_bytes(), InputEncoding::Utf8).unwrap();
+        assert_eq!(problems[0].message, "é");
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let json = r#"[{ "resource": "a", "startLineNumber": 1, "message": "déprécié" }]"#;
+        let content: Vec<u8> = json.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let problems = read_with_encoding(&content, InputEncoding::Utf16).unwrap();
+        assert_eq!(problems[0].message, "déprécié");
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        let json = r#"[{ "resource": "a", "startLineNumber": 1, "message": "déprécié" }]"#;
+        // Les caractères Latin-1 ont le même code que leur point de code Unicode
+        let content: Vec<u8> = json.chars().map(|c| c as u8).collect();
+        let problems = read_with_encoding(&content, InputEncoding::Latin1).unwrap();
+        assert_eq!(problems[0].message, "déprécié");
+    }
+
+    #[test]
+    fn test_decode_invalid_utf8() {
+        let content = [b'[', 0xE9, b']'];
+        let err = read_with_encoding(&content, InputEncoding::Utf8).unwrap_err();
+        assert!(format!("{err}").contains("Impossible de décoder"));
+    }
+}
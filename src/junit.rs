@@ -0,0 +1,108 @@
+use crate::output::escape_html;
+use crate::problem::Problem;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Échappe un texte pour un attribut ou un contenu XML. Les caractères de
+/// contrôle interdits en XML 1.0 (séquences ANSI par exemple) sont retirés.
+fn escape_xml(text: &str) -> String {
+    let text: String = text
+        .chars()
+        .filter(|&c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    escape_html(&text)
+}
+
+/// Écrit les problèmes au format JUnit XML : une suite `<testsuite>` par fichier
+/// concerné (dans l'ordre de première apparition), et un cas `<testcase>` en
+/// échec par problème, le message étant porté par `<failure>`
+pub fn write_junit<W: Write>(out: &mut W, problems: &[&Problem], line_offset: u32) -> io::Result<()> {
+    let mut suites: Vec<(&str, Vec<&Problem>)> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for problem in problems {
+        match positions.get(problem.resource.as_str()) {
+            Some(&position) => suites[position].1.push(problem),
+            None => {
+                positions.insert(&problem.resource, suites.len());
+                suites.push((&problem.resource, vec![problem]));
+            }
+        }
+    }
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<testsuites name="{}" tests="{count}" failures="{count}">"#,
+        env!("CARGO_PKG_NAME"),
+        count = problems.len()
+    )?;
+    for (resource, problems) in &suites {
+        let resource = escape_xml(resource);
+        writeln!(
+            out,
+            r#"  <testsuite name="{resource}" tests="{count}" failures="{count}">"#,
+            count = problems.len()
+        )?;
+        for problem in problems {
+            let line = problem.start_line_number.saturating_add(line_offset);
+            let message = escape_xml(&problem.message);
+            let severity = problem.severity.map_or("none", |severity| severity.name());
+            writeln!(out, r#"    <testcase name="{resource}:{line}" classname="{resource}">"#)?;
+            writeln!(out, r#"      <failure message="{message}" type="{severity}">{message}</failure>"#)?;
+            writeln!(out, "    </testcase>")?;
+        }
+        writeln!(out, "  </testsuite>")?;
+    }
+    writeln!(out, "</testsuites>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Severity;
+
+    fn problem(resource: &str, line: u32, message: &str) -> Problem {
+        Problem {
+            resource: resource.to_string(),
+            start_line_number: line,
+            message: message.to_string(),
+            severity: Some(Severity::Warning),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_write_junit() {
+        let problems = [
+            problem("src/a.ts", 1, "'x' is deprecated"),
+            problem("src/b&c.ts", 2, "expected <T> \u{1b}[1mhere\u{1b}[0m"),
+            problem("src/a.ts", 3, "\"y\" is unused"),
+        ];
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let mut out = Vec::new();
+        write_junit(&mut out, &refs, 0).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+        assert_eq!(
+            xml,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="vscode-problems-filtering" tests="3" failures="3">
+  <testsuite name="src/a.ts" tests="2" failures="2">
+    <testcase name="src/a.ts:1" classname="src/a.ts">
+      <failure message="&#39;x&#39; is deprecated" type="warning">&#39;x&#39; is deprecated</failure>
+    </testcase>
+    <testcase name="src/a.ts:3" classname="src/a.ts">
+      <failure message="&quot;y&quot; is unused" type="warning">&quot;y&quot; is unused</failure>
+    </testcase>
+  </testsuite>
+  <testsuite name="src/b&amp;c.ts" tests="1" failures="1">
+    <testcase name="src/b&amp;c.ts:2" classname="src/b&amp;c.ts">
+      <failure message="expected &lt;T&gt; [1mhere[0m" type="warning">expected &lt;T&gt; [1mhere[0m</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
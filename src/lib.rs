@@ -0,0 +1,7 @@
+//! Modèle des problèmes et critères de filtrage, partagés par le binaire et
+//! les mesures de performance (`benches/`)
+
+pub mod classify;
+pub mod filter;
+pub mod normalize;
+pub mod problem;
@@ -1,321 +1,4975 @@
-mod problem;
+mod criteria;
+mod dedup;
+mod grouping;
+mod input;
+mod junit;
+mod output;
+mod preview;
+mod query;
+mod sarif;
+mod sort;
+mod tree;
+
+use vscode_problems_filtering::{classify, filter, normalize, problem};
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use problem::{Problem, ProblemOutput};
+use classify::Classifier;
+use criteria::FilterCriteria;
+use filter::{Criterion, DEFAULT_GENERATED_PATTERNS, FilterDecision, FilterFunnel, ProblemFilter, Synonyms, TextOptions};
+use grouping::{GroupBy, GroupSort};
+use input::{InputEncoding, InputFormat, ParseOptions};
+use output::{Column, OutputFormat};
+use problem::{OutputOptions, Problem, ProblemOutput, Severity, SeverityOptions, TruncateMode};
+use query::Expr;
+use rand::SeedableRng;
+use regex::{Regex, RegexBuilder};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use sort::SortBy;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
-use tabled::{Table};
-use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use tree::TreeNode;
+use std::io::{IsTerminal, Read, Write};
 
 /// Application CLI pour filtrer les problèmes VS Code
-#[derive(Parser)]
+#[derive(Parser, Default, Clone)]
 #[command(
     name = "vscode-problems-filtering",
     about = "Filtre les problèmes VS Code selon des critères d'inclusion et d'exclusion",
-    version = "0.1.0"
+    version = "0.1.0",
+    // Options lisant les fichiers sources, résolus par rapport à --source-root
+    group = clap::ArgGroup::new("source_readers").args(["source_preview", "verify_resources", "resolve_symlinks"]).multiple(true)
 )]
 struct CliProblemApp {
-    /// Fichier JSON contenant les problèmes VS Code
-    #[arg(short = 'f', long, value_name = "FILE")]
-    input: PathBuf,
+    /// Fichier JSON contenant les problèmes VS Code (répétable, les problèmes sont fusionnés ; `-` pour l'entrée standard)
+    #[arg(short = 'f', long = "input", value_name = "FILE", required_unless_present_any = ["files_from", "input_dir"])]
+    inputs: Vec<PathBuf>,
+
+    /// Dossier dont tous les fichiers `*.json` sont lus et fusionnés
+    #[arg(long, value_name = "DIR")]
+    input_dir: Option<PathBuf>,
+
+    /// Parcourir aussi les sous-dossiers de --input-dir
+    #[arg(long, requires = "input_dir")]
+    recursive: bool,
+
+    /// Signaler les fichiers d'entrée illisibles ou invalides sur la sortie d'erreur et
+    /// continuer avec les autres, au lieu d'arrêter
+    #[arg(long)]
+    skip_invalid: bool,
+
+    /// Fichier listant les fichiers JSON à lire (un par ligne)
+    #[arg(long, value_name = "FILE")]
+    files_from: Option<PathBuf>,
+
+    /// Les fichiers listés par --files-from sont séparés par des octets NUL (find -print0)
+    #[arg(long, requires = "files_from")]
+    null_data: bool,
+
+    /// Critères de filtrage au format JSON (ou @FICHIER pour les lire dans un fichier),
+    /// par exemple {"include": ["deprecated"], "ignore_case": true} ; les options explicites sont prioritaires
+    #[arg(long, value_name = "JSON")]
+    filter_json: Option<String>,
 
     /// Termes à inclure (tous doivent être présents dans le message)
     #[arg(short = 'i', long = "include", value_name = "TERM")]
     include_terms: Vec<String>,
 
+    /// Fichier de termes à inclure, un par ligne (`-` pour l'entrée standard), ajoutés à --include
+    #[arg(long, value_name = "FILE")]
+    include_from: Option<PathBuf>,
+
+    /// Fichier TOML de groupes de synonymes (`unused = ["never used", "is not used"]`) : un terme
+    /// d'inclusion ou d'exclusion d'un groupe correspond à n'importe quel terme du groupe
+    #[arg(long, value_name = "FILE")]
+    synonyms: Option<PathBuf>,
+
+    /// Groupes de synonymes lus depuis --synonyms
+    #[arg(skip)]
+    synonym_groups: Synonyms,
+
+    /// Garder les problèmes contenant au moins K des termes d'inclusion, plutôt que tous
+    #[arg(long, value_name = "K", requires = "include_terms")]
+    include_min: Option<usize>,
+
+    /// Un terme d'inclusion de plusieurs mots correspond si ses mots apparaissent dans l'ordre,
+    /// même séparés par d'autres mots (`cannot symbol` correspond à `cannot find symbol`)
+    #[arg(long)]
+    loose_phrase: bool,
+
     /// Termes à exclure (aucun ne doit être présent dans le message)
     #[arg(short = 'e', long = "exclude", value_name = "TERM")]
     exclude_terms: Vec<String>,
 
+    /// Fichier de termes à exclure, un par ligne (`-` pour l'entrée standard), ajoutés à --exclude
+    #[arg(long, value_name = "FILE")]
+    exclude_from: Option<PathBuf>,
+
+    /// Format des fichiers d'entrée : auto (détecté d'après le contenu), json, json-seq, jsonc, zip ou gzip
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+
+    /// Encodage des fichiers d'entrée
+    #[arg(long, value_enum, default_value_t = InputEncoding::Utf8)]
+    encoding: InputEncoding,
+
+    /// Les fichiers d'entrée contiennent une suite d'objets JSON (par exemple la sortie de `jq '.[]'`) plutôt qu'un tableau
+    #[arg(long)]
+    json_seq: bool,
+
+    /// Pointeur JSON vers le tableau des problèmes dans les fichiers d'entrée, par exemple /diagnostics
+    #[arg(long, value_name = "PTR", conflicts_with = "json_seq")]
+    json_pointer: Option<String>,
+
+    /// Accepter les commentaires (`//`, `/* */`) et les virgules finales dans les fichiers d'entrée (JSONC)
+    #[arg(long)]
+    jsonc: bool,
+
+    /// Rejeter les fichiers d'entrée dont un objet contient plusieurs fois la même clé
+    /// (par défaut, seuls les champs reconnus sont vérifiés : la dernière valeur des autres est gardée)
+    #[arg(long)]
+    strict_json: bool,
+
+    /// Champ de l'export contenant la sévérité, à la place de `severity` (par exemple `level`)
+    #[arg(long, value_name = "NAME")]
+    severity_field: Option<String>,
+
+    /// Correspondance entre une valeur de sévérité de l'export et une sévérité (error, warning, info, hint),
+    /// prioritaire sur les valeurs reconnues par défaut, par exemple 3=error (répétable)
+    #[arg(long, value_name = "VALUE=SEVERITY", value_parser = parse_severity_mapping)]
+    severity_map: Vec<(String, Severity)>,
+
     /// Ignorer la casse lors de la comparaison
     #[arg(long)]
     ignore_case: bool,
 
+    /// Ignorer la casse lors de la comparaison des fichiers (--resource-regex,
+    /// --exclude-resource-regex, fichiers générés, .vpfignore, champ path de --query),
+    /// indépendamment de celle des messages
+    #[arg(long)]
+    ignore_case_resource: bool,
+
+    /// Garder uniquement les problèmes de cette sévérité : error, warning, info ou hint (répétable)
+    #[arg(long = "severity", value_name = "SEVERITY", value_parser = parse_severity_name)]
+    severities: Vec<Severity>,
+
+    /// Garder uniquement les messages d'au moins N caractères
+    #[arg(long, value_name = "N")]
+    min_msg_len: Option<usize>,
+
+    /// Garder uniquement les messages d'au plus N caractères
+    #[arg(long, value_name = "N")]
+    max_msg_len: Option<usize>,
+
+    /// Chercher les termes d'inclusion et d'exclusion uniquement dans les N premiers caractères
+    /// du message (le message est affiché en entier)
+    #[arg(long, value_name = "N")]
+    message_prefix_len: Option<usize>,
+
+    /// Exclure les problèmes des fichiers générés (generated, node_modules/, /target/, /dist/, .d.ts, .min.js)
+    #[arg(long)]
+    ignore_generated: bool,
+
+    /// Fichier de motifs au format .gitignore des fichiers à exclure (négations `!` comprises)
+    /// [défaut: .vpfignore du dossier courant s'il existe]
+    #[arg(long, value_name = "FILE")]
+    ignore_file: Option<PathBuf>,
+
+    /// Ne pas lire le fichier .vpfignore du dossier courant
+    #[arg(long, conflicts_with = "ignore_file")]
+    no_ignore_file: bool,
+
+    /// Garder uniquement les problèmes dont le fichier correspond à l'expression régulière
+    #[arg(long, value_name = "PATTERN")]
+    resource_regex: Option<String>,
+
+    /// Exclure les problèmes dont le fichier correspond à l'expression régulière
+    #[arg(long, value_name = "PATTERN")]
+    exclude_resource_regex: Option<String>,
+
+    /// Motif de chemin supplémentaire identifiant un fichier généré (répétable)
+    #[arg(long, value_name = "STR", requires = "ignore_generated")]
+    generated_pattern: Vec<String>,
+
+    /// Ne pas utiliser les motifs de fichiers générés par défaut, seulement ceux de --generated-pattern
+    #[arg(long, requires = "generated_pattern")]
+    no_default_generated: bool,
+
+    /// Exclure les problèmes dont le message est vide ou ne contient que des espaces
+    #[arg(long, conflicts_with = "only_empty")]
+    drop_empty: bool,
+
+    /// Garder uniquement les problèmes dont le message est vide ou ne contient que des espaces
+    /// (aucun terme d'inclusion ou d'exclusion n'est alors nécessaire)
+    #[arg(long)]
+    only_empty: bool,
+
+    /// Réduire les suites d'espaces, tabulations et retours à la ligne à un seul espace avant la comparaison
+    #[arg(long)]
+    normalize_whitespace: bool,
+
     /// Afficher seulement le nombre de résultats (pas le tableau)
     #[arg(short = 'c', long)]
     count_only: bool,
 
-    /// Sortie au format JSON
+    /// Ne pas afficher le message indiquant qu'aucun problème ne correspond
+    #[arg(short = 'q', long)]
+    quiet: bool,
+
+    /// Ne pas afficher la ligne « Nombre total de problèmes » du rapport texte
+    #[arg(long)]
+    no_total: bool,
+
+    /// Format de sortie
+    #[arg(long, visible_alias = "output-format", value_enum, value_name = "FORMAT", conflicts_with_all = ["json", "csv"])]
+    format: Option<OutputFormat>,
+
+    /// Écrire le résultat dans ce fichier plutôt que sur la sortie standard
+    #[arg(short = 'o', long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Avec --output, ajouter le résultat à la fin du fichier au lieu de le remplacer
+    /// (formats table, markdown, csv et ndjson)
+    #[arg(long, requires = "output")]
+    append: bool,
+
+    /// Sortie au format JSON (obsolète, équivalent à --format json)
     #[arg(long)]
     json: bool,
+
+    /// Avec --format json, écrire les problèmes un par un au fil de l'eau plutôt que
+    /// de construire le document entier en mémoire
+    #[arg(long)]
+    stream: bool,
+
+    /// Regrouper les problèmes de la sortie structurée par fichier d'entrée :
+    /// `[{"source_file": ..., "problems": [...]}, ...]`
+    #[arg(long, conflicts_with_all = ["unique_messages", "group_by", "count_only", "summary_only"])]
+    json_grouped: bool,
+
+    /// Sortie au format CSV (obsolète, équivalent à --format csv)
+    #[arg(long, conflicts_with_all = ["json", "count_only", "unique_messages", "group_by", "list_resources", "tree"])]
+    csv: bool,
+
+    /// Sortie sous forme de tableau HTML (équivalent à --format html)
+    #[arg(long, conflicts_with_all = ["json", "csv", "format"])]
+    html: bool,
+
+    /// Sortie au format SARIF 2.1.0 pour l'analyse de code en CI (équivalent à --format sarif)
+    #[arg(long, conflicts_with_all = ["json", "csv", "html", "format"])]
+    sarif: bool,
+
+    /// Sortie au format JUnit XML pour les rapports de tests en CI (équivalent à --format junit)
+    #[arg(long, conflicts_with_all = ["json", "csv", "html", "sarif", "format"])]
+    junit: bool,
+
+    /// Sortie au format `resource(line): message`, relue par un problem matcher VS Code
+    /// (équivalent à --format vscode-text)
+    #[arg(long, conflicts_with_all = ["json", "csv", "html", "sarif", "junit", "format"])]
+    vscode_text: bool,
+
+    /// Sortie sous forme de document HTML complet plutôt que d'un fragment
+    #[arg(long, conflicts_with_all = ["json", "csv"])]
+    html_full: bool,
+
+    /// Ne pas écrire la ligne d'en-tête en sortie CSV
+    #[arg(long)]
+    no_header: bool,
+
+    /// Séparateur de champs de la sortie CSV (un seul caractère, `\t` ou `tab` pour une tabulation) [défaut: ,]
+    #[arg(long, value_name = "CHAR", value_parser = parse_delimiter)]
+    delimiter: Option<char>,
+
+    /// Renommer des colonnes du tableau et de l'en-tête CSV, par exemple Resource=File,Line=Row
+    #[arg(long = "rename", value_name = "COLUMN=LABEL", value_delimiter = ',', value_parser = parse_rename)]
+    renames: Vec<(Column, String)>,
+
+    /// Nombre de composants conservés à la fin des chemins affichés (1 pour le nom du fichier seul) [défaut: 2]
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    path_depth: Option<usize>,
+
+    /// Afficher les chemins Windows avec des `/` plutôt que des `\`
+    #[arg(long)]
+    normalize_paths: bool,
+
+    /// Numérotation des lignes de l'export : 0 si la première ligne est numérotée 0,
+    /// les lignes affichées sont alors décalées de 1 [défaut: 1, aucun décalage]
+    #[arg(long, value_name = "0|1", value_parser = clap::value_parser!(u8).range(0..=1))]
+    line_base: Option<u8>,
+
+    /// Largeur maximale du tableau, en colonnes : les cellules trop larges (le message
+    /// en premier) sont renvoyées à la ligne [défaut: variable COLUMNS si la sortie est un terminal]
+    #[arg(long, value_name = "N")]
+    table_width: Option<usize>,
+
+    /// Tableaux compacts : pas de ligne de séparation entre les problèmes, seulement sous l'en-tête
+    #[arg(long)]
+    compact: bool,
+
+    /// Ajouter une colonne indiquant le fichier d'entrée dont provient chaque problème
+    #[arg(long)]
+    show_file: bool,
+
+    /// Ajouter une colonne Code (et un champ JSON `code`) avec le code du diagnostic
+    #[arg(long)]
+    show_code: bool,
+
+    /// Ajouter une colonne Offset (et un champ JSON `offset`) avec la position du problème
+    /// en caractères ou en octets, si l'export la fournit (`offset`, `startOffset`, `endOffset`)
+    #[arg(long)]
+    show_offsets: bool,
+
+    /// Ajouter une colonne Preview (et un champ JSON `preview`) avec le texte de la ligne
+    /// concernée, lu dans le fichier source (vide si le fichier ne peut pas être lu)
+    #[arg(long)]
+    source_preview: bool,
+
+    /// Ajouter une colonne Exists (et un champ JSON `exists`) indiquant si le fichier de chaque
+    /// problème existe sur le disque (`n/a` pour les URI), et lister les fichiers absents
+    #[arg(long)]
+    verify_resources: bool,
+
+    /// Dossier par rapport auquel sont résolues les ressources pour --source-preview,
+    /// --verify-resources et --resolve-symlinks [défaut: .]
+    #[arg(long, value_name = "DIR", requires = "source_readers")]
+    source_root: Option<PathBuf>,
+
+    /// Masquer les colonnes vides pour tous les problèmes affichés (par exemple Code ou File)
+    #[arg(long)]
+    auto_hide_empty: bool,
+
+    /// Classer les problèmes par catégorie (unused, deprecated, syntax, type-error, other)
+    /// d'après les mots-clés de leur message, et ajouter une colonne Kind
+    #[arg(long)]
+    classify: bool,
+
+    /// Garder uniquement les problèmes de cette catégorie (répétable)
+    #[arg(long = "kind", value_name = "NAME")]
+    kinds: Vec<String>,
+
+    /// Fichier TOML de règles de classification (`[[rule]]` avec `kind` et `keywords`),
+    /// prioritaires sur les règles par défaut
+    #[arg(long, value_name = "FILE")]
+    kind_rules: Option<PathBuf>,
+
+    /// Remplacer les tabulations des messages affichés par N espaces [défaut: 4]
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "4")]
+    expand_tabs: Option<usize>,
+
+    /// Afficher chaque message sur une seule ligne, les retours à la ligne et
+    /// tabulations (et l'indentation qui les suit) étant remplacés par une espace
+    #[arg(long)]
+    single_line: bool,
+
+    /// Troncature des messages de plus de 150 caractères
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = TruncateMode::Char)]
+    truncate_mode: TruncateMode,
+
+    /// Champ supplémentaire de l'export à recopier dans les sorties structurées (JSON, NDJSON, TOML), répétable
+    #[arg(long = "keep-field", value_name = "NAME")]
+    keep_fields: Vec<String>,
+
+    /// Afficher le chemin canonique de chaque fichier concerné (liens symboliques résolus, par
+    /// rapport à --source-root) ; le filtrage porte toujours sur le chemin d'origine
+    #[arg(long)]
+    resolve_symlinks: bool,
+
+    /// Supprimer les séquences d'échappement ANSI (couleurs) des messages affichés
+    #[arg(long)]
+    strip_ansi: bool,
+
+    /// Requête booléenne remplaçant les termes d'inclusion/exclusion,
+    /// par exemple: message~"deprecated" AND severity=error AND NOT path~"test"
+    #[arg(long, value_name = "EXPR", conflicts_with_all = ["include_terms", "exclude_terms"])]
+    query: Option<String>,
+
+    /// Afficher les informations associées (`relatedInformation`) de chaque problème retenu
+    /// comme des lignes supplémentaires
+    #[arg(long)]
+    flatten_related: bool,
+
+    /// Ajouter au message affiché les emplacements des informations associées
+    /// (`relatedInformation`), sous la forme `(voir fichier:ligne)`
+    #[arg(long, conflicts_with = "flatten_related")]
+    inline_related: bool,
+
+    /// Supprimer les problèmes en double (même fichier, ligne et message)
+    #[arg(long)]
+    dedup: bool,
+
+    /// Garder uniquement les problèmes présents plusieurs fois (même fichier, ligne et message),
+    /// regroupés par problème ; en JSON, la liste des groupes de doublons
+    #[arg(long, conflicts_with = "dedup")]
+    duplicates_only: bool,
+
+    /// Avec --dedup, conserver les doublons provenant de fichiers d'entrée différents
+    #[arg(long, requires = "dedup")]
+    keep_duplicates_across_files: bool,
+
+    /// Considérer comme un même fichier les chemins ne différant que par la casse
+    /// (déduplication, fichiers concernés, --count-distinct), en gardant la casse de la première occurrence
+    #[arg(long)]
+    resource_ci: bool,
+
+    /// Avec --dedup, lister sur la sortie d'erreur les groupes de doublons supprimés
+    #[arg(long, requires = "dedup")]
+    dedup_report: bool,
+
+    /// Avec --dedup, repérer les doublons par une empreinte de 64 bits de leur clé
+    /// (moins de mémoire sur les très grands exports, résultat identique)
+    #[arg(long, requires = "dedup")]
+    hashed_dedup: bool,
+
+    /// Indiquer aussi le nombre de problèmes filtrés distincts selon la clé choisie
+    #[arg(long, value_enum, value_name = "KEY")]
+    count_distinct: Option<dedup::DistinctBy>,
+
+    /// Normaliser les messages (segments entre guillemets, nombres) pour les regroupements
+    #[arg(long)]
+    normalize: bool,
+
+    /// Garder au plus N problèmes partageant le même message
+    #[arg(long, value_name = "N")]
+    per_message_limit: Option<usize>,
+
+    /// Regrouper les problèmes filtrés par message et afficher le nombre d'occurrences
+    #[arg(long)]
+    unique_messages: bool,
+
+    /// Ignorer les messages apparaissant moins de N fois (avec --unique-messages)
+    #[arg(long, value_name = "N", requires = "unique_messages")]
+    min_occurrences: Option<usize>,
+
+    /// Lister uniquement les fichiers concernés, un par ligne et triés
+    #[arg(long, conflicts_with_all = ["json", "count_only", "unique_messages"])]
+    list_resources: bool,
+
+    /// Afficher uniquement les messages des problèmes filtrés, un par ligne
+    /// (distincts avec --dedup, normalisés avec --normalize)
+    #[arg(long, conflicts_with_all = ["json", "csv", "count_only", "unique_messages", "list_resources"])]
+    messages_only: bool,
+
+    /// Fusionner les problèmes filtrés d'une même ligne d'un même fichier en une seule
+    /// ligne, leurs messages étant séparés par `; `
+    #[arg(long)]
+    merge_same_line: bool,
+
+    /// Ne considérer que les N premiers problèmes lus (tous fichiers confondus), avant filtrage
+    #[arg(long, value_name = "N")]
+    head_input: Option<usize>,
+
+    /// Arrêter le filtrage dès que N problèmes sont retenus, sans examiner les suivants
+    #[arg(long, value_name = "N")]
+    cap: Option<usize>,
+
+    /// Filtrer les problèmes en parallèle sur tous les cœurs (à partir de 10000 problèmes lus),
+    /// l'ordre des problèmes étant conservé
+    #[arg(long, conflicts_with = "cap")]
+    parallel: bool,
+
+    /// Interrompre l'exécution avec une erreur lorsque le filtrage et la mise en forme
+    /// dépassent SECS secondes, après avoir écrit les résultats partiels du filtrage
+    /// (limite indicative, vérifiée périodiquement : l'exécution n'est pas tuée)
+    #[arg(long, value_name = "SECS", conflicts_with = "parallel")]
+    max_runtime: Option<u64>,
+
+    /// Lire et parser les fichiers d'entrée, puis afficher le nombre de problèmes lus
+    /// et leur répartition par sévérité, sans filtrage (aucun terme n'est requis)
+    #[arg(long, conflicts_with_all = ["count_only", "summary_only", "unique_messages", "group_by", "list_resources", "messages_only", "tree"])]
+    dry_parse: bool,
+
+    /// Afficher uniquement les chiffres globaux : nombres de problèmes, répartition
+    /// par sévérité et fichiers concernés (un objet de synthèse en JSON)
+    #[arg(long, conflicts_with_all = ["count_only", "unique_messages", "group_by", "list_resources", "messages_only", "tree"])]
+    summary_only: bool,
+
+    /// Classer les fichiers concernés par score pondéré selon la sévérité de leurs
+    /// problèmes (par défaut error=10, warning=3, info=1, hint=0)
+    #[arg(long, conflicts_with_all = ["count_only", "summary_only", "unique_messages", "group_by", "list_resources", "messages_only", "tree"])]
+    rank_files: bool,
+
+    /// Poids d'une sévérité pour --rank-files, par exemple warning=5 (répétable)
+    #[arg(long, value_name = "SEVERITY=WEIGHT", value_parser = parse_severity_weight, requires = "rank_files")]
+    severity_weight: Vec<(Severity, u32)>,
+
+    /// Compter les problèmes filtrés par valeur d'un champ
+    #[arg(long, value_enum, value_name = "FIELD", conflicts_with_all = ["unique_messages", "list_resources"])]
+    group_by: Option<GroupBy>,
+
+    /// Largeur des tranches de lignes de --group-by line-bucket [défaut: 50]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    bucket_size: Option<u32>,
+
+    /// Ordre des groupes de --group-by et --unique-messages : count (nombre décroissant) ou name (alphabétique)
+    #[arg(long, value_enum, value_name = "ORDER", default_value_t = GroupSort::Count)]
+    group_sort: GroupSort,
+
+    /// Inverser l'ordre des groupes (--group-sort)
+    #[arg(long)]
+    group_reverse: bool,
+
+    /// Afficher les problèmes filtrés sous forme d'arborescence de fichiers
+    #[arg(long, conflicts_with_all = ["json", "unique_messages", "list_resources", "group_by"])]
+    tree: bool,
+
+    /// Fusionner les dossiers n'ayant qu'un seul sous-dossier dans l'arborescence (a/b/c/)
+    #[arg(long, requires = "tree")]
+    collapse_paths: bool,
+
+    /// Retirer de l'arborescence les nœuds (et leurs sous-dossiers) ayant moins de N problèmes
+    #[arg(long, value_name = "N", requires = "tree")]
+    tree_min: Option<usize>,
+
+    /// Trier les problèmes filtrés (tri stable, l'ordre d'origine est conservé à égalité)
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort_by: Option<SortBy>,
+
+    /// Inverser l'ordre du tri de --sort-by
+    #[arg(long, requires = "sort_by")]
+    reverse: bool,
+
+    /// Afficher un échantillon aléatoire de K problèmes parmi les problèmes filtrés
+    #[arg(long, value_name = "K")]
+    sample: Option<usize>,
+
+    /// Graine du générateur aléatoire pour un échantillon reproductible
+    #[arg(long, value_name = "N", requires = "sample")]
+    seed: Option<u64>,
+
+    /// Indiquer, pour chaque terme d'inclusion, le nombre de problèmes filtrés qui le contiennent
+    #[arg(long, requires = "include_terms", conflicts_with_all = ["unique_messages", "group_by", "list_resources"])]
+    term_stats: bool,
+
+    /// Indiquer, pour chaque terme d'inclusion, le nombre de problèmes de l'entrée complète
+    /// qui le contiennent, indépendamment du résultat du filtrage
+    #[arg(long, requires = "include_terms", conflicts_with_all = ["unique_messages", "group_by", "list_resources"])]
+    prevalence: bool,
+
+    /// Afficher le nombre de fichiers distincts concernés par les problèmes filtrés
+    #[arg(long)]
+    show_affected_files: bool,
+
+    /// Afficher la répartition des problèmes filtrés par longueur de message (tranches de 50 caractères)
+    #[arg(long)]
+    length_histogram: bool,
+
+    /// Afficher sur la sortie d'erreur la durée de lecture, de parsing et de filtrage
+    #[arg(long)]
+    timing: bool,
+
+    /// Afficher sur la sortie d'erreur le nombre de problèmes rejetés par chaque critère
+    /// de filtrage, dans l'ordre où ils sont évalués
+    #[arg(long)]
+    profile_filter: bool,
+
+    /// Code de sortie non nul si plus de N erreurs sont trouvées après filtrage
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// Code de sortie non nul si plus de N avertissements sont trouvés après filtrage
+    #[arg(long, value_name = "N")]
+    max_warnings: Option<usize>,
+
+    /// Code de sortie non nul si les problèmes filtrés concernent moins de N fichiers distincts
+    #[arg(long, value_name = "N")]
+    fail_if_resources_below: Option<usize>,
+
+    /// Code de sortie non nul si les problèmes filtrés concernent plus de N fichiers distincts
+    #[arg(long, value_name = "N")]
+    fail_if_resources_above: Option<usize>,
+
+    /// Code de sortie selon la sévérité la plus grave des problèmes filtrés :
+    /// 2 pour une erreur, 1 pour un avertissement, 0 sinon
+    #[arg(long)]
+    severity_exit_codes: bool,
+
+    /// Remplace le code de sortie d'une sévérité pour --severity-exit-codes, par exemple warning=0 (répétable)
+    #[arg(long, value_name = "SEVERITY=CODE", value_parser = parse_severity_exit_code, requires = "severity_exit_codes")]
+    severity_exit_code: Vec<(Severity, u8)>,
+}
+
+/// Lit un séparateur CSV : un seul caractère, ou `\t`/`tab` pour une tabulation
+fn parse_delimiter(value: &str) -> Result<char, String> {
+    if value == "\\t" || value.eq_ignore_ascii_case("tab") {
+        return Ok('\t');
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some('"' | '\n' | '\r'), None) => Err(format!("séparateur non autorisé: '{value}'")),
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("le séparateur doit être un seul caractère: '{value}'")),
+    }
+}
+
+/// Lit un renommage de colonne `Colonne=Libellé`
+fn parse_rename(value: &str) -> Result<(Column, String), String> {
+    let (name, label) = value
+        .split_once('=')
+        .ok_or_else(|| format!("renommage attendu sous la forme COLONNE=LIBELLÉ: '{value}'"))?;
+    let column = Column::from_header(name.trim()).ok_or_else(|| {
+        format!("colonne inconnue '{name}' (colonnes disponibles: Resource, Message, Line, File)")
+    })?;
+    Ok((column, label.trim().to_string()))
+}
+
+/// Compile une expression régulière de `--resource-regex`, sans tenir compte
+/// de la casse avec `--ignore-case-resource`
+fn parse_regex(value: &str, ignore_case: bool) -> Result<Regex, String> {
+    RegexBuilder::new(value)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| format!("expression régulière invalide: {e}"))
+}
+
+/// Lit un code de sortie associé à une sévérité `SÉVÉRITÉ=CODE`
+fn parse_severity_exit_code(value: &str) -> Result<(Severity, u8), String> {
+    let (name, code) = value
+        .split_once('=')
+        .ok_or_else(|| format!("code attendu sous la forme SÉVÉRITÉ=CODE: '{value}'"))?;
+    let code = code
+        .trim()
+        .parse()
+        .map_err(|_| format!("code de sortie invalide '{code}' (entier de 0 à 255 attendu)"))?;
+    Ok((parse_severity_name(name)?, code))
+}
+
+/// Lit un poids associé à une sévérité `SÉVÉRITÉ=POIDS`
+fn parse_severity_weight(value: &str) -> Result<(Severity, u32), String> {
+    let (name, weight) = value
+        .split_once('=')
+        .ok_or_else(|| format!("poids attendu sous la forme SÉVÉRITÉ=POIDS: '{value}'"))?;
+    let weight = weight
+        .trim()
+        .parse()
+        .map_err(|_| format!("poids invalide '{weight}' (entier positif attendu)"))?;
+    Ok((parse_severity_name(name)?, weight))
+}
+
+/// Lit un nom de sévérité (`error`, `warning`, `info`, `hint`)
+fn parse_severity_name(value: &str) -> Result<Severity, String> {
+    Severity::from_name(value.trim())
+        .ok_or_else(|| format!("sévérité inconnue '{value}' (valeurs possibles: error, warning, info, hint)"))
+}
+
+/// Lit une correspondance de sévérité `valeur=sévérité`
+fn parse_severity_mapping(value: &str) -> Result<(String, Severity), String> {
+    let (raw, name) = value
+        .split_once('=')
+        .ok_or_else(|| format!("correspondance attendue sous la forme VALEUR=SÉVÉRITÉ: '{value}'"))?;
+    Ok((raw.trim().to_string(), parse_severity_name(name)?))
 }
 
 impl CliProblemApp {
-    /// Filtre un problème selon les critères d'inclusion et d'exclusion
-    fn filter_problem(&self, problem: &Problem) -> bool {
-        let message = if self.ignore_case {
-            problem.message.to_lowercase()
+    /// Complète les options avec les critères de `--filter-json` : les options
+    /// données explicitement sur la ligne de commande restent prioritaires
+    fn with_criteria(&self, criteria: FilterCriteria) -> Result<Self> {
+        let mut cli = self.clone();
+        if cli.include_terms.is_empty() {
+            cli.include_terms = criteria.include;
+        }
+        if cli.exclude_terms.is_empty() {
+            cli.exclude_terms = criteria.exclude;
+        }
+        cli.include_min = cli.include_min.or(criteria.include_min);
+        cli.query = cli.query.or(criteria.query);
+        cli.ignore_case |= criteria.ignore_case;
+        cli.normalize_whitespace |= criteria.normalize_whitespace;
+        cli.min_msg_len = cli.min_msg_len.or(criteria.min_msg_len);
+        cli.max_msg_len = cli.max_msg_len.or(criteria.max_msg_len);
+        cli.drop_empty |= criteria.drop_empty;
+        cli.only_empty |= criteria.only_empty;
+        cli.loose_phrase |= criteria.loose_phrase;
+        cli.message_prefix_len = cli.message_prefix_len.or(criteria.message_prefix_len);
+        if cli.severities.is_empty() {
+            cli.severities = criteria
+                .severity
+                .iter()
+                .map(|name| parse_severity_name(name))
+                .collect::<Result<_, _>>()
+                .map_err(|error| anyhow::anyhow!("severity: {error}"))?;
+        }
+        if cli.kinds.is_empty() {
+            cli.kinds = criteria.kind;
+        }
+        cli.resource_regex = cli.resource_regex.or(criteria.resource_regex);
+        cli.exclude_resource_regex = cli.exclude_resource_regex.or(criteria.exclude_resource_regex);
+        cli.ignore_case_resource |= criteria.ignore_case_resource;
+        cli.ignore_generated |= criteria.ignore_generated;
+        if cli.generated_pattern.is_empty() {
+            cli.generated_pattern = criteria.generated_pattern;
+        }
+        cli.no_default_generated |= criteria.no_default_generated;
+        Ok(cli)
+    }
+
+    /// Format de sortie demandé, les options obsolètes `--json` et `--csv` étant des alias de `--format`
+    fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else if self.csv {
+            OutputFormat::Csv
+        } else if self.html {
+            OutputFormat::Html
+        } else if self.sarif {
+            OutputFormat::Sarif
+        } else if self.junit {
+            OutputFormat::Junit
+        } else if self.vscode_text {
+            OutputFormat::VscodeText
+        } else {
+            self.format.unwrap_or_default()
+        }
+    }
+
+    /// Clé utilisée pour rapprocher les problèmes ayant le même message
+    fn message_key(&self, problem: &Problem) -> String {
+        let message = if self.normalize_whitespace {
+            normalize::collapse_whitespace(&problem.message)
         } else {
             problem.message.clone()
         };
 
-        // Vérifier que tous les termes d'inclusion sont présents
-        let all_include_present = self.include_terms.iter().all(|term| {
-            let search_term = if self.ignore_case {
-                term.to_lowercase()
-            } else {
-                term.clone()
-            };
-            message.contains(&search_term)
-        });
+        if self.normalize {
+            normalize::normalize_message(&message)
+        } else {
+            message
+        }
+    }
 
-        // Vérifier qu'aucun terme d'exclusion n'est présent
-        let no_exclude_present = self.exclude_terms.iter().all(|term| {
-            let search_term = if self.ignore_case {
-                term.to_lowercase()
-            } else {
-                term.clone()
-            };
-            !message.contains(&search_term)
-        });
+    /// Met en forme un tableau de la sortie texte : style du format, et largeur
+    /// limitée par --table-width (sauf en Markdown, où une ligne doit le rester)
+    fn render_table(&self, table: tabled::Table) -> tabled::Table {
+        let format = self.output_format();
+        let table = match format {
+            OutputFormat::Table if self.compact => output::compact(table),
+            _ => format.style(table),
+        };
+        match self.table_width {
+            Some(width) if format == OutputFormat::Table => output::fit_width(table, width),
+            _ => table,
+        }
+    }
+
+    /// Code de sortie de `--severity-exit-codes` : celui de la sévérité la plus grave
+    /// présente, selon --severity-exit-code puis les codes par défaut (0 sans sévérité)
+    fn severity_exit_code(&self, problems: &[&Problem]) -> u8 {
+        let Some(highest) = problems.iter().filter_map(|problem| problem.severity).max() else {
+            return EXIT_SUCCESS;
+        };
+        self.severity_exit_code
+            .iter()
+            .rev()
+            .chain(&DEFAULT_SEVERITY_EXIT_CODES)
+            .find_map(|(severity, code)| (*severity == highest).then_some(*code))
+            .unwrap_or(EXIT_SUCCESS)
+    }
+
+    /// Indique si au moins un critère de filtrage est donné (termes, requête,
+    /// sévérité, catégorie, longueur, fichiers générés, messages vides)
+    fn has_filter_criteria(&self) -> bool {
+        !self.include_terms.is_empty()
+            || !self.exclude_terms.is_empty()
+            || self.query.is_some()
+            || !self.severities.is_empty()
+            || self.resource_regex.is_some()
+            || self.exclude_resource_regex.is_some()
+            || !self.kinds.is_empty()
+            || self.min_msg_len.is_some()
+            || self.max_msg_len.is_some()
+            || self.ignore_generated
+            || self.drop_empty
+            || self.only_empty
+    }
+
+    /// Critères de filtrage, avec les termes préparés une seule fois
+    fn problem_filter(&self) -> ProblemFilter<'_> {
+        let text = TextOptions { ignore_case: self.ignore_case, normalize_whitespace: self.normalize_whitespace };
+        let defaults: &[&str] = if self.no_default_generated { &[] } else { &DEFAULT_GENERATED_PATTERNS };
+        let generated_patterns = if self.ignore_generated {
+            defaults
+                .iter()
+                .copied()
+                .chain(self.generated_pattern.iter().map(String::as_str))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        ProblemFilter {
+            include_min: self.include_min,
+            loose_phrase: self.loose_phrase,
+            min_msg_len: self.min_msg_len,
+            max_msg_len: self.max_msg_len,
+            message_prefix_len: self.message_prefix_len,
+            drop_empty: self.drop_empty,
+            only_empty: self.only_empty,
+            severities: self.severities.clone(),
+            ..ProblemFilter::new(&self.include_terms, &self.exclude_terms, text, &self.synonym_groups)
+        }
+        .with_generated_patterns(generated_patterns, self.ignore_case_resource)
+    }
+
+    /// Nombre de problèmes contenant chaque terme d'inclusion, dans l'ordre des termes
+    fn term_stats(&self, problems: &[&Problem]) -> Vec<grouping::Group> {
+        let counts = self.problem_filter().term_counts(problems);
+        self.include_terms
+            .iter()
+            .zip(counts)
+            .map(|(term, count)| grouping::Group { key: term.clone(), count })
+            .collect()
+    }
+}
+
+/// Code de sortie en l'absence de seuil dépassé
+const EXIT_SUCCESS: u8 = 0;
+
+/// Code de sortie lorsqu'un seuil (`--max-errors`, `--max-warnings`) est dépassé
+const EXIT_THRESHOLD_EXCEEDED: u8 = 1;
+
+/// Largeur par défaut des tranches de `--group-by line-bucket`
+const DEFAULT_BUCKET_SIZE: u32 = 50;
+
+/// Nombre de problèmes lus à partir duquel `--parallel` filtre en parallèle
+const PARALLEL_THRESHOLD: usize = 10_000;
+
+/// Nombre de problèmes écrits par `--stream` entre deux vidages de la sortie
+const STREAM_FLUSH_INTERVAL: usize = 1024;
+
+/// Nombre de problèmes examinés entre deux vérifications de la durée `--max-runtime`
+const RUNTIME_CHECK_INTERVAL: usize = 1024;
 
-        all_include_present && no_exclude_present
+/// Codes de sortie par défaut de `--severity-exit-codes`
+const DEFAULT_SEVERITY_EXIT_CODES: [(Severity, u8); 4] =
+    [(Severity::Error, 2), (Severity::Warning, 1), (Severity::Info, 0), (Severity::Hint, 0)];
+
+fn main() -> Result<ExitCode> {
+    let mut cli = CliProblemApp::parse();
+
+    // Sans --ignore-file, le fichier .vpfignore du dossier courant est utilisé s'il existe
+    if cli.ignore_file.is_none() && !cli.no_ignore_file && Path::new(filter::DEFAULT_IGNORE_FILE).is_file() {
+        cli.ignore_file = Some(PathBuf::from(filter::DEFAULT_IGNORE_FILE));
+    }
+
+    // Sans --table-width, le tableau est ajusté à la largeur du terminal lorsqu'elle est connue
+    if cli.table_width.is_none() && cli.output.is_none() && std::io::stdout().is_terminal() {
+        cli.table_width = std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok());
     }
+
+    // Utiliser stdout (ou le fichier --output) et stderr comme writers pour l'exécution normale.
+    // Le fichier --output n'est ouvert qu'à la première écriture, une fois les options
+    // validées et les entrées lues.
+    let mut stderr = std::io::stderr();
+    let exit_code = match &cli.output {
+        Some(path) => {
+            let mut file = output::LazyFile::new(path.clone(), cli.append);
+            let exit_code = run_app(&cli, read_input, &mut file, &mut stderr)?;
+            file.finish()?;
+            exit_code
+        }
+        None => {
+            let mut stdout = std::io::stdout();
+            let exit_code = run_app(&cli, read_input, &mut stdout, &mut stderr)?;
+            stdout.flush()?;
+            exit_code
+        }
+    };
+    Ok(ExitCode::from(exit_code))
 }
 
-fn main() -> Result<()> {
-    let cli = CliProblemApp::parse();
+/// Lit un fichier d'entrée, ou l'entrée standard pour `-`
+fn read_input(path: &PathBuf) -> Result<Vec<u8>> {
+    if path.as_os_str() == "-" {
+        let mut content = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut content)
+            .with_context(|| "Impossible de lire l'entrée standard")?;
+        return Ok(content);
+    }
+    fs::read(path).with_context(|| format!("Impossible de lire le fichier: {path:?}"))
+}
 
-    // Utiliser stdout comme writer pour l'exécution normale
-    let mut stdout = std::io::stdout();
-    run_app(
-        &cli,
-    |p: &PathBuf| fs::read_to_string(p).with_context(|| format!("Impossible de lire le fichier: {p:?}")),
-        &mut stdout,
-    )
+/// Affiche l'entonnoir du filtrage (`--profile-filter`) : pour chaque critère, le
+/// nombre de problèmes qu'il rejette et le nombre de problèmes restants
+fn write_funnel<E: Write>(err: &mut E, funnel: &FilterFunnel, total: usize) -> std::io::Result<()> {
+    writeln!(err, "Entonnoir du filtrage:")?;
+    writeln!(err, "  Problèmes évalués: {total}")?;
+    let mut remaining = total;
+    for criterion in Criterion::ALL {
+        let rejected = funnel.rejected_by(criterion);
+        remaining -= rejected;
+        writeln!(err, "  - {}: {rejected} rejetés, {remaining} restants", criterion.name())?;
+    }
+    Ok(())
 }
 
-/// Function extracted from `main` to allow injecting a reader and an output writer for tests.
-fn run_app<F, W>(
+/// Function extracted from `main` to allow injecting a reader and output/warning writers for tests.
+/// Returns the process exit code.
+fn run_app<F, B, W, E>(
     cli: &CliProblemApp,
     read_fn: F,
     out: &mut W,
-) -> Result<()>
+    err: &mut E,
+) -> Result<u8>
 where
-    F: Fn(&PathBuf) -> Result<String>,
+    F: Fn(&PathBuf) -> Result<B>,
+    B: AsRef<[u8]>,
     W: Write,
+    E: Write,
 {
+    // L'entrée standard ne peut être lue qu'une seule fois
+    let stdin_users: Vec<&str> = [
+        (cli.inputs.iter().any(|path| path.as_os_str() == "-"), "--input"),
+        (cli.files_from.as_ref().is_some_and(|path| path.as_os_str() == "-"), "--files-from"),
+        (cli.include_from.as_ref().is_some_and(|path| path.as_os_str() == "-"), "--include-from"),
+        (cli.exclude_from.as_ref().is_some_and(|path| path.as_os_str() == "-"), "--exclude-from"),
+    ]
+    .into_iter()
+    .filter_map(|(uses_stdin, option)| uses_stdin.then_some(option))
+    .collect();
+    if stdin_users.len() > 1 {
+        anyhow::bail!("L'entrée standard (-) ne peut être lue que par une seule option: {}", stdin_users.join(", "));
+    }
+
+    // Termes lus dans des fichiers (--include-from, --exclude-from)
+    let mut resolved: Option<CliProblemApp> = None;
+    for (path, include) in [(&cli.include_from, true), (&cli.exclude_from, false)] {
+        let Some(path) = path else {
+            continue;
+        };
+        let content = read_fn(path)?;
+        let content = std::str::from_utf8(content.as_ref())
+            .with_context(|| format!("Liste de termes invalide (UTF-8 attendu): {path:?}"))?;
+        let terms = input::parse_term_list(content);
+        let resolved = resolved.get_or_insert_with(|| cli.clone());
+        if include {
+            resolved.include_terms.extend(terms);
+        } else {
+            resolved.exclude_terms.extend(terms);
+        }
+    }
+
+    // Critères de filtrage fournis en JSON
+    if let Some(spec) = &cli.filter_json {
+        let criteria = match spec.strip_prefix('@') {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                let content = read_fn(&path)?;
+                let content = String::from_utf8_lossy(content.as_ref()).into_owned();
+                FilterCriteria::parse(&content).with_context(|| format!("Critères de filtrage JSON invalides: {path:?}"))?
+            }
+            None => FilterCriteria::parse(spec).with_context(|| "Critères de filtrage JSON invalides")?,
+        };
+        resolved = Some(
+            resolved
+                .as_ref()
+                .unwrap_or(cli)
+                .with_criteria(criteria)
+                .with_context(|| "Critères de filtrage JSON invalides")?,
+        );
+    }
+
+    // Groupes de synonymes des termes
+    if let Some(path) = &cli.synonyms {
+        let content = read_fn(path)?;
+        let synonyms = Synonyms::from_toml(&String::from_utf8_lossy(content.as_ref()))
+            .with_context(|| format!("Impossible de lire les synonymes: {path:?}"))?;
+        resolved.get_or_insert_with(|| cli.clone()).synonym_groups = synonyms;
+    }
+    let cli = resolved.as_ref().unwrap_or(cli);
+
     // Validation des arguments
-    if cli.include_terms.is_empty() && cli.exclude_terms.is_empty() {
-        anyhow::bail!("Au moins un terme d'inclusion ou d'exclusion doit être spécifié");
+    if !cli.dry_parse && !cli.has_filter_criteria() {
+        anyhow::bail!(
+            "Au moins un critère de filtrage doit être spécifié (terme d'inclusion ou d'exclusion, --query, --severity, --kind, ...)"
+        );
+    }
+
+    // Les critères de --filter-json s'ajoutent aux options : les incompatibilités vérifiées
+    // par la ligne de commande le sont à nouveau après la fusion
+    if cli.query.is_some() && !(cli.include_terms.is_empty() && cli.exclude_terms.is_empty()) {
+        anyhow::bail!("--query n'est pas compatible avec les termes d'inclusion ou d'exclusion (--include, --exclude)");
+    }
+    if cli.drop_empty && cli.only_empty {
+        anyhow::bail!("--drop-empty n'est pas compatible avec --only-empty");
     }
 
-    // Lecture et parsing du fichier JSON
-    let file_content = read_fn(&cli.input)?;
+    if let Some(min) = cli.include_min
+        && !(1..=cli.include_terms.len()).contains(&min)
+    {
+        anyhow::bail!(
+            "--include-min doit être compris entre 1 et le nombre de termes d'inclusion ({})",
+            cli.include_terms.len()
+        );
+    }
 
-    let problems: Vec<Problem> =
-        serde_json::from_str(&file_content).with_context(|| "Erreur lors du parsing du JSON")?;
+    let format = cli.output_format();
+    if !format.is_text() {
+        let text_only = [
+            (cli.list_resources, "--list-resources"),
+            (cli.messages_only, "--messages-only"),
+            (cli.tree, "--tree"),
+        ];
+        let problems_only_incompatible = [
+            (cli.count_only, "--count-only"),
+            (cli.summary_only, "--summary-only"),
+            (cli.rank_files, "--rank-files"),
+            (cli.dry_parse, "--dry-parse"),
+            (cli.unique_messages, "--unique-messages"),
+            (cli.group_by.is_some(), "--group-by"),
+        ];
+        let conflict = text_only
+            .into_iter()
+            .chain(problems_only_incompatible.into_iter().filter(|_| format.is_problems_only()))
+            .find_map(|(enabled, flag)| enabled.then_some(flag));
+        if let Some(flag) = conflict {
+            anyhow::bail!("Le format {} n'est pas compatible avec {flag}", format.name());
+        }
+    }
 
-    // Filtrage des problèmes
-    let filtered_problems: Vec<ProblemOutput> = problems
-        .iter()
-        .filter(|problem| cli.filter_problem(problem))
-        .map(ProblemOutput::new)
-        .collect();
+    if cli.html_full && format != OutputFormat::Html {
+        anyhow::bail!("--html-full nécessite le format html");
+    }
 
-    if cli.json {
-        let json_output = serde_json::to_string_pretty(&filtered_problems)
-            .with_context(|| "Erreur lors de la sérialisation JSON")?;
-        writeln!(out, "{json_output}")?;
-        return Ok(());
+    if cli.stream && format != OutputFormat::Json {
+        anyhow::bail!("--stream nécessite le format json");
+    }
+    // Seul le tableau des problèmes est écrit au fil de l'eau
+    if cli.stream {
+        let not_streamable = [
+            (cli.count_only, "--count-only"),
+            (cli.summary_only, "--summary-only"),
+            (cli.rank_files, "--rank-files"),
+            (cli.dry_parse, "--dry-parse"),
+            (cli.unique_messages, "--unique-messages"),
+            (cli.group_by.is_some(), "--group-by"),
+            (cli.json_grouped, "--json-grouped"),
+            (cli.duplicates_only, "--duplicates-only"),
+            (cli.term_stats, "--term-stats"),
+            (cli.prevalence, "--prevalence"),
+            (cli.length_histogram, "--length-histogram"),
+            (cli.show_affected_files, "--show-affected-files"),
+            (cli.count_distinct.is_some(), "--count-distinct"),
+        ];
+        if let Some(flag) = not_streamable.into_iter().find_map(|(enabled, flag)| enabled.then_some(flag)) {
+            anyhow::bail!("--stream n'est pas compatible avec {flag}: seul le tableau des problèmes est écrit au fil de l'eau");
+        }
+    }
+
+    if cli.json_grouped && (format.is_text() || format.is_problems_only()) {
+        anyhow::bail!(
+            "--json-grouped nécessite un format structuré (json, ndjson ou toml), pas {}",
+            format.name()
+        );
+    }
+
+    if cli.append && matches!(format, OutputFormat::Json | OutputFormat::Toml | OutputFormat::Sarif | OutputFormat::Junit) {
+        anyhow::bail!(
+            "--append n'est pas compatible avec le format {} (formats possibles: table, markdown, csv, ndjson, html)",
+            format.name()
+        );
     }
 
-    writeln!(out, "Nombre total de problèmes: {}", problems.len())?;
+    if cli.no_header && format != OutputFormat::Csv {
+        writeln!(err, "Avertissement: --no-header est ignoré en dehors de la sortie CSV")?;
+    }
+
+    let query = cli.query.as_deref().map(Expr::parse).transpose()?;
+    // Expressions régulières des fichiers, compilées une seule fois selon --ignore-case-resource
+    let regex = |pattern: &Option<String>, option: &str| {
+        pattern
+            .as_deref()
+            .map(|pattern| parse_regex(pattern, cli.ignore_case_resource))
+            .transpose()
+            .map_err(|error| anyhow::anyhow!("{option}: {error}"))
+    };
+    let resource_regex = regex(&cli.resource_regex, "--resource-regex")?;
+    let exclude_resource_regex = regex(&cli.exclude_resource_regex, "--exclude-resource-regex")?;
+
+    // Lecture et parsing des fichiers JSON
+    let mut inputs = cli.inputs.clone();
+    if let Some(files_from) = &cli.files_from {
+        let list = read_fn(files_from)?;
+        let list = std::str::from_utf8(list.as_ref())
+            .with_context(|| format!("Liste de fichiers invalide (UTF-8 attendu): {files_from:?}"))?;
+        inputs.extend(input::parse_file_list(list, cli.null_data));
+    }
+    if let Some(dir) = &cli.input_dir {
+        inputs.extend(input::list_json_files(dir, cli.recursive)?);
+    }
+
+    if inputs.is_empty() {
+        anyhow::bail!("Aucun fichier d'entrée à lire");
+    }
+
+    let parse_options = ParseOptions {
+        format: cli.input_format,
+        encoding: cli.encoding,
+        jsonc: cli.jsonc,
+        json_seq: cli.json_seq,
+        json_pointer: cli.json_pointer.as_deref(),
+        strict: cli.strict_json,
+        severity: SeverityOptions {
+            field: cli.severity_field.as_deref(),
+            map: &cli.severity_map,
+        },
+    };
+
+    let mut reading = Duration::ZERO;
+    let mut parsing = Duration::ZERO;
+
+    let mut problems: Vec<Problem> = Vec::new();
+    for path in &inputs {
+        let start = Instant::now();
+        let file_content = read_fn(path);
+        reading += start.elapsed();
+
+        let start = Instant::now();
+        let parsed = file_content.and_then(|content| input::read_problems(content.as_ref(), path, &parse_options, err));
+        parsing += start.elapsed();
+
+        match parsed {
+            Ok(parsed) => problems.extend(parsed),
+            Err(error) if cli.skip_invalid => writeln!(err, "Fichier ignoré: {error:#}")?,
+            Err(error) => return Err(error),
+        }
+
+        // Les fichiers suivants ne sont pas lus une fois la limite --head-input atteinte
+        if let Some(head) = cli.head_input
+            && problems.len() >= head
+        {
+            problems.truncate(head);
+            break;
+        }
+    }
+
+    if cli.dry_parse {
+        write_dry_parse(cli, out, inputs.len(), &problems)?;
+        return Ok(EXIT_SUCCESS);
+    }
+
+    for (index, problem) in problems.iter_mut().enumerate() {
+        problem.index = index;
+    }
+
+    // Classification des problèmes (--classify, --kind)
+    let classifier = match &cli.kind_rules {
+        Some(path) => {
+            let content = read_fn(path)?;
+            Some(
+                Classifier::from_toml(&String::from_utf8_lossy(content.as_ref()))
+                    .with_context(|| format!("Impossible de lire les règles de classification: {path:?}"))?,
+            )
+        }
+        None => (cli.classify || !cli.kinds.is_empty() || cli.group_by == Some(GroupBy::Kind)).then(Classifier::default),
+    };
+    let kind_ok = |problem: &Problem| {
+        cli.kinds.is_empty()
+            || classifier
+                .as_ref()
+                .is_some_and(|classifier| cli.kinds.iter().any(|kind| kind == classifier.classify(problem)))
+    };
+
+    // Filtrage des problèmes
+    let start = Instant::now();
+    let over_budget = || cli.max_runtime.is_some_and(|secs| start.elapsed() > Duration::from_secs(secs));
+    let ignored = match &cli.ignore_file {
+        Some(path) => {
+            let content = read_fn(path)?;
+            Some(
+                filter::ignore_patterns(&String::from_utf8_lossy(content.as_ref()), cli.ignore_case_resource)
+                    .with_context(|| format!("Impossible de lire les motifs d'exclusion: {path:?}"))?,
+            )
+        }
+        None => None,
+    };
+    let filter = ProblemFilter {
+        ignored: ignored.as_ref(),
+        resource_regex: resource_regex.as_ref(),
+        exclude_resource_regex: exclude_resource_regex.as_ref(),
+        ..cli.problem_filter()
+    };
+    let decide = |problem: &Problem| {
+        // --query remplace les termes, les autres critères du filtre s'appliquent toujours
+        if !query.as_ref().is_none_or(|query| query.evaluate(problem, cli.ignore_case, cli.ignore_case || cli.ignore_case_resource)) {
+            return FilterDecision::Rejected(Criterion::Query);
+        }
+        match filter.decide(problem) {
+            FilterDecision::Kept if !kind_ok(problem) => FilterDecision::Rejected(Criterion::Kind),
+            decision => decision,
+        }
+    };
+    let mut funnel = FilterFunnel::default();
+    let mut evaluated = 0;
+    let mut timed_out = false;
+    let mut kept: Vec<&Problem> = if cli.parallel && problems.len() >= PARALLEL_THRESHOLD {
+        // Les décisions sont collectées dans l'ordre des problèmes
+        let decisions: Vec<FilterDecision> = problems.par_iter().map(decide).collect();
+        evaluated = problems.len();
+        problems
+            .iter()
+            .zip(decisions)
+            .filter(|(_, decision)| {
+                funnel.record(*decision);
+                *decision == FilterDecision::Kept
+            })
+            .map(|(problem, _)| problem)
+            .collect()
+    } else {
+        problems
+            .iter()
+            .enumerate()
+            // La durée --max-runtime n'est vérifiée que tous les RUNTIME_CHECK_INTERVAL problèmes
+            .take_while(|(index, _)| {
+                timed_out = index % RUNTIME_CHECK_INTERVAL == 0 && *index > 0 && over_budget();
+                !timed_out
+            })
+            .map(|(_, problem)| problem)
+            .filter(|problem| {
+                evaluated += 1;
+                let decision = decide(problem);
+                funnel.record(decision);
+                decision == FilterDecision::Kept
+            })
+            // Les problèmes suivants ne sont pas examinés une fois le plafond --cap atteint
+            .take(cli.cap.unwrap_or(usize::MAX))
+            .collect()
+    };
+    let filtering = start.elapsed();
+
+    // Les résultats partiels sont écrits avant de signaler le dépassement de --max-runtime
+    let interrupted = timed_out.then(|| {
+        format!(
+            "Durée maximale --max-runtime de {}s dépassée: filtrage interrompu après {evaluated} problèmes sur {}, \
+             seuls les résultats partiels ({} problèmes retenus) ont été écrits",
+            cli.max_runtime.unwrap_or_default(),
+            problems.len(),
+            kept.len()
+        )
+    });
+
+    if cli.cap.is_some_and(|cap| kept.len() >= cap) && evaluated < problems.len() {
+        writeln!(
+            err,
+            "Plafond --cap atteint: {} problèmes retenus, filtrage interrompu après {evaluated} problèmes sur {}",
+            kept.len(),
+            problems.len()
+        )?;
+    }
+    if cli.profile_filter {
+        write_funnel(err, &funnel, evaluated)?;
+    }
+
+    // Informations associées affichées à la suite de chaque problème retenu
+    let related: Vec<Vec<Problem>> = if cli.flatten_related {
+        kept.iter().map(|problem| problem.related()).collect()
+    } else {
+        Vec::new()
+    };
+    if cli.flatten_related {
+        kept = kept
+            .iter()
+            .zip(&related)
+            .flat_map(|(problem, related)| std::iter::once(*problem).chain(related))
+            .collect();
+    }
+
+    if cli.timing {
+        writeln!(err, "Durée de lecture: {reading:?}")?;
+        writeln!(err, "Durée du parsing: {parsing:?}")?;
+        writeln!(err, "Durée du filtrage: {filtering:?}")?;
+    }
+
+    let mut stats = ReportStats::default();
+
+    // Les seuils (sévérités, fichiers concernés) portent sur les problèmes filtrés, avant les sélections qui suivent
+    // (doublons, fusion, limite par message, échantillonnage)
+    let filtered = kept.clone();
+
+    // Suppression des doublons
+    if cli.dedup {
+        let key_builder = dedup::KeyBuilder {
+            per_file: cli.keep_duplicates_across_files,
+            resource_ci: cli.resource_ci,
+            ..Default::default()
+        };
+        let groups = if cli.hashed_dedup {
+            key_builder.dedup_groups_hashed(&mut kept)
+        } else {
+            key_builder.dedup_groups(&mut kept)
+        };
+        stats.duplicates = dedup::removed_count(&groups);
+
+        if cli.dedup_report {
+            writeln!(err, "Groupes de doublons supprimés: {}", groups.len())?;
+            for group in &groups {
+                writeln!(err, "  {} occurrences: {}", group.count, group.key)?;
+            }
+        }
+    }
+
+    // Fusion des problèmes d'une même ligne
+    let merged: Vec<Problem>;
+    if cli.merge_same_line {
+        merged = dedup::KeyBuilder { resource_ci: cli.resource_ci, ..Default::default() }.merge_same_line(&kept);
+        stats.merged = kept.len() - merged.len();
+        kept = merged.iter().collect();
+    }
+
+    // Limitation du nombre de problèmes par message
+    if let Some(limit) = cli.per_message_limit {
+        let mut per_message: HashMap<String, usize> = HashMap::new();
+        kept.retain(|problem| {
+            let count = per_message.entry(cli.message_key(problem)).or_insert(0);
+            *count += 1;
+            if *count > limit {
+                stats.suppressed += 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    // Échantillonnage aléatoire (l'ordre d'origine est conservé)
+    if let Some(size) = cli.sample
+        && size < kept.len()
+    {
+        let mut rng = match cli.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => rand::make_rng(),
+        };
+        let mut indices = rand::seq::index::sample(&mut rng, kept.len(), size).into_vec();
+        indices.sort_unstable();
+        stats.sampled_from = Some(kept.len());
+        kept = indices.into_iter().map(|index| kept[index]).collect();
+    }
+
+    if let Some(sort_by) = cli.sort_by {
+        sort::sort_problems(&mut kept, sort_by, cli.reverse);
+    }
+
+    let resource_keys = dedup::KeyBuilder { resource_ci: cli.resource_ci, ..Default::default() };
+
+    // Problèmes répétés, regroupés dans l'ordre de leur première occurrence
+    if cli.duplicates_only {
+        let groups = resource_keys.duplicate_groups(&kept);
+        stats.duplicate_groups = groups.len();
+        kept = groups.concat();
+    }
+
+    stats.affected_files = resource_keys.distinct_resources(&kept).len();
+
+    stats.distinct = cli
+        .count_distinct
+        .map(|by| dedup::KeyBuilder { by, ..resource_keys }.count_distinct(&kept));
+
+    let classifier = classifier.as_ref();
+    if interrupted.is_none() && over_budget() {
+        anyhow::bail!(
+            "Durée maximale --max-runtime de {}s dépassée avant l'écriture des résultats ({} problèmes retenus sur {}), \
+             aucun résultat n'a été écrit",
+            cli.max_runtime.unwrap_or_default(),
+            kept.len(),
+            problems.len()
+        );
+    }
+    write_report(cli, out, &problems, &kept, &stats, classifier)?;
+    if let Some(message) = interrupted {
+        anyhow::bail!(message);
+    }
+
+    // Fichiers absents du disque (--verify-resources)
+    if cli.verify_resources {
+        let root = cli.source_root.as_deref().unwrap_or(Path::new(preview::DEFAULT_SOURCE_ROOT));
+        let missing: Vec<&str> = resource_keys
+            .distinct_resources(&kept)
+            .into_iter()
+            .filter(|resource| preview::exists(root, resource) == Some(false))
+            .collect();
+        if !missing.is_empty() {
+            writeln!(err, "Fichiers introuvables sur le disque: {}", missing.len())?;
+            for resource in missing {
+                writeln!(err, "  {resource}")?;
+            }
+        }
+    }
+
+    // Seuils par sévérité
+    let exit_code = check_severity_thresholds(cli, &filtered, err)?;
+    let filtered_files = resource_keys.distinct_resources(&filtered).len();
+    let exit_code = exit_code.max(check_resource_thresholds(cli, filtered_files, err)?);
+    if cli.severity_exit_codes {
+        return Ok(exit_code.max(cli.severity_exit_code(&kept)));
+    }
+    Ok(exit_code)
+}
+
+/// Informations sur les sélections appliquées après le filtrage
+#[derive(Default)]
+struct ReportStats {
+    /// Doublons supprimés par `--dedup`
+    duplicates: usize,
+    /// Problèmes masqués par `--per-message-limit`
+    suppressed: usize,
+    /// Problèmes fusionnés avec un autre de la même ligne par `--merge-same-line`
+    merged: usize,
+    /// Groupes de problèmes répétés conservés par `--duplicates-only`
+    duplicate_groups: usize,
+    /// Nombre de problèmes filtrés avant échantillonnage par `--sample`
+    sampled_from: Option<usize>,
+    /// Nombre de fichiers distincts concernés par les problèmes retenus
+    affected_files: usize,
+    /// Nombre de problèmes distincts selon `--count-distinct`
+    distinct: Option<usize>,
+}
+
+/// Écrit le résultat du filtrage dans le format demandé
+fn write_report<W: Write>(
+    cli: &CliProblemApp,
+    out: &mut W,
+    problems: &[Problem],
+    kept: &[&Problem],
+    stats: &ReportStats,
+    classifier: Option<&Classifier>,
+) -> Result<()> {
+    // Chemins canoniques des fichiers des problèmes retenus, pour l'affichage uniquement
+    let root = cli.source_root.as_deref().unwrap_or(Path::new(preview::DEFAULT_SOURCE_ROOT));
+    let canonical = cli.resolve_symlinks.then(|| {
+        // Emplacements associés affichés par --inline-related
+        let related: Vec<Problem> = if cli.inline_related {
+            kept.iter().flat_map(|problem| problem.related()).collect()
+        } else {
+            Vec::new()
+        };
+        let resources = kept.iter().copied().chain(&related).map(|problem| problem.resource.as_str());
+        preview::canonical_paths(root, resources)
+    });
+
+    if cli.list_resources {
+        // Chemins complets par défaut, pour être passés à une autre commande ; --path-depth,
+        // --normalize-paths et --resolve-symlinks s'appliquent comme dans le tableau
+        let options = OutputOptions {
+            path_depth: cli.path_depth.unwrap_or(usize::MAX),
+            normalize_paths: cli.normalize_paths,
+            canonical: canonical.as_ref(),
+            ..Default::default()
+        };
+        let resources: BTreeSet<String> = dedup::KeyBuilder { resource_ci: cli.resource_ci, ..Default::default() }
+            .distinct_resources(kept)
+            .into_iter()
+            .map(|resource| problem::display_resource(resource, &options))
+            .collect();
+        for resource in resources {
+            writeln!(out, "{resource}")?;
+        }
+        return Ok(());
+    }
+
+    if cli.messages_only {
+        let mut seen = BTreeSet::new();
+        for problem in kept {
+            // Un message par ligne, même si le message d'origine en compte plusieurs
+            let message = cli.message_key(problem).replace(['\r', '\n'], " ");
+            if !cli.dedup || seen.insert(message.clone()) {
+                writeln!(out, "{message}")?;
+            }
+        }
+        return Ok(());
+    }
+
+    if cli.summary_only {
+        return write_summary(cli, out, problems.len(), kept, stats);
+    }
+
+    if cli.rank_files {
+        let ranks = grouping::rank_files(kept, &cli.severity_weight);
+        let format = cli.output_format();
+        if !format.is_text() {
+            let ranks: Vec<serde_json::Value> = ranks.iter().map(|rank| serde_json::json!(rank)).collect();
+            return write_list(out, format, "files", &ranks, &[]);
+        }
+        writeln!(out, "Classement des fichiers ({} problème(s) filtré(s)):", kept.len())?;
+        writeln!(out, "{}", cli.render_table(grouping::rank_table(&ranks)))?;
+        return Ok(());
+    }
+
+    // Regroupement par message
+    let message_groups = cli.unique_messages.then(|| {
+        let mut groups = grouping::group_by(kept, |problem| cli.message_key(problem));
+        if let Some(min) = cli.min_occurrences {
+            groups.retain(|group| group.count >= min);
+        }
+        cli.group_sort.sort(&mut groups, cli.group_reverse);
+        groups
+    });
+
+    // Regroupement par champ
+    let bucket_size = cli.bucket_size.unwrap_or(DEFAULT_BUCKET_SIZE);
+    let field_groups = cli
+        .group_by
+        .map(|group_by| {
+            let mut groups = grouping::group_by(kept, |problem| group_by.key(problem, bucket_size, classifier));
+            cli.group_sort.sort(&mut groups, cli.group_reverse);
+            groups
+        });
+
+    let term_stats = cli.term_stats.then(|| cli.term_stats(kept));
+
+    // Prévalence des termes sur l'ensemble des problèmes lus, avant filtrage
+    let prevalence = cli.prevalence.then(|| {
+        let all: Vec<&Problem> = problems.iter().collect();
+        let mut prevalence = cli.term_stats(&all);
+        grouping::sort_groups(&mut prevalence);
+        prevalence
+    });
+
+    let length_histogram = cli.length_histogram.then(|| grouping::length_histogram(kept));
+
+    // Informations ajoutées aux problèmes dans les sorties structurées
+    let mut summaries: Vec<(&str, serde_json::Value)> = [
+        ("term_stats", &term_stats),
+        ("prevalence", &prevalence),
+        ("length_histogram", &length_histogram),
+    ]
+        .into_iter()
+        .filter_map(|(name, groups)| groups.as_deref().map(|groups| (name, grouping::groups_map(groups).into())))
+        .collect();
+    if cli.show_affected_files {
+        summaries.push(("affected_files", stats.affected_files.into()));
+    }
+    if let Some(distinct) = stats.distinct {
+        summaries.push(("distinct_count", distinct.into()));
+    }
+
+    let output_options = OutputOptions {
+        path_depth: cli.path_depth.unwrap_or(problem::DEFAULT_PATH_DEPTH),
+        normalize_paths: cli.normalize_paths,
+        show_file: cli.show_file,
+        strip_ansi: cli.strip_ansi,
+        expand_tabs: cli.expand_tabs,
+        single_line: cli.single_line,
+        inline_related: cli.inline_related,
+        truncate_mode: cli.truncate_mode,
+        keep_fields: &cli.keep_fields,
+        show_code: cli.show_code,
+        show_offsets: cli.show_offsets,
+        line_offset: if cli.line_base == Some(0) { 1 } else { 0 },
+        // La colonne Kind n'est ajoutée qu'avec --classify
+        classifier: classifier.filter(|_| cli.classify),
+        canonical: canonical.as_ref(),
+    };
+    let mut filtered_problems: Vec<ProblemOutput> = kept
+        .iter()
+        .map(|problem| ProblemOutput::new(problem, &output_options))
+        .collect();
+    let mut columns = output::columns(&output_options);
+    if cli.source_preview {
+        let mut sources = preview::SourcePreview::new(root);
+        for (output, problem) in filtered_problems.iter_mut().zip(kept) {
+            output.preview = sources.line(&problem.resource, output.line);
+        }
+        columns.push(Column::Preview);
+    }
+    if cli.verify_resources {
+        for (output, problem) in filtered_problems.iter_mut().zip(kept) {
+            let exists = match preview::exists(root, &problem.resource) {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "n/a",
+            };
+            output.exists = Some(exists.to_string());
+        }
+        columns.push(Column::Exists);
+    }
+    if cli.auto_hide_empty {
+        columns = output::non_empty_columns(&columns, &filtered_problems);
+    }
+    let headers = output::headers(&columns, &cli.renames);
+
+    let format = cli.output_format();
+    match format {
+        OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Toml if cli.json_grouped => {
+            let files = problems_by_file(kept, &filtered_problems);
+            return write_list(out, format, "files", &files, &summaries);
+        }
+        OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Toml if cli.duplicates_only => {
+            let groups = duplicate_groups(cli, kept, &filtered_problems);
+            return write_list(out, format, "duplicates", &groups, &summaries);
+        }
+        OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Toml => {
+            let message_groups = message_groups.as_ref().map(|groups| {
+                groups
+                    .iter()
+                    .map(|group| serde_json::json!({ "message": group.key, "count": group.count }))
+                    .collect::<Vec<_>>()
+            });
+            let records = StructuredRecords {
+                message_groups: message_groups.as_deref(),
+                field_groups: field_groups.as_deref(),
+                problems: &filtered_problems,
+                summaries: &summaries,
+            };
+            // Les options incompatibles avec --stream ont été refusées par run_app
+            if cli.stream && records.is_problem_list() {
+                return write_json_stream(out, &filtered_problems);
+            }
+            return write_structured(out, format, &records, cli.count_only);
+        }
+        OutputFormat::Csv => {
+            let delimiter = cli.delimiter.unwrap_or(output::DEFAULT_DELIMITER);
+            let headers = (!cli.no_header).then_some(headers.as_slice());
+            output::write_csv(out, &filtered_problems, &columns, headers, delimiter)?;
+            return Ok(());
+        }
+        OutputFormat::Html => {
+            output::write_html(out, &filtered_problems, &columns, &headers, cli.html_full)?;
+            return Ok(());
+        }
+        OutputFormat::Sarif => {
+            let document = sarif::document(kept, output_options.line_offset);
+            let output = serde_json::to_string_pretty(&document).with_context(|| "Erreur lors de la sérialisation SARIF")?;
+            writeln!(out, "{output}")?;
+            return Ok(());
+        }
+        OutputFormat::Junit => {
+            junit::write_junit(out, kept, output_options.line_offset)?;
+            return Ok(());
+        }
+        OutputFormat::VscodeText => {
+            output::write_vscode_text(out, &filtered_problems)?;
+            return Ok(());
+        }
+        OutputFormat::Table | OutputFormat::Markdown => {}
+    }
+
+    if !cli.no_total {
+        writeln!(out, "Nombre total de problèmes: {}", problems.len())?;
+    }
+
+    if !cli.include_terms.is_empty() {
+        writeln!(out, "Termes à inclure: {}", cli.include_terms.join(", "))?;
+    }
+
+    if !cli.exclude_terms.is_empty() {
+        writeln!(out, "Termes à exclure: {}", cli.exclude_terms.join(", "))?;
+    }
+
+    if let Some(query) = &cli.query {
+        writeln!(out, "Requête: {query}")?;
+    }
+
+    if cli.ignore_case {
+        writeln!(out, "Mode insensible à la casse activé")?;
+    }
+
+    writeln!(out)?;
+
+    match stats.sampled_from {
+        Some(filtered) => writeln!(
+            out,
+            "Nombre de problèmes filtrés: {filtered} (échantillon aléatoire de {})",
+            filtered_problems.len()
+        )?,
+        None => writeln!(out, "Nombre de problèmes filtrés: {}", filtered_problems.len())?,
+    }
+
+    if let Some(distinct) = stats.distinct {
+        writeln!(out, "Nombre de problèmes distincts: {distinct}")?;
+    }
+
+    if cli.show_affected_files {
+        writeln!(out, "Fichiers concernés: {}", stats.affected_files)?;
+    }
+
+    if cli.dedup {
+        writeln!(out, "Doublons supprimés: {}", stats.duplicates)?;
+    }
+
+    if cli.merge_same_line {
+        writeln!(out, "Problèmes fusionnés sur une même ligne: {}", stats.merged)?;
+    }
+
+    if cli.duplicates_only {
+        writeln!(out, "Groupes de problèmes répétés: {}", stats.duplicate_groups)?;
+    }
+
+    if cli.per_message_limit.is_some() {
+        writeln!(out, "Problèmes masqués par la limite par message: {}", stats.suppressed)?;
+    }
+
+    if let Some(term_stats) = &term_stats {
+        writeln!(out)?;
+        writeln!(out, "Statistiques par terme:")?;
+        writeln!(out, "{}", cli.render_table(grouping::groups_table(term_stats, "Terme")))?;
+    }
+
+    if let Some(prevalence) = &prevalence {
+        writeln!(out)?;
+        writeln!(out, "Prévalence des termes (sur les {} problèmes lus):", problems.len())?;
+        writeln!(out, "{}", cli.render_table(grouping::groups_table(prevalence, "Terme")))?;
+    }
+
+    if let Some(histogram) = &length_histogram {
+        writeln!(out)?;
+        writeln!(out, "Longueur des messages (en caractères):")?;
+        writeln!(out, "{}", cli.render_table(grouping::histogram_table(histogram, "Longueur")))?;
+    }
+
+    if let Some(groups) = &message_groups {
+        writeln!(out, "Nombre de messages distincts: {}", groups.len())?;
+
+        if cli.count_only {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+
+        if groups.is_empty() {
+            if !cli.quiet {
+                writeln!(out, "Aucun message ne correspond aux critères de filtrage.")?;
+            }
+        } else {
+            writeln!(out, "{}", cli.render_table(grouping::groups_table(groups, "Message")))?;
+        }
+
+        return Ok(());
+    }
+
+    if let (Some(group_by), Some(groups)) = (cli.group_by, &field_groups) {
+        writeln!(out, "Nombre de groupes: {}", groups.len())?;
+
+        if cli.count_only {
+            return Ok(());
+        }
+
+        writeln!(out)?;
+
+        if groups.is_empty() {
+            if !cli.quiet {
+                writeln!(out, "Aucun problème ne correspond aux critères de filtrage.")?;
+            }
+        } else {
+            writeln!(out, "{}", cli.render_table(grouping::groups_table(groups, group_by.header())))?;
+        }
+
+        return Ok(());
+    }
+
+    if cli.count_only {
+        return Ok(());
+    }
+
+    writeln!(out)?;
+
+    // Affichage du tableau ou de l'arborescence
+    if filtered_problems.is_empty() {
+        if !cli.quiet {
+            writeln!(out, "Aucun problème ne correspond aux critères de filtrage.")?;
+        }
+    } else if cli.tree {
+        let mut tree = TreeNode::build(kept);
+        if let Some(min) = cli.tree_min {
+            tree = tree.prune(min);
+        }
+        if cli.collapse_paths {
+            tree = tree.collapse();
+        }
+        write!(out, "{}", tree.render())?;
+    } else {
+        let table = cli.render_table(output::problems_table(&filtered_problems, &columns, &headers));
+        writeln!(out, "{table}")?;
+    }
+
+    Ok(())
+}
+
+/// Écrit uniquement la synthèse du filtrage (`--summary-only`) : nombres de
+/// problèmes, répartition par sévérité et fichiers concernés
+fn write_summary<W: Write>(
+    cli: &CliProblemApp,
+    out: &mut W,
+    total: usize,
+    kept: &[&Problem],
+    stats: &ReportStats,
+) -> Result<()> {
+    let severities = severity_groups(kept);
+
+    let format = cli.output_format();
+    if !format.is_text() {
+        let mut summary = serde_json::Map::new();
+        summary.insert("total".to_string(), total.into());
+        summary.insert("filtered".to_string(), kept.len().into());
+        summary.insert("severities".to_string(), grouping::groups_map(&severities).into());
+        summary.insert("affected_files".to_string(), stats.affected_files.into());
+        if let Some(distinct) = stats.distinct {
+            summary.insert("distinct_count".to_string(), distinct.into());
+        }
+        return write_document(out, format, summary);
+    }
+
+    if !cli.no_total {
+        writeln!(out, "Nombre total de problèmes: {total}")?;
+    }
+    writeln!(out, "Nombre de problèmes filtrés: {}", kept.len())?;
+    if let Some(distinct) = stats.distinct {
+        writeln!(out, "Nombre de problèmes distincts: {distinct}")?;
+    }
+    writeln!(out, "Fichiers concernés: {}", stats.affected_files)?;
+    writeln!(out)?;
+    writeln!(out, "Répartition par sévérité:")?;
+    writeln!(out, "{}", cli.render_table(grouping::groups_table(&severities, "Sévérité")))?;
+    Ok(())
+}
+
+/// Contrôle de lecture (`--dry-parse`) : nombre de fichiers et de problèmes lus,
+/// et répartition par sévérité, sans aucun filtrage
+fn write_dry_parse<W: Write>(cli: &CliProblemApp, out: &mut W, files: usize, problems: &[Problem]) -> Result<()> {
+    let all: Vec<&Problem> = problems.iter().collect();
+    let severities = severity_groups(&all);
+
+    let format = cli.output_format();
+    if !format.is_text() {
+        let mut summary = serde_json::Map::new();
+        summary.insert("files".to_string(), files.into());
+        summary.insert("total".to_string(), problems.len().into());
+        summary.insert("severities".to_string(), grouping::groups_map(&severities).into());
+        return write_document(out, format, summary);
+    }
+
+    writeln!(out, "Fichiers lus: {files}")?;
+    writeln!(out, "Nombre total de problèmes: {}", problems.len())?;
+    writeln!(out)?;
+    writeln!(out, "Répartition par sévérité:")?;
+    writeln!(out, "{}", cli.render_table(grouping::groups_table(&severities, "Sévérité")))?;
+    Ok(())
+}
+
+/// Nombre de problèmes par sévérité, de la plus grave à la moins grave, puis sans sévérité
+fn severity_groups(problems: &[&Problem]) -> Vec<grouping::Group> {
+    let mut severities: Vec<grouping::Group> = Severity::ALL
+        .iter()
+        .map(|severity| grouping::Group {
+            key: severity.name().to_string(),
+            count: problems.iter().filter(|problem| problem.severity == Some(*severity)).count(),
+        })
+        .collect();
+    severities.push(grouping::Group {
+        key: "(none)".to_string(),
+        count: problems.iter().filter(|problem| problem.severity.is_none()).count(),
+    });
+    severities
+}
+
+/// Écrit un objet de synthèse dans un format structuré (JSON, NDJSON ou TOML)
+fn write_document<W: Write>(out: &mut W, format: OutputFormat, document: serde_json::Map<String, serde_json::Value>) -> Result<()> {
+    let output = match format {
+        OutputFormat::Toml => toml::to_string(&document).with_context(|| "Erreur lors de la sérialisation TOML")?,
+        OutputFormat::Ndjson => format!("{}\n", serde_json::Value::Object(document)),
+        _ => {
+            let output = serde_json::to_string_pretty(&document).with_context(|| "Erreur lors de la sérialisation JSON")?;
+            format!("{output}\n")
+        }
+    };
+    write!(out, "{output}")?;
+    Ok(())
+}
+
+/// Problèmes regroupés par fichier d'entrée, dans l'ordre de première apparition :
+/// `{"source_file": ..., "problems": [...]}`
+fn problems_by_file(kept: &[&Problem], problems: &[ProblemOutput]) -> Vec<serde_json::Value> {
+    let mut files: Vec<(String, Vec<&ProblemOutput>)> = Vec::new();
+    for (problem, output) in kept.iter().zip(problems) {
+        let source_file = problem
+            .origin
+            .as_ref()
+            .map(|origin| origin.display().to_string())
+            .unwrap_or_default();
+        match files.iter_mut().find(|(file, _)| *file == source_file) {
+            Some((_, problems)) => problems.push(output),
+            None => files.push((source_file, vec![output])),
+        }
+    }
+    files
+        .into_iter()
+        .map(|(source_file, problems)| serde_json::json!({ "source_file": source_file, "problems": problems }))
+        .collect()
+}
+
+/// Groupes de problèmes répétés (`--duplicates-only`) : les problèmes d'un même
+/// groupe sont consécutifs dans `kept`
+fn duplicate_groups(cli: &CliProblemApp, kept: &[&Problem], problems: &[ProblemOutput]) -> Vec<serde_json::Value> {
+    let key_builder = dedup::KeyBuilder { resource_ci: cli.resource_ci, ..Default::default() };
+    let mut groups: Vec<(String, Vec<&ProblemOutput>)> = Vec::new();
+    let mut previous = None;
+    for (problem, output) in kept.iter().zip(problems) {
+        let key = key_builder.key(problem);
+        match groups.last_mut() {
+            Some((_, outputs)) if previous.as_ref() == Some(&key) => outputs.push(output),
+            _ => groups.push((key.to_string(), vec![output])),
+        }
+        previous = Some(key);
+    }
+    groups
+        .into_iter()
+        .map(|(key, problems)| serde_json::json!({ "key": key, "count": problems.len(), "problems": problems }))
+        .collect()
+}
+
+/// Écrit une liste d'objets (par fichier pour `--json-grouped` et `--rank-files`,
+/// par groupe de doublons pour `--duplicates-only`) : un tableau JSON, ou un objet
+/// par ligne en NDJSON. Les informations complémentaires éventuelles accompagnent
+/// la liste, nommée `name`.
+fn write_list<W: Write>(
+    out: &mut W,
+    format: OutputFormat,
+    name: &str,
+    files: &[serde_json::Value],
+    summaries: &[(&str, serde_json::Value)],
+) -> Result<()> {
+    if format == OutputFormat::Ndjson {
+        for file in files {
+            writeln!(out, "{file}")?;
+        }
+        for (name, value) in summaries {
+            writeln!(out, "{}", serde_json::json!({ *name: value }))?;
+        }
+        return Ok(());
+    }
+
+    if format == OutputFormat::Json && summaries.is_empty() {
+        let output = serde_json::to_string_pretty(files).with_context(|| "Erreur lors de la sérialisation JSON")?;
+        writeln!(out, "{output}")?;
+        return Ok(());
+    }
+
+    // Le document TOML doit être une table : la liste y est nommée
+    let mut document = serde_json::Map::new();
+    document.insert(name.to_string(), files.into());
+    for (name, value) in summaries {
+        document.insert(name.to_string(), value.clone());
+    }
+    write_document(out, format, document)
+}
+
+/// Contenu d'un rapport structuré : groupes de messages, groupes par champ ou problèmes,
+/// accompagnés des informations complémentaires
+struct StructuredRecords<'a> {
+    message_groups: Option<&'a [serde_json::Value]>,
+    field_groups: Option<&'a [grouping::Group]>,
+    problems: &'a [ProblemOutput],
+    summaries: &'a [(&'a str, serde_json::Value)],
+}
+
+impl StructuredRecords<'_> {
+    /// Nombre d'enregistrements du rapport (groupes ou problèmes)
+    fn count(&self) -> usize {
+        match (self.message_groups, self.field_groups) {
+            (Some(groups), _) => groups.len(),
+            (None, Some(groups)) => groups.len(),
+            (None, None) => self.problems.len(),
+        }
+    }
+
+    /// Indique si le rapport se réduit au tableau des problèmes (sans groupe ni
+    /// information complémentaire), seul document écrit par `--stream`
+    fn is_problem_list(&self) -> bool {
+        self.message_groups.is_none() && self.field_groups.is_none() && self.summaries.is_empty()
+    }
+}
+
+/// Écrit le tableau JSON des problèmes élément par élément (`--stream`), sans
+/// construire le document en mémoire, la sortie étant vidée tous les
+/// `STREAM_FLUSH_INTERVAL` problèmes. Le tableau produit est le même que celui
+/// de `write_structured`, à la mise en forme près.
+fn write_json_stream<W: Write>(out: &mut W, problems: &[ProblemOutput]) -> Result<()> {
+    write!(out, "[")?;
+    for (index, problem) in problems.iter().enumerate() {
+        write!(out, "{}\n  ", if index == 0 { "" } else { "," })?;
+        serde_json::to_writer(&mut *out, problem).with_context(|| "Erreur lors de la sérialisation JSON")?;
+        if (index + 1) % STREAM_FLUSH_INTERVAL == 0 {
+            out.flush()?;
+        }
+    }
+    writeln!(out, "{}]", if problems.is_empty() { "" } else { "\n" })?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Écrit le rapport dans un format structuré (JSON, NDJSON ou TOML). Avec
+/// `count_only`, seul le nombre d'enregistrements est écrit (`{"count": N}`),
+/// suivi des informations complémentaires.
+fn write_structured<W: Write>(
+    out: &mut W,
+    format: OutputFormat,
+    records: &StructuredRecords,
+    count_only: bool,
+) -> Result<()> {
+    let StructuredRecords { message_groups, field_groups, problems, summaries } = *records;
+
+    if count_only {
+        let mut document = serde_json::Map::new();
+        document.insert("count".to_string(), records.count().into());
+        for (name, value) in summaries {
+            document.insert(name.to_string(), value.clone());
+        }
+        return write_document(out, format, document);
+    }
+
+    if format == OutputFormat::Ndjson {
+        let records: Vec<String> = match (message_groups, field_groups) {
+            (Some(groups), _) => groups.iter().map(|group| group.to_string()).collect(),
+            (None, Some(groups)) => groups.iter().map(|group| serde_json::json!(group).to_string()).collect(),
+            (None, None) => {
+                let mut records = problems
+                    .iter()
+                    .map(serde_json::to_string)
+                    .collect::<serde_json::Result<Vec<_>>>()
+                    .with_context(|| "Erreur lors de la sérialisation JSON")?;
+                for (name, value) in summaries {
+                    records.push(serde_json::json!({ *name: value }).to_string());
+                }
+                records
+            }
+        };
+        for record in records {
+            writeln!(out, "{record}")?;
+        }
+        return Ok(());
+    }
+
+    // Le document TOML doit être une table : les listes y sont nommées
+    let toml = format == OutputFormat::Toml;
+    let document = match (message_groups, field_groups) {
+        (Some(groups), _) if toml => serde_json::json!({ "messages": groups }),
+        (Some(groups), _) => serde_json::json!(groups),
+        (None, Some(groups)) if toml => serde_json::json!({ "groups": grouping::groups_map(groups) }),
+        (None, Some(groups)) => serde_json::Value::Object(grouping::groups_map(groups)),
+        (None, None) if !summaries.is_empty() => {
+            let mut document = serde_json::Map::new();
+            document.insert("problems".to_string(), serde_json::json!(problems));
+            for (name, value) in summaries {
+                document.insert(name.to_string(), value.clone());
+            }
+            serde_json::Value::Object(document)
+        }
+        (None, None) if toml => serde_json::json!({ "problems": problems }),
+        (None, None) => {
+            let output = serde_json::to_string_pretty(problems).with_context(|| "Erreur lors de la sérialisation JSON")?;
+            writeln!(out, "{output}")?;
+            return Ok(());
+        }
+    };
+
+    if toml {
+        let output = toml::to_string(&document).with_context(|| "Erreur lors de la sérialisation TOML")?;
+        write!(out, "{output}")?;
+    } else {
+        let output = serde_json::to_string_pretty(&document).with_context(|| "Erreur lors de la sérialisation JSON")?;
+        writeln!(out, "{output}")?;
+    }
+    Ok(())
+}
+
+/// Compare le nombre de problèmes par sévérité aux seuils `--max-errors` et
+/// `--max-warnings`, et renvoie le code de sortie correspondant
+fn check_severity_thresholds<E: Write>(cli: &CliProblemApp, filtered: &[&Problem], err: &mut E) -> Result<u8> {
+    let thresholds = [
+        (Severity::Error, cli.max_errors, "erreur(s)"),
+        (Severity::Warning, cli.max_warnings, "avertissement(s)"),
+    ];
+
+    let mut exit_code = EXIT_SUCCESS;
+    for (severity, max, label) in thresholds {
+        let Some(max) = max else {
+            continue;
+        };
+        let count = filtered.iter().filter(|problem| problem.severity == Some(severity)).count();
+        if count > max {
+            writeln!(err, "Seuil dépassé: {count} {label} (maximum autorisé: {max})")?;
+            exit_code = EXIT_THRESHOLD_EXCEEDED;
+        }
+    }
+
+    Ok(exit_code)
+}
+
+/// Compare le nombre de fichiers distincts concernés aux bornes
+/// `--fail-if-resources-below` et `--fail-if-resources-above`
+fn check_resource_thresholds<E: Write>(cli: &CliProblemApp, affected_files: usize, err: &mut E) -> Result<u8> {
+    let mut exit_code = EXIT_SUCCESS;
+    if let Some(min) = cli.fail_if_resources_below
+        && affected_files < min
+    {
+        writeln!(err, "Seuil non atteint: {affected_files} fichier(s) concerné(s) (minimum requis: {min})")?;
+        exit_code = EXIT_THRESHOLD_EXCEEDED;
+    }
+    if let Some(max) = cli.fail_if_resources_above
+        && affected_files > max
+    {
+        writeln!(err, "Seuil dépassé: {affected_files} fichier(s) concerné(s) (maximum autorisé: {max})")?;
+        exit_code = EXIT_THRESHOLD_EXCEEDED;
+    }
+    Ok(exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_app_json_output() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("does_not_matter.json")],
+            include_terms: vec!["deprecated".to_string()],
+            exclude_terms: vec![],
+            ignore_case: false,
+            count_only: false,
+            json: true,
+            ..Default::default()
+        };
+
+        // JSON in-memory with one problem matching
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "This is deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("This is deprecated"));
+        assert!(s.trim_start().starts_with('['));
+    }
+
+    fn run_format(cli: CliProblemApp) -> Result<String> {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            ..cli
+        };
+
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "a is deprecated", "source": "java" },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "b is deprecated", "source": "java" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink())?;
+        Ok(String::from_utf8(out).expect("invalid utf8"))
+    }
+
+    /// Exécute `run_app` avec des critères qu'aucun problème ne vérifie
+    fn run_empty(cli: CliProblemApp) -> String {
+        run_format(CliProblemApp { exclude_terms: vec!["deprecated".to_string()], ..cli }).expect("run_app failed")
+    }
+
+    #[test]
+    fn test_run_app_empty_result_in_every_format() {
+        let format = |format| CliProblemApp { format: Some(format), ..Default::default() };
+
+        let value: serde_json::Value = serde_json::from_str(&run_empty(format(OutputFormat::Json))).unwrap();
+        assert_eq!(value, serde_json::json!([]));
+
+        assert_eq!(run_empty(format(OutputFormat::Ndjson)), "");
+
+        let value: toml::Table = toml::from_str(&run_empty(format(OutputFormat::Toml))).unwrap();
+        assert_eq!(value["problems"].as_array().map(Vec::len), Some(0));
+
+        assert_eq!(run_empty(format(OutputFormat::Csv)), "Resource,Message,Line\n");
+
+        let html = run_empty(format(OutputFormat::Html));
+        assert!(html.contains("<tbody>\n</tbody>"), "unexpected output: {html}");
+
+        let value: serde_json::Value = serde_json::from_str(&run_empty(format(OutputFormat::Sarif))).unwrap();
+        assert_eq!(value["runs"][0]["results"], serde_json::json!([]));
+
+        assert_eq!(run_empty(format(OutputFormat::VscodeText)), "");
+
+        let xml = run_empty(format(OutputFormat::Junit));
+        assert!(xml.contains(r#"tests="0" failures="0">"#) && !xml.contains("<testsuite "), "unexpected output: {xml}");
+
+        for text_format in [OutputFormat::Table, OutputFormat::Markdown] {
+            let s = run_empty(format(text_format));
+            assert!(s.contains("Nombre de problèmes filtrés: 0"));
+            assert!(s.contains("Aucun problème ne correspond aux critères de filtrage."));
+
+            let s = run_empty(CliProblemApp { quiet: true, ..format(text_format) });
+            assert!(s.contains("Nombre de problèmes filtrés: 0"));
+            assert!(!s.contains("Aucun problème"), "unexpected output: {s}");
+        }
+    }
+
+    #[test]
+    fn test_run_app_count_only_structured() {
+        let count_only = |format| CliProblemApp { format: Some(format), count_only: true, ..Default::default() };
+
+        let value: serde_json::Value = serde_json::from_str(&run_empty(count_only(OutputFormat::Json))).unwrap();
+        assert_eq!(value, serde_json::json!({ "count": 0 }));
+        assert_eq!(run_empty(count_only(OutputFormat::Ndjson)), "{\"count\":0}\n");
+        assert_eq!(run_empty(count_only(OutputFormat::Toml)), "count = 0\n");
+
+        let s = run_format(CliProblemApp { show_affected_files: true, ..count_only(OutputFormat::Json) }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value, serde_json::json!({ "count": 2, "affected_files": 2 }));
+
+        let s = run_format(CliProblemApp { group_by: Some(GroupBy::Source), ..count_only(OutputFormat::Json) }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value, serde_json::json!({ "count": 1 }));
+    }
+
+    #[test]
+    fn test_run_app_resource_ci() {
+        let run = |resource_ci| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                dedup: true,
+                show_affected_files: true,
+                resource_ci,
+                ..Default::default()
+            };
+            let json = r#"[
+                { "resource": "Src/File.ts", "startLineNumber": 1, "message": "deprecated" },
+                { "resource": "src/file.ts", "startLineNumber": 1, "message": "deprecated" },
+                { "resource": "src/file.ts", "startLineNumber": 2, "message": "deprecated" }
+            ]"#;
+
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(false);
+        assert!(s.contains("Doublons supprimés: 0"));
+        assert!(s.contains("Fichiers concernés: 2"));
+
+        let s = run(true);
+        assert!(s.contains("Doublons supprimés: 1"));
+        assert!(s.contains("Fichiers concernés: 1"));
+        assert!(s.contains("| Src/File.ts "), "unexpected output: {s}");
+    }
+
+    #[test]
+    fn test_run_app_no_total() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "a is deprecated" },
+            { "resource": "src/b.ts", "startLineNumber": 2, "message": "other" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                no_total: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        for s in [run(CliProblemApp::default()), run(CliProblemApp { summary_only: true, ..Default::default() })] {
+            assert!(!s.contains("Nombre total de problèmes"), "unexpected output: {s}");
+            assert!(s.contains("Nombre de problèmes filtrés: 1"), "unexpected output: {s}");
+        }
+
+        // Sans effet sur les formats structurés
+        let value: serde_json::Value =
+            serde_json::from_str(&run(CliProblemApp { summary_only: true, json: true, ..Default::default() })).unwrap();
+        assert_eq!(value["total"], 2);
+    }
+
+    #[test]
+    fn test_run_app_rank_files() {
+        let json = r#"[
+            { "resource": "src/warnings.ts", "startLineNumber": 1, "message": "a is deprecated", "severity": 4 },
+            { "resource": "src/warnings.ts", "startLineNumber": 2, "message": "b is deprecated", "severity": 4 },
+            { "resource": "src/error.ts", "startLineNumber": 3, "message": "c is deprecated", "severity": 8 },
+            { "resource": "src/info.ts", "startLineNumber": 4, "message": "d is deprecated", "severity": 2 }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                rank_files: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink())
+                .map(|_| String::from_utf8(out).unwrap())
+        };
+
+        let s = run(CliProblemApp::default()).unwrap();
+        let error = s.find("src/error.ts").unwrap();
+        let warnings = s.find("src/warnings.ts").unwrap();
+        assert!(error < warnings && warnings < s.find("src/info.ts").unwrap(), "unexpected ranking: {s}");
+        assert!(s.contains("| src/warnings.ts | 6     | 0       | 2              | 0      |"), "unexpected ranking: {s}");
+
+        let s = run(CliProblemApp { severity_weight: vec![(Severity::Warning, 6)], json: true, ..Default::default() }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(
+            value[0],
+            serde_json::json!({ "resource": "src/warnings.ts", "score": 12, "errors": 0, "warnings": 2, "others": 0 })
+        );
+        assert_eq!(value[1]["resource"], "src/error.ts");
+
+        let err = run(CliProblemApp { format: Some(OutputFormat::Csv), ..Default::default() }).unwrap_err();
+        assert_eq!(format!("{err}"), "Le format csv n'est pas compatible avec --rank-files");
+    }
+
+    #[test]
+    fn test_parse_severity_weight() {
+        assert_eq!(parse_severity_weight("error = 20"), Ok((Severity::Error, 20)));
+        assert!(parse_severity_weight("error").is_err());
+        assert!(parse_severity_weight("hint=-1").unwrap_err().contains("poids invalide"));
+    }
+
+    #[test]
+    fn test_run_app_summary_only() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "a is deprecated", "severity": 8 },
+            { "resource": "src/a.ts", "startLineNumber": 2, "message": "b is deprecated", "severity": 4 },
+            { "resource": "src/b.ts", "startLineNumber": 3, "message": "c is deprecated" },
+            { "resource": "src/c.ts", "startLineNumber": 4, "message": "other", "severity": 8 }
+        ]"#;
+        let run = |format| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                summary_only: true,
+                format: Some(format),
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(OutputFormat::Table);
+        assert!(s.contains("Nombre total de problèmes: 4"));
+        assert!(s.contains("Nombre de problèmes filtrés: 3"));
+        assert!(s.contains("Fichiers concernés: 2"));
+        assert!(s.contains("| error    | 1           |"), "unexpected output: {s}");
+        assert!(s.contains("| (none)   | 1           |"), "unexpected output: {s}");
+        assert!(!s.contains("is deprecated"), "unexpected per-problem rows: {s}");
+
+        let value: serde_json::Value = serde_json::from_str(&run(OutputFormat::Json)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "total": 4,
+                "filtered": 3,
+                "severities": { "error": 1, "warning": 1, "info": 0, "hint": 0, "(none)": 1 },
+                "affected_files": 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_app_table_width() {
+        let json = r#"[{ "resource": "src/a.ts", "startLineNumber": 1,
+            "message": "the function foo is deprecated and will be removed in the next major version" }]"#;
+        let run = |table_width, format| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                table_width,
+                format,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+        let table_lines = |s: &str| s.lines().filter(|line| line.starts_with(['|', '+'])).map(str::to_string).collect::<Vec<_>>();
+
+        let lines = table_lines(&run(Some(40), None));
+        assert!(lines.iter().all(|line| line.chars().count() <= 40), "unexpected table: {lines:#?}");
+        assert!(lines.iter().any(|line| line.contains("| src/a.ts ")), "unexpected table: {lines:#?}");
+
+        let lines = table_lines(&run(None, None));
+        assert!(lines.iter().any(|line| line.chars().count() > 40));
+
+        // Le Markdown n'est pas replié
+        let lines = table_lines(&run(Some(40), Some(OutputFormat::Markdown)));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_run_app_compact_table() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "a is deprecated" },
+            { "resource": "src/b.ts", "startLineNumber": 2, "message": "b is deprecated" },
+            { "resource": "src/c.ts", "startLineNumber": 3, "message": "c is deprecated" }
+        ]"#;
+        let separators = |compact| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                compact,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            let s = String::from_utf8(out).unwrap();
+            assert_eq!(s.lines().filter(|line| line.starts_with('|')).count(), 4, "unexpected table: {s}");
+            s.lines().filter(|line| line.starts_with('+')).count()
+        };
+
+        // Bordures haute et basse, et une ligne sous l'en-tête
+        assert_eq!(separators(true), 3);
+        assert_eq!(separators(false), 5);
+    }
+
+    #[test]
+    fn test_run_app_classify_and_kind() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'x' is declared but its value is never read." },
+            { "resource": "src/a.ts", "startLineNumber": 2, "message": "'y' is deprecated." },
+            { "resource": "src/b.ts", "startLineNumber": 3, "message": "Missing semicolon." }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            // Tous les messages se terminent par un point
+            let cli = CliProblemApp { inputs: vec![PathBuf::from("x.json")], include_terms: vec![".".to_string()], ..cli };
+            let read_fn = |p: &PathBuf| match p.to_str() {
+                Some("x.json") => Ok(json.to_string()),
+                Some("rules.toml") => Ok("[[rule]]\nkind = \"style\"\nkeywords = [\"semicolon\"]\n".to_string()),
+                _ => anyhow::bail!("unexpected path {p:?}"),
+            };
+            let mut out = Vec::new();
+            run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+        let s = run(CliProblemApp { classify: true, format: Some(OutputFormat::Csv), ..Default::default() });
+        assert_eq!(
+            s.lines().collect::<Vec<_>>(),
+            [
+                "Resource,Message,Line,Kind",
+                "src/a.ts,'x' is declared but its value is never read.,1,unused",
+                "src/a.ts,'y' is deprecated.,2,deprecated",
+                "src/b.ts,Missing semicolon.,3,other",
+            ]
+        );
+
+        let s = run(CliProblemApp { kinds: vec!["deprecated".to_string()], json: true, ..Default::default() });
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value.as_array().map(Vec::len), Some(1));
+        assert_eq!(value[0]["line"], 2);
+        assert!(value[0].get("kind").is_none());
+
+        let s = run(CliProblemApp {
+            classify: true,
+            kind_rules: Some(PathBuf::from("rules.toml")),
+            kinds: vec!["style".to_string()],
+            json: true,
+            ..Default::default()
+        });
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value[0]["kind"], "style");
+        assert_eq!(value.as_array().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn test_run_app_dry_parse_without_terms() {
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp { inputs: vec![PathBuf::from("a.json"), PathBuf::from("b.json")], dry_parse: true, ..cli };
+            let read_fn = |p: &PathBuf| match p.to_str() {
+                Some("a.json") => Ok(r#"[{ "resource": "a", "startLineNumber": 1, "message": "m", "severity": 8 },
+                                        { "resource": "a", "startLineNumber": 2, "message": "m" }]"#
+                    .to_string()),
+                Some("b.json") => Ok(r#"{ "resource": "b", "startLineNumber": 1, "message": "m", "severity": 8 }"#.to_string()),
+                _ => anyhow::bail!("unexpected path {p:?}"),
+            };
+            let mut out = Vec::new();
+            let code = run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+            assert_eq!(code, EXIT_SUCCESS);
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(CliProblemApp::default());
+        assert!(s.contains("Fichiers lus: 2"));
+        assert!(s.contains("Nombre total de problèmes: 3"));
+        assert!(s.contains("| error    | 2           |"), "unexpected output: {s}");
+        assert!(!s.contains("filtrés"), "unexpected output: {s}");
+
+        let value: serde_json::Value = serde_json::from_str(&run(CliProblemApp { json: true, ..Default::default() })).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "files": 2,
+                "total": 3,
+                "severities": { "error": 2, "warning": 0, "info": 0, "hint": 0, "(none)": 1 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_app_severity_filter_without_terms() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            severities: vec![Severity::Error, Severity::Hint],
+            json: true,
+            ..Default::default()
+        };
+        let json = r#"[
+            { "resource": "a", "startLineNumber": 1, "message": "m1", "severity": 8 },
+            { "resource": "a", "startLineNumber": 2, "message": "m2", "severity": 4 },
+            { "resource": "a", "startLineNumber": 3, "message": "m3" },
+            { "resource": "a", "startLineNumber": 4, "message": "m4", "severity": "hint" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let lines: Vec<&serde_json::Value> = value.as_array().unwrap().iter().map(|problem| &problem["line"]).collect();
+        assert_eq!(lines, [1, 4]);
+    }
+
+    #[test]
+    fn test_run_app_other_criteria_without_terms() {
+        let criteria = [
+            CliProblemApp { kinds: vec!["deprecated".to_string()], ..Default::default() },
+            CliProblemApp { min_msg_len: Some(1), ..Default::default() },
+            CliProblemApp { ignore_generated: true, ..Default::default() },
+            CliProblemApp { drop_empty: true, ..Default::default() },
+        ];
+        for cli in criteria {
+            let cli = CliProblemApp { inputs: vec![PathBuf::from("x.json")], ..cli };
+            let result = run_app(&cli, |_p: &PathBuf| Ok("[]".to_string()), &mut std::io::sink(), &mut std::io::sink());
+            assert!(result.is_ok(), "unexpected error: {result:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_severity_name() {
+        assert_eq!(parse_severity_name("Warning"), Ok(Severity::Warning));
+        assert!(parse_severity_name("fatal").unwrap_err().contains("sévérité inconnue 'fatal'"));
+    }
+
+    #[test]
+    fn test_run_app_length_histogram() {
+        let run = |json_output: bool| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["x".to_string()],
+                length_histogram: true,
+                count_only: !json_output,
+                json: json_output,
+                ..Default::default()
+            };
+            let json = format!(
+                r#"[
+                    {{ "resource": "a", "startLineNumber": 1, "message": "x" }},
+                    {{ "resource": "a", "startLineNumber": 2, "message": "{}" }},
+                    {{ "resource": "a", "startLineNumber": 3, "message": "{}" }}
+                ]"#,
+                "x".repeat(60),
+                "x".repeat(120)
+            );
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.clone()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(false);
+        assert!(s.contains("Longueur des messages (en caractères):"));
+        assert!(s.contains("| 0-50     | 1           |"), "unexpected output: {s}");
+        assert!(s.contains("| 101-150  | 1           |"), "unexpected output: {s}");
+
+        let value: serde_json::Value = serde_json::from_str(&run(true)).unwrap();
+        assert_eq!(value["length_histogram"], serde_json::json!({ "0-50": 1, "51-100": 1, "101-150": 1 }));
+        assert_eq!(value["problems"].as_array().map(Vec::len), Some(3));
+    }
+
+    #[test]
+    fn test_run_app_resource_regex() {
+        let run = |resource_regex: Option<&str>, exclude_resource_regex: Option<&str>| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                resource_regex: resource_regex.map(str::to_string),
+                exclude_resource_regex: exclude_resource_regex.map(str::to_string),
+                list_resources: true,
+                ..Default::default()
+            };
+            let json = r#"[
+                { "resource": "/ws/front/src/app.ts", "startLineNumber": 1, "message": "m" },
+                { "resource": "/ws/back/src/api.rs", "startLineNumber": 1, "message": "m" },
+                { "resource": "/ws/front/test/app.spec.ts", "startLineNumber": 1, "message": "m" }
+            ]"#;
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(run(Some(r"/(front|back)/src/"), None), "/ws/back/src/api.rs\n/ws/front/src/app.ts\n");
+        assert_eq!(run(None, Some(r"\.spec\.ts$")), "/ws/back/src/api.rs\n/ws/front/src/app.ts\n");
+        assert_eq!(run(Some("front"), Some("/test/")), "/ws/front/src/app.ts\n");
+        assert_eq!(run(Some("^/other/"), None), "");
+    }
+
+    #[test]
+    fn test_parse_regex_invalid() {
+        let err = parse_regex("src/(a|b", false).unwrap_err();
+        assert!(err.starts_with("expression régulière invalide"), "unexpected error: {err}");
+        assert!(!parse_regex("^src/", false).unwrap().is_match("SRC/a.ts"));
+        assert!(parse_regex("^src/", true).unwrap().is_match("SRC/a.ts"));
+    }
+
+    #[test]
+    fn test_run_app_auto_hide_empty_code() {
+        let run = |auto_hide_empty, code: &str| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                show_code: true,
+                auto_hide_empty,
+                csv: true,
+                ..Default::default()
+            };
+            let json = format!(
+                r#"[{{ "resource": "a.ts", "startLineNumber": 1, "message": "deprecated"{code} }},
+                    {{ "resource": "b.ts", "startLineNumber": 2, "message": "deprecated" }}]"#
+            );
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.clone()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(run(false, ""), "Resource,Message,Line,Code\na.ts,deprecated,1,\nb.ts,deprecated,2,\n");
+        assert_eq!(run(true, ""), "Resource,Message,Line\na.ts,deprecated,1\nb.ts,deprecated,2\n");
+        assert_eq!(
+            run(true, r#", "code": "TS6385""#),
+            "Resource,Message,Line,Code\na.ts,deprecated,1,TS6385\nb.ts,deprecated,2,\n"
+        );
+    }
+
+    #[test]
+    fn test_run_app_source_preview() {
+        let root = std::env::temp_dir().join(format!("vpf-source-preview-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.ts"), "import { x } from './x';\n  x.old();\n").unwrap();
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 9, "message": "deprecated" },
+            { "resource": "src/missing.ts", "startLineNumber": 1, "message": "deprecated" }
+        ]"#;
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            source_preview: true,
+            source_root: Some(root.clone()),
+            csv: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Resource,Message,Line,Preview\nsrc/a.ts,deprecated,2,x.old();\nsrc/a.ts,deprecated,9,\nsrc/missing.ts,deprecated,1,\n"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_run_app_verify_resources() {
+        let root = std::env::temp_dir().join(format!("vpf-verify-resources-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.ts"), "").unwrap();
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/deleted.ts", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "https://example.com/lib.ts", "startLineNumber": 3, "message": "deprecated" },
+            { "resource": "src/deleted.ts", "startLineNumber": 4, "message": "deprecated" }
+        ]"#;
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            verify_resources: true,
+            source_root: Some(root.clone()),
+            path_depth: Some(usize::MAX),
+            csv: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut err).expect("run_app failed");
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Resource,Message,Line,Exists\nsrc/a.ts,deprecated,1,yes\nsrc/deleted.ts,deprecated,2,no\n\
+             https://example.com/lib.ts,deprecated,3,n/a\nsrc/deleted.ts,deprecated,4,no\n"
+        );
+        assert_eq!(String::from_utf8(err).unwrap(), "Fichiers introuvables sur le disque: 1\n  src/deleted.ts\n");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_run_app_show_offsets() {
+        let json = r#"[
+            { "resource": "a.ts", "startLineNumber": 1, "message": "deprecated", "startOffset": 120, "endOffset": 135 },
+            { "resource": "b.ts", "startLineNumber": 2, "message": "deprecated", "offset": 42 },
+            { "resource": "c.ts", "startLineNumber": 3, "message": "deprecated" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(CliProblemApp { show_offsets: true, csv: true, ..Default::default() });
+        assert_eq!(
+            s,
+            "Resource,Message,Line,Offset\na.ts,deprecated,1,120-135\nb.ts,deprecated,2,42\nc.ts,deprecated,3,\n"
+        );
+
+        let s = run(CliProblemApp { show_offsets: true, json: true, ..Default::default() });
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value[0]["offset"], "120-135");
+        assert!(value[2].get("offset").is_none());
+
+        // Sans --show-offsets, les champs de l'export ne sont pas repris
+        assert!(!run(CliProblemApp { csv: true, ..Default::default() }).contains("120"));
+    }
+
+    #[test]
+    fn test_run_app_group_sort() {
+        let run = |group_sort, group_reverse| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["m".to_string()],
+                group_by: Some(GroupBy::Source),
+                group_sort,
+                group_reverse,
+                format: Some(OutputFormat::Ndjson),
+                ..Default::default()
+            };
+            let json = r#"[
+                { "resource": "a", "startLineNumber": 1, "message": "m", "source": "tsc" },
+                { "resource": "a", "startLineNumber": 2, "message": "m", "source": "eslint" },
+                { "resource": "a", "startLineNumber": 3, "message": "m", "source": "tsc" },
+                { "resource": "a", "startLineNumber": 4, "message": "m", "source": "biome" },
+                { "resource": "a", "startLineNumber": 5, "message": "m", "source": "eslint" }
+            ]"#;
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["key"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(GroupSort::Count, false), ["eslint", "tsc", "biome"]);
+        assert_eq!(run(GroupSort::Count, true), ["biome", "eslint", "tsc"]);
+        assert_eq!(run(GroupSort::Name, false), ["biome", "eslint", "tsc"]);
+        assert_eq!(run(GroupSort::Name, true), ["tsc", "eslint", "biome"]);
+    }
+
+    #[test]
+    fn test_run_app_parallel_matches_serial() {
+        let problems: Vec<serde_json::Value> = (0..PARALLEL_THRESHOLD + 500)
+            .map(|i| {
+                let message = match i % 3 {
+                    0 => format!("'symbol{i}' is deprecated"),
+                    1 => format!("'symbol{i}' is deprecated in tests"),
+                    _ => format!("Cannot find name 'symbol{i}'"),
+                };
+                serde_json::json!({
+                    "resource": format!("src/file{}.ts", i % 40),
+                    "startLineNumber": i % 300 + 1,
+                    "message": message,
+                    "severity": 8 >> (i % 3),
+                })
+            })
+            .collect();
+        let json = serde_json::to_string(&problems).unwrap();
+        let run = |parallel: bool| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                exclude_terms: vec!["tests".to_string()],
+                severities: vec![Severity::Error, Severity::Warning],
+                parallel,
+                profile_filter: true,
+                csv: true,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            let mut err = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.clone()), &mut out, &mut err).expect("run_app failed");
+            (String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+        };
+
+        let (serial, serial_funnel) = run(false);
+        let (parallel, parallel_funnel) = run(true);
+        assert_eq!(serial.lines().count(), 1 + (PARALLEL_THRESHOLD + 500).div_ceil(3));
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel_funnel, serial_funnel);
+    }
+
+    #[test]
+    fn test_run_app_max_runtime() {
+        let problems: Vec<serde_json::Value> = (0..5000)
+            .map(|i| serde_json::json!({ "resource": format!("src/{i}.ts"), "startLineNumber": i, "message": "'x' is deprecated" }))
+            .collect();
+        let json = serde_json::to_string(&problems).unwrap();
+        let run = |json: &str, max_runtime: u64| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                max_runtime: Some(max_runtime),
+                count_only: true,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            let result = run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink());
+            (result.map_err(|error| error.to_string()), String::from_utf8(out).unwrap())
+        };
+
+        // Budget nul : le filtrage s'arrête à la première vérification, les résultats partiels sont écrits
+        let (result, out) = run(&json, 0);
+        assert_eq!(
+            result.unwrap_err(),
+            "Durée maximale --max-runtime de 0s dépassée: filtrage interrompu après 1024 problèmes sur 5000, \
+             seuls les résultats partiels (1024 problèmes retenus) ont été écrits"
+        );
+        assert!(out.contains("Nombre de problèmes filtrés: 1024"));
+
+        // Filtrage terminé, mais budget dépassé avant l'écriture
+        let small = r#"[{ "resource": "src/a.ts", "startLineNumber": 1, "message": "'x' is deprecated" }]"#;
+        let (result, out) = run(small, 0);
+        assert!(result.unwrap_err().contains("dépassée avant l'écriture des résultats (1 problèmes retenus sur 1)"));
+        assert!(out.is_empty());
+
+        let (result, out) = run(&json, 3600);
+        assert_eq!(result, Ok(0));
+        assert!(out.contains("Nombre de problèmes filtrés: 5000"));
+        assert!(CliProblemApp::try_parse_from(["prog", "-f", "x.json", "-i", "a", "--max-runtime", "1"]).is_ok());
+        assert!(CliProblemApp::try_parse_from(["prog", "-f", "x.json", "-i", "a", "--max-runtime", "1", "--parallel"]).is_err());
+    }
+
+    #[test]
+    fn test_run_app_cap() {
+        let json = r#"[
+            { "resource": "a.ts", "startLineNumber": 1, "message": "'a' is deprecated" },
+            { "resource": "b.ts", "startLineNumber": 1, "message": "Missing semicolon" },
+            { "resource": "c.ts", "startLineNumber": 1, "message": "'c' is deprecated" },
+            { "resource": "d.ts", "startLineNumber": 1, "message": "'d' is deprecated" },
+            { "resource": "e.ts", "startLineNumber": 1, "message": "'e' is deprecated" }
+        ]"#;
+        let run = |cap: usize| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                cap: Some(cap),
+                profile_filter: true,
+                csv: true,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            let mut err = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut err).expect("run_app failed");
+            (String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+        };
+
+        let (s, err) = run(2);
+        assert_eq!(s, "Resource,Message,Line\na.ts,'a' is deprecated,1\nc.ts,'c' is deprecated,1\n");
+        assert!(
+            err.contains("Plafond --cap atteint: 2 problèmes retenus, filtrage interrompu après 3 problèmes sur 5"),
+            "unexpected stderr: {err}"
+        );
+        // Les problèmes suivants n'ont pas été examinés
+        assert!(err.contains("  Problèmes évalués: 3\n"), "unexpected stderr: {err}");
+
+        // Plafond non atteint : aucune note
+        let (s, err) = run(10);
+        assert_eq!(s.lines().count(), 5);
+        assert!(!err.contains("Plafond"));
+    }
+
+    #[test]
+    fn test_run_app_head_input() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("a.json"), PathBuf::from("b.json")],
+            include_terms: vec!["m".to_string()],
+            head_input: Some(3),
+            count_only: true,
+            ..Default::default()
+        };
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("a.json") => Ok(r#"[{ "resource": "a", "startLineNumber": 1, "message": "m" },
+                                    { "resource": "a", "startLineNumber": 2, "message": "m" }]"#
+                .to_string()),
+            Some("b.json") => Ok(r#"[{ "resource": "b", "startLineNumber": 1, "message": "m" },
+                                    { "resource": "b", "startLineNumber": 2, "message": "m" }]"#
+                .to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.contains("Nombre total de problèmes: 3"), "unexpected output: {s}");
+        assert!(s.contains("Nombre de problèmes filtrés: 3"), "unexpected output: {s}");
+
+        // Le second fichier n'est pas lu si la limite est atteinte avec le premier
+        let cli = CliProblemApp { head_input: Some(2), ..cli };
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("a.json") => read_fn(p),
+            _ => anyhow::bail!("unexpected read of {p:?}"),
+        };
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        assert!(String::from_utf8(out).unwrap().contains("Nombre total de problèmes: 2"));
+    }
+
+    #[test]
+    fn test_run_app_synonyms() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["unused".to_string()],
+            synonyms: Some(PathBuf::from("synonyms.toml")),
+            term_stats: true,
+            json: true,
+            ..Default::default()
+        };
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("synonyms.toml") => Ok(r#"unused = ["never used", "is not used"]"#.to_string()),
+            Some("x.json") => Ok(r#"[
+                { "resource": "a", "startLineNumber": 1, "message": "unused import" },
+                { "resource": "a", "startLineNumber": 2, "message": "'x' is never used" },
+                { "resource": "a", "startLineNumber": 3, "message": "'y' is not used" },
+                { "resource": "a", "startLineNumber": 4, "message": "'z' is deprecated" }
+            ]"#
+            .to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["problems"].as_array().map(Vec::len), Some(3));
+        assert_eq!(value["term_stats"], serde_json::json!({ "unused": 3 }));
+    }
+
+    #[test]
+    fn test_path_depth_rejects_zero() {
+        assert!(CliProblemApp::try_parse_from(["prog", "-f", "x.json", "-i", "a", "--path-depth", "0"]).is_err());
+        let cli = CliProblemApp::try_parse_from(["prog", "-f", "x.json", "-i", "a", "--path-depth", "1"]).unwrap();
+        assert_eq!(cli.path_depth, Some(1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_app_resolve_symlinks() {
+        let root = std::env::temp_dir().join(format!("vpf-app-symlinks-{}", std::process::id()));
+        fs::create_dir_all(root.join("real")).unwrap();
+        fs::write(root.join("real/a.ts"), "").unwrap();
+        std::os::unix::fs::symlink("real", root.join("link")).unwrap();
+        let json = r#"[
+            { "resource": "link/a.ts", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "link/missing.ts", "startLineNumber": 2, "message": "deprecated" }
+        ]"#;
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            // Le filtrage porte sur le chemin d'origine
+            resource_regex: Some("^link/".to_string()),
+            resolve_symlinks: true,
+            source_root: Some(root.clone()),
+            path_depth: Some(usize::MAX),
+            csv: true,
+            no_header: true,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let canonical = fs::canonicalize(root.join("real/a.ts")).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{},deprecated,1\nlink/missing.ts,deprecated,2\n", canonical.display())
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_source_root_requires_source_reader() {
+        let parse = |args: &[&str]| CliProblemApp::try_parse_from(["prog", "-f", "x.json", "-i", "a"].iter().chain(args));
+        assert!(parse(&["--source-root", "src"]).is_err());
+        assert!(parse(&["--source-root", "src", "--source-preview"]).is_ok());
+        assert!(parse(&["--source-root", "src", "--verify-resources"]).is_ok());
+        assert!(parse(&["--source-root", "src", "--source-preview", "--verify-resources"]).is_ok());
+    }
+
+    #[test]
+    fn test_run_app_line_base() {
+        let run = |line_base, format| {
+            run_format(CliProblemApp { line_base, format: Some(format), ..Default::default() }).unwrap()
+        };
+
+        assert!(run(None, OutputFormat::Csv).contains("src/a.java,a is deprecated,1\n"));
+        assert!(run(Some(1), OutputFormat::Csv).contains("src/a.java,a is deprecated,1\n"));
+        assert!(run(Some(0), OutputFormat::Csv).contains("src/a.java,a is deprecated,2\n"));
+        assert!(run(Some(0), OutputFormat::Table).contains("| src/a.java | a is deprecated | 2    |"));
+
+        let value: serde_json::Value = serde_json::from_str(&run(Some(0), OutputFormat::Json)).unwrap();
+        assert_eq!(value[1]["line"], 3);
+
+        assert!(CliProblemApp::try_parse_from(["prog", "-f", "x.json", "-i", "a", "--line-base", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_run_app_severity_exit_codes() {
+        let run = |severities: &[u64], overrides: Vec<(Severity, u8)>| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["m".to_string()],
+                severity_exit_codes: true,
+                severity_exit_code: overrides,
+                count_only: true,
+                ..Default::default()
+            };
+            let problems: Vec<serde_json::Value> = severities
+                .iter()
+                .map(|severity| serde_json::json!({ "resource": "a", "startLineNumber": 1, "message": "m", "severity": severity }))
+                .chain([serde_json::json!({ "resource": "a", "startLineNumber": 2, "message": "m" })])
+                .collect();
+            let json = serde_json::to_string(&problems).unwrap();
+            run_app(&cli, |_p: &PathBuf| Ok(json.clone()), &mut std::io::sink(), &mut std::io::sink()).expect("run_app failed")
+        };
+
+        assert_eq!(run(&[4, 8, 1], vec![]), 2);
+        assert_eq!(run(&[4, 2], vec![]), 1);
+        assert_eq!(run(&[2, 1], vec![]), 0);
+        assert_eq!(run(&[], vec![]), 0);
+
+        assert_eq!(run(&[4, 8], vec![(Severity::Error, 10)]), 10);
+        assert_eq!(run(&[4], vec![(Severity::Warning, 0)]), 0);
+        assert_eq!(run(&[2], vec![(Severity::Info, 3)]), 3);
+    }
+
+    #[test]
+    fn test_parse_severity_exit_code() {
+        assert_eq!(parse_severity_exit_code("warning=0"), Ok((Severity::Warning, 0)));
+        assert!(parse_severity_exit_code("warning").is_err());
+        assert!(parse_severity_exit_code("error=256").unwrap_err().contains("code de sortie invalide"));
+    }
+
+    #[test]
+    fn test_run_app_duplicates_only() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'a' is deprecated" },
+            { "resource": "src/b.ts", "startLineNumber": 2, "message": "'b' is deprecated" },
+            { "resource": "src/c.ts", "startLineNumber": 3, "message": "'c' is deprecated" },
+            { "resource": "src/b.ts", "startLineNumber": 2, "message": "'b' is deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'a' is deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'a' is deprecated" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                duplicates_only: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(CliProblemApp { csv: true, no_header: true, ..Default::default() });
+        assert_eq!(
+            s,
+            "src/a.ts,'a' is deprecated,1\nsrc/a.ts,'a' is deprecated,1\nsrc/a.ts,'a' is deprecated,1\n\
+             src/b.ts,'b' is deprecated,2\nsrc/b.ts,'b' is deprecated,2\n"
+        );
+        assert!(run(CliProblemApp::default()).contains("Groupes de problèmes répétés: 2"));
+
+        let value: serde_json::Value = serde_json::from_str(&run(CliProblemApp { json: true, ..Default::default() })).unwrap();
+        let groups = value.as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0]["key"], "src/a.ts:1: 'a' is deprecated");
+        assert_eq!(groups[0]["count"], 3);
+        assert_eq!(groups[0]["problems"].as_array().map(Vec::len), Some(3));
+        assert_eq!(groups[1]["count"], 2);
+    }
+
+    #[test]
+    fn test_run_app_json_grouped() {
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("front.json"), PathBuf::from("back.json")],
+                include_terms: vec!["deprecated".to_string()],
+                json_grouped: true,
+                ..cli
+            };
+            let read_fn = |p: &PathBuf| match p.to_str() {
+                Some("front.json") => Ok(r#"[{ "resource": "src/a.ts", "startLineNumber": 1, "message": "a is deprecated" },
+                                            { "resource": "src/b.ts", "startLineNumber": 2, "message": "b is deprecated" }]"#
+                    .to_string()),
+                Some("back.json") => Ok(r#"[{ "resource": "src/c.rs", "startLineNumber": 3, "message": "c is deprecated" },
+                                           { "resource": "src/d.rs", "startLineNumber": 4, "message": "other" }]"#
+                    .to_string()),
+                _ => anyhow::bail!("unexpected path {p:?}"),
+            };
+            let mut out = Vec::new();
+            run_app(&cli, read_fn, &mut out, &mut std::io::sink()).map(|_| String::from_utf8(out).unwrap())
+        };
+
+        let value: serde_json::Value = serde_json::from_str(&run(CliProblemApp { json: true, ..Default::default() }).unwrap()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {
+                    "source_file": "front.json",
+                    "problems": [
+                        { "resource": "src/a.ts", "message": "a is deprecated", "line": 1 },
+                        { "resource": "src/b.ts", "message": "b is deprecated", "line": 2 }
+                    ]
+                },
+                {
+                    "source_file": "back.json",
+                    "problems": [{ "resource": "src/c.rs", "message": "c is deprecated", "line": 3 }]
+                }
+            ])
+        );
+
+        let s = run(CliProblemApp { format: Some(OutputFormat::Toml), show_affected_files: true, ..Default::default() }).unwrap();
+        let value: toml::Table = toml::from_str(&s).unwrap();
+        assert_eq!(value["files"].as_array().map(Vec::len), Some(2));
+        assert_eq!(value["affected_files"].as_integer(), Some(3));
+
+        let err = run(CliProblemApp::default()).unwrap_err();
+        assert_eq!(format!("{err}"), "--json-grouped nécessite un format structuré (json, ndjson ou toml), pas table");
+    }
+
+    #[test]
+    fn test_output_format_legacy_aliases() {
+        assert_eq!(CliProblemApp::default().output_format(), OutputFormat::Table);
+        assert_eq!(CliProblemApp { json: true, ..Default::default() }.output_format(), OutputFormat::Json);
+        assert_eq!(CliProblemApp { csv: true, ..Default::default() }.output_format(), OutputFormat::Csv);
+        assert_eq!(
+            CliProblemApp { format: Some(OutputFormat::Toml), ..Default::default() }.output_format(),
+            OutputFormat::Toml
+        );
+    }
+
+    #[test]
+    fn test_run_app_format_json_matches_legacy_flag() {
+        let legacy = run_format(CliProblemApp { json: true, ..Default::default() }).unwrap();
+        let format = run_format(CliProblemApp { format: Some(OutputFormat::Json), ..Default::default() }).unwrap();
+        assert_eq!(legacy, format);
+        assert!(format.trim_start().starts_with('['));
+    }
+
+    #[test]
+    fn test_run_app_format_ndjson() {
+        let s = run_format(CliProblemApp { format: Some(OutputFormat::Ndjson), ..Default::default() }).unwrap();
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"resource":"src/a.java","message":"a is deprecated","line":1}"#);
+
+        let s = run_format(CliProblemApp {
+            format: Some(OutputFormat::Ndjson),
+            group_by: Some(GroupBy::Source),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(s, "{\"count\":2,\"key\":\"java\"}\n");
+    }
+
+    #[test]
+    fn test_run_app_format_toml() {
+        let s = run_format(CliProblemApp { format: Some(OutputFormat::Toml), ..Default::default() }).unwrap();
+        assert!(s.contains("[[problems]]"), "unexpected output: {s}");
+        assert!(s.contains("message = \"a is deprecated\""), "unexpected output: {s}");
+
+        let s = run_format(CliProblemApp {
+            format: Some(OutputFormat::Toml),
+            unique_messages: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(s.contains("[[messages]]"), "unexpected output: {s}");
+        assert!(s.contains("count = 1"), "unexpected output: {s}");
+    }
+
+    #[test]
+    fn test_run_app_format_markdown() {
+        let s = run_format(CliProblemApp { format: Some(OutputFormat::Markdown), ..Default::default() }).unwrap();
+        assert!(s.contains("Nombre de problèmes filtrés: 2"));
+        assert!(s.contains("| Resource   | Message         | Line |"), "unexpected output: {s}");
+    }
+
+    #[test]
+    fn test_run_app_keep_field_json() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            keep_fields: vec!["owner".to_string()],
+            json: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "a is deprecated", "owner": "team-a" },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "b is deprecated" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+        assert_eq!(value[0]["owner"], "team-a");
+        assert_eq!(value[1].get("owner"), None);
+    }
+
+    #[test]
+    fn test_parse_rename() {
+        assert_eq!(parse_rename("Resource=File"), Ok((Column::Resource, "File".to_string())));
+        assert_eq!(parse_rename("line = Row"), Ok((Column::Line, "Row".to_string())));
+        assert!(parse_rename("Severity=Level").unwrap_err().contains("colonne inconnue 'Severity'"));
+        assert!(parse_rename("Resource").is_err());
+
+        let cli = CliProblemApp::try_parse_from(["app", "-f", "x.json", "-i", "a", "--rename", "Resource=File,Line=Row"]).unwrap();
+        assert_eq!(cli.renames, [(Column::Resource, "File".to_string()), (Column::Line, "Row".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_severity_mapping() {
+        assert_eq!(parse_severity_mapping("3=error"), Ok(("3".to_string(), Severity::Error)));
+        assert_eq!(parse_severity_mapping("fatal = Error"), Ok(("fatal".to_string(), Severity::Error)));
+        assert!(parse_severity_mapping("3").is_err());
+        assert!(parse_severity_mapping("3=critical").is_err());
+    }
+
+    #[test]
+    fn test_run_app_thresholds_before_selections() {
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "a is deprecated", "severity": 8 },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "a is deprecated", "severity": 8 },
+            { "resource": "src/c.java", "startLineNumber": 3, "message": "a is deprecated", "severity": 8 },
+            { "resource": "src/d.java", "startLineNumber": 4, "message": "b is deprecated", "severity": 4 }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                max_errors: Some(2),
+                count_only: true,
+                ..cli
+            };
+            let mut err = Vec::new();
+            let code = run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut std::io::sink(), &mut err).unwrap();
+            (code, String::from_utf8(err).unwrap())
+        };
+
+        // L'échantillon ou la limite par message ne masquent pas les erreurs au seuil
+        for cli in [
+            CliProblemApp { sample: Some(1), ..Default::default() },
+            CliProblemApp { per_message_limit: Some(1), ..Default::default() },
+            CliProblemApp { dedup: true, ..Default::default() },
+        ] {
+            let (code, err) = run(cli);
+            assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+            assert!(err.contains("Seuil dépassé: 3 erreur(s)"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_run_app_resource_thresholds_before_selections() {
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "a is deprecated" },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "a is deprecated" },
+            { "resource": "src/c.java", "startLineNumber": 3, "message": "a is deprecated" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                fail_if_resources_above: Some(2),
+                count_only: true,
+                ..cli
+            };
+            let mut err = Vec::new();
+            let code = run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut std::io::sink(), &mut err).unwrap();
+            (code, String::from_utf8(err).unwrap())
+        };
+
+        for cli in [
+            CliProblemApp { sample: Some(1), ..Default::default() },
+            CliProblemApp { per_message_limit: Some(1), ..Default::default() },
+        ] {
+            let (code, err) = run(cli);
+            assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+            assert!(err.contains("Seuil dépassé: 3 fichier(s) concerné(s)"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_run_app_severity_field_thresholds() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            severity_field: Some("level".to_string()),
+            severity_map: vec![("3".to_string(), Severity::Error)],
+            max_errors: Some(1),
+            count_only: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "a is deprecated", "level": 3 },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "b is deprecated", "level": "error" }
+        ]"#;
+
+        let mut err = Vec::new();
+        let code = run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut std::io::sink(), &mut err).unwrap();
+        assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+        assert!(String::from_utf8(err).unwrap().contains("2 erreur(s)"));
+    }
+
+    #[test]
+    fn test_run_app_append_rejects_json() {
+        let err = run_format(CliProblemApp {
+            output: Some(PathBuf::from("out.json")),
+            append: true,
+            json: true,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(format!("{err}").contains("--append n'est pas compatible avec le format json"));
+
+        let s = run_format(CliProblemApp {
+            output: Some(PathBuf::from("out.ndjson")),
+            append: true,
+            format: Some(OutputFormat::Ndjson),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(s.lines().count(), 2);
+    }
+
+    fn run_affected_files(json_output: bool) -> String {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            show_affected_files: true,
+            json: json_output,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/a.java", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "src/b.java", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/c.java", "startLineNumber": 1, "message": "other" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        String::from_utf8(out).expect("invalid utf8")
+    }
+
+    #[test]
+    fn test_run_app_show_affected_files() {
+        assert!(run_affected_files(false).contains("Fichiers concernés: 2"));
+
+        let value: serde_json::Value = serde_json::from_str(&run_affected_files(true)).expect("invalid json");
+        assert_eq!(value["affected_files"], 2);
+        assert_eq!(value["problems"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_run_app_sort_by_severity() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            sort_by: Some(SortBy::Severity),
+            format: Some(OutputFormat::Ndjson),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a", "startLineNumber": 1, "message": "deprecated info", "severity": 2 },
+            { "resource": "b", "startLineNumber": 2, "message": "deprecated none" },
+            { "resource": "c", "startLineNumber": 3, "message": "deprecated error", "severity": 8 },
+            { "resource": "d", "startLineNumber": 4, "message": "deprecated warning", "severity": 4 }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let resources: Vec<String> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["resource"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(resources, ["c", "d", "a", "b"]);
+    }
+
+    #[test]
+    fn test_run_app_flatten_related() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "Duplicate identifier 'x'",
+              "relatedInformation": [{ "resource": "src/b.ts", "startLineNumber": 9, "message": "'x' was also declared here" }] },
+            { "resource": "src/c.ts", "startLineNumber": 2, "message": "Duplicate identifier 'y'" },
+            { "resource": "src/d.ts", "startLineNumber": 3, "message": "Unused import",
+              "relatedInformation": [{ "resource": "src/e.ts", "startLineNumber": 4, "message": "Duplicate" }] }
+        ]"#;
+        let run = |flatten_related| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["Duplicate".to_string()],
+                flatten_related,
+                format: Some(OutputFormat::Ndjson),
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(run(false).lines().count(), 2);
+
+        let lines: Vec<String> = run(true).lines().map(str::to_string).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("'x' was also declared here"), "unexpected output: {lines:?}");
+        assert!(lines[1].contains(r#""line":9"#), "unexpected output: {lines:?}");
+    }
+
+    fn run_messages_only(dedup: bool, normalize: bool) -> String {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            messages_only: true,
+            dedup,
+            normalize,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "'foo' is deprecated" },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "'bar' is deprecated" },
+            { "resource": "src/c.java", "startLineNumber": 3, "message": "'foo' is deprecated" },
+            { "resource": "src/d.java", "startLineNumber": 4, "message": "other" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        String::from_utf8(out).expect("invalid utf8")
+    }
+
+    #[test]
+    fn test_run_app_messages_only() {
+        assert_eq!(
+            run_messages_only(false, false),
+            "'foo' is deprecated\n'bar' is deprecated\n'foo' is deprecated\n"
+        );
+        assert_eq!(run_messages_only(true, false), "'foo' is deprecated\n'bar' is deprecated\n");
+        assert_eq!(run_messages_only(true, true), "'*' is deprecated\n");
+    }
+
+    #[test]
+    fn test_with_criteria_explicit_flags_win() {
+        let cli = CliProblemApp {
+            include_terms: vec!["explicit".to_string()],
+            max_msg_len: Some(10),
+            ..Default::default()
+        };
+        let criteria = FilterCriteria::parse(
+            r#"{ "include": ["from-json"], "exclude": ["test"], "ignore_case": true, "max_msg_len": 80, "min_msg_len": 2 }"#,
+        )
+        .unwrap();
+
+        let merged = cli.with_criteria(criteria).unwrap();
+        assert_eq!(merged.include_terms, ["explicit"]);
+        assert_eq!(merged.exclude_terms, ["test"]);
+        assert!(merged.ignore_case);
+        assert_eq!(merged.max_msg_len, Some(10));
+        assert_eq!(merged.min_msg_len, Some(2));
+    }
+
+    #[test]
+    fn test_with_criteria_all_fields() {
+        let cli = CliProblemApp { severities: vec![Severity::Warning], ..Default::default() };
+        let criteria = FilterCriteria::parse(
+            r#"{ "severity": ["error"], "kind": ["type"], "resource_regex": "^src/", "only_empty": true,
+                 "ignore_generated": true, "generated_pattern": ["gen/"], "message_prefix_len": 40 }"#,
+        )
+        .unwrap();
+
+        let merged = cli.with_criteria(criteria).unwrap();
+        assert_eq!(merged.severities, [Severity::Warning]);
+        assert_eq!(merged.kinds, ["type"]);
+        assert_eq!(merged.resource_regex.as_deref(), Some("^src/"));
+        assert!(merged.only_empty && merged.ignore_generated);
+        assert_eq!(merged.generated_pattern, ["gen/"]);
+        assert_eq!(merged.message_prefix_len, Some(40));
+
+        let invalid = FilterCriteria::parse(r#"{ "severity": ["fatal"], "resource_regex": "(" }"#).unwrap();
+        let error = CliProblemApp::default().with_criteria(invalid).err().expect("critères invalides acceptés");
+        assert!(error.to_string().contains("sévérité inconnue 'fatal'"), "{error}");
+    }
+
+    #[test]
+    fn test_run_app_filter_json_conflicts() {
+        let run = |cli: CliProblemApp, filter_json: &str| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                filter_json: Some(filter_json.to_string()),
+                count_only: true,
+                ..cli
+            };
+            let json = r#"[{ "resource": "a", "startLineNumber": 1, "message": "deprecated" }]"#;
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut std::io::sink(), &mut std::io::sink())
+        };
+
+        let include = CliProblemApp { include_terms: vec!["deprecated".to_string()], ..Default::default() };
+        let error = run(include, r#"{ "query": "message~\"x\"" }"#).unwrap_err();
+        assert!(error.to_string().contains("--query n'est pas compatible"), "{error}");
+
+        let query = CliProblemApp { query: Some(r#"message~"deprecated""#.to_string()), ..Default::default() };
+        assert!(run(query, r#"{ "exclude": ["test"] }"#).is_err());
+
+        let drop_empty = CliProblemApp { drop_empty: true, ..Default::default() };
+        assert!(run(drop_empty, r#"{ "only_empty": true }"#).unwrap_err().to_string().contains("--only-empty"));
+        assert_eq!(run(CliProblemApp::default(), r#"{ "query": "message~\"deprecated\"" }"#).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_run_app_filter_json_file() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            filter_json: Some("@filter.json".to_string()),
+            count_only: true,
+            ..Default::default()
+        };
+
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("filter.json") => Ok(r#"{ "include": ["DEPRECATED"], "ignore_case": true }"#.to_string()),
+            Some("x.json") => Ok(r#"[
+                { "resource": "a", "startLineNumber": 1, "message": "deprecated" },
+                { "resource": "b", "startLineNumber": 2, "message": "other" }
+            ]"#
+            .to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.contains("Termes à inclure: DEPRECATED"));
+        assert!(s.contains("Nombre de problèmes filtrés: 1"));
+    }
+
+    #[test]
+    fn test_run_app_count_distinct() {
+        let run = |by, json_output: bool| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                count_distinct: Some(by),
+                count_only: !json_output,
+                json: json_output,
+                ..Default::default()
+            };
+
+            let json = r#"[
+                { "resource": "a", "startLineNumber": 1, "message": "deprecated" },
+                { "resource": "a", "startLineNumber": 1, "message": "deprecated" },
+                { "resource": "a", "startLineNumber": 5, "message": "deprecated" },
+                { "resource": "b", "startLineNumber": 1, "message": "deprecated" }
+            ]"#;
+
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let s = run(dedup::DistinctBy::Problem, false);
+        assert!(s.contains("Nombre de problèmes filtrés: 4"));
+        assert!(s.contains("Nombre de problèmes distincts: 3"));
+        assert!(run(dedup::DistinctBy::ResourceMessage, false).contains("Nombre de problèmes distincts: 2"));
+        assert!(run(dedup::DistinctBy::Message, false).contains("Nombre de problèmes distincts: 1"));
+
+        let value: serde_json::Value = serde_json::from_str(&run(dedup::DistinctBy::Message, true)).unwrap();
+        assert_eq!(value["distinct_count"], 1);
+    }
+
+    #[test]
+    fn test_run_app_json_stream() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'a' is \"deprecated\"" },
+            { "resource": "src/b.ts", "startLineNumber": 2, "message": "'b' is deprecated", "code": "TS6385" },
+            { "resource": "src/c.ts", "startLineNumber": 3, "message": "Missing semicolon" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                json: true,
+                show_code: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let buffered: serde_json::Value = serde_json::from_str(&run(CliProblemApp::default())).unwrap();
+        let s = run(CliProblemApp { stream: true, ..Default::default() });
+        let streamed: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(streamed, buffered);
+        assert_eq!(streamed.as_array().map(Vec::len), Some(2));
+        assert_eq!(s.lines().count(), 4, "one problem per line: {s}");
+
+        let s = run(CliProblemApp { stream: true, exclude_terms: vec!["is".to_string()], ..Default::default() });
+        assert_eq!(s, "[]\n");
+
+        let err = run_format(CliProblemApp { stream: true, csv: true, ..Default::default() }).unwrap_err();
+        assert_eq!(format!("{err}"), "--stream nécessite le format json");
+
+        // Les informations complémentaires ne peuvent pas être écrites au fil de l'eau
+        for cli in [
+            CliProblemApp { stream: true, json: true, show_affected_files: true, ..Default::default() },
+            CliProblemApp { stream: true, json: true, count_only: true, ..Default::default() },
+            CliProblemApp { stream: true, json: true, group_by: Some(GroupBy::Source), ..Default::default() },
+        ] {
+            let err = run_format(cli).unwrap_err();
+            assert!(err.to_string().starts_with("--stream n'est pas compatible avec --"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_run_app_sarif() {
+        let s = run_format(CliProblemApp { sarif: true, ..Default::default() }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value["version"], "2.1.0");
+        assert!(value["$schema"].as_str().unwrap().contains("sarif-2.1.0"));
+
+        let results = value["runs"][0]["results"].as_array().unwrap();
+        assert!(!results.is_empty());
+        for result in results {
+            assert!(result["message"]["text"].as_str().unwrap().contains("deprecated"));
+            let location = &result["locations"][0]["physicalLocation"];
+            assert!(location["artifactLocation"]["uri"].is_string(), "unexpected result: {result}");
+            assert!(location["region"]["startLine"].as_u64().unwrap() >= 1, "unexpected result: {result}");
+        }
+
+        let err = run_format(CliProblemApp { sarif: true, count_only: true, ..Default::default() }).unwrap_err();
+        assert_eq!(format!("{err}"), "Le format sarif n'est pas compatible avec --count-only");
+    }
+
+    #[test]
+    fn test_run_app_junit() {
+        let s = run_format(CliProblemApp { junit: true, ..Default::default() }).unwrap();
+        assert!(s.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#), "unexpected output: {s}");
+        assert!(s.trim_end().ends_with("</testsuites>"), "unexpected output: {s}");
+        let cases = s.matches("<testcase ").count();
+        assert!(cases > 0);
+        assert_eq!(s.matches("</testcase>").count(), cases);
+        assert_eq!(s.matches("<failure ").count(), cases);
+        assert_eq!(s.matches("<testsuite ").count(), s.matches("</testsuite>").count());
+        assert!(s.contains(&format!(r#"tests="{cases}" failures="{cases}">"#)), "unexpected output: {s}");
+
+        let err = run_format(CliProblemApp { junit: true, count_only: true, ..Default::default() }).unwrap_err();
+        assert_eq!(format!("{err}"), "Le format junit n'est pas compatible avec --count-only");
+    }
+
+    #[test]
+    fn test_run_app_vscode_text() {
+        let json = r#"[
+            { "resource": "/home/dev/project/src/a.ts", "startLineNumber": 3, "message": "'a' is deprecated" },
+            { "resource": "/home/dev/project/src/b.ts", "startLineNumber": 7, "message": "'b' is deprecated" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                vscode_text: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(run(CliProblemApp::default()), "src/a.ts(3): 'a' is deprecated\nsrc/b.ts(7): 'b' is deprecated\n");
+        assert_eq!(
+            run(CliProblemApp { path_depth: Some(usize::MAX), ..Default::default() }),
+            "/home/dev/project/src/a.ts(3): 'a' is deprecated\n/home/dev/project/src/b.ts(7): 'b' is deprecated\n"
+        );
+    }
+
+    #[test]
+    fn test_run_app_html() {
+        let s = run_format(CliProblemApp { html: true, ..Default::default() }).unwrap();
+        assert!(s.starts_with("<table>"), "unexpected output: {s}");
+        assert_eq!(s.matches("<tr class=\"severity-none\">").count(), 2);
+
+        let s = run_format(CliProblemApp { format: Some(OutputFormat::Html), html_full: true, ..Default::default() }).unwrap();
+        assert!(s.starts_with("<!DOCTYPE html>"), "unexpected output: {s}");
+
+        let err = run_format(CliProblemApp { html_full: true, ..Default::default() }).unwrap_err();
+        assert_eq!(format!("{err}"), "--html-full nécessite le format html");
+
+        let err = run_format(CliProblemApp { html: true, group_by: Some(GroupBy::Source), ..Default::default() }).unwrap_err();
+        assert_eq!(format!("{err}"), "Le format html n'est pas compatible avec --group-by");
+    }
+
+    #[test]
+    fn test_run_app_format_conflicts() {
+        let err = run_format(CliProblemApp {
+            format: Some(OutputFormat::Csv),
+            unique_messages: true,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(format!("{err}"), "Le format csv n'est pas compatible avec --unique-messages");
+
+        let err = run_format(CliProblemApp { format: Some(OutputFormat::Json), tree: true, ..Default::default() })
+            .unwrap_err();
+        assert_eq!(format!("{err}"), "Le format json n'est pas compatible avec --tree");
+    }
+
+    #[test]
+    fn test_run_app_count_only() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["foo".to_string()],
+            exclude_terms: vec![],
+            ignore_case: false,
+            count_only: true,
+            json: false,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "foo bar" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "no match" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre total de problèmes: 2"));
+        assert!(s.contains("Nombre de problèmes filtrés: 1"));
+    }
+
+    #[test]
+    fn test_run_app_validation_error() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec![],
+            exclude_terms: vec![],
+            ignore_case: false,
+            count_only: false,
+            json: false,
+            ..Default::default()
+        };
+
+        let read_fn = |_p: &PathBuf| Ok("[]".to_string());
+
+        let mut out = Vec::new();
+        let res = run_app(&cli, read_fn, &mut out, &mut std::io::sink());
+        assert!(res.is_err());
+        // Vérifier que le message d'erreur correspond à la validation des arguments
+        if let Err(e) = res {
+            let msg = format!("{e}");
+            assert!(msg.contains("Au moins un critère de filtrage doit être spécifié"), "unexpected error message: {msg}");
+        }
+    }
+
+    #[test]
+    fn test_run_app_table_output() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["matchme".to_string()],
+            exclude_terms: vec![],
+            ignore_case: false,
+            count_only: false,
+            json: false,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 3, "message": "matchme here" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        // Table should contain header Resource and Message
+        assert!(s.contains("Resource"));
+        assert!(s.contains("Message"));
+        assert!(s.contains("matchme here"));
+    }
+
+    #[test]
+    fn test_run_app_ignore_case_resource() {
+        let json = r#"[
+            { "resource": "Src/Main.ts", "startLineNumber": 1, "message": "'a' is deprecated" },
+            { "resource": "src/main.ts", "startLineNumber": 2, "message": "'b' is DEPRECATED" },
+            { "resource": "SRC/Tests/a.ts", "startLineNumber": 3, "message": "'c' is deprecated" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                resource_regex: Some("^src/".to_string()),
+                exclude_resource_regex: Some("tests/".to_string()),
+                csv: true,
+                no_header: true,
+                path_depth: Some(usize::MAX),
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        // Sans option, la casse compte pour les messages comme pour les fichiers
+        assert_eq!(run(CliProblemApp::default()), "");
+        // La casse des fichiers est ignorée, pas celle des messages
+        assert_eq!(run(CliProblemApp { ignore_case_resource: true, ..Default::default() }), "Src/Main.ts,'a' is deprecated,1\n");
+        // La casse des messages est ignorée, pas celle des fichiers
+        assert_eq!(run(CliProblemApp { ignore_case: true, ..Default::default() }), "src/main.ts,'b' is DEPRECATED,2\n");
+        assert_eq!(
+            run(CliProblemApp { ignore_case: true, ignore_case_resource: true, ..Default::default() }),
+            "Src/Main.ts,'a' is deprecated,1\nsrc/main.ts,'b' is DEPRECATED,2\n"
+        );
+    }
+
+    #[test]
+    fn test_run_app_ignore_case_include() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["DEPRECATED".to_string()],
+            exclude_terms: vec![],
+            ignore_case: true,
+            count_only: false,
+            json: false,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 4, "message": "this is deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de problèmes filtrés: 1"));
+    }
+
+    #[test]
+    fn test_run_app_ignore_case_exclude() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec![],
+            exclude_terms: vec!["WARNING".to_string()],
+            ignore_case: true,
+            count_only: false,
+            json: false,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 4, "message": "this is a warning" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        // Since the only problem matches the exclude term, filtered count should be 0
+        assert!(s.contains("Nombre de problèmes filtrés: 0"));
+    }
+
+    #[test]
+    fn test_run_app_query() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            query: Some(r#"message~"deprecated" AND severity=error"#.to_string()),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "foo is deprecated", "severity": 8 },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "bar is deprecated", "severity": 4 },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "unused import", "severity": 8 }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de problèmes filtrés: 1"));
+        assert!(s.contains("foo is deprecated"));
+    }
+
+    #[test]
+    fn test_run_app_invalid_query() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            query: Some("message deprecated".to_string()),
+            ..Default::default()
+        };
+
+        let read_fn = |_p: &PathBuf| Ok("[]".to_string());
+
+        let mut out = Vec::new();
+        let err = run_app(&cli, read_fn, &mut out, &mut std::io::sink()).unwrap_err();
+        assert!(format!("{err}").contains("position 9"));
+    }
+
+    #[test]
+    fn test_run_app_merges_inputs() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("a.json"), PathBuf::from("b.json")],
+            include_terms: vec!["deprecated".to_string()],
+            count_only: true,
+            ..Default::default()
+        };
+
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("a.json") => Ok(r#"[{ "resource": "a", "startLineNumber": 1, "message": "deprecated" }]"#.to_string()),
+            Some("b.json") => Ok(r#"[{ "resource": "b", "startLineNumber": 1, "message": "deprecated" },
+                                     { "resource": "b", "startLineNumber": 2, "message": "other" }]"#.to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre total de problèmes: 3"));
+        assert!(s.contains("Nombre de problèmes filtrés: 2"));
+    }
+
+    #[test]
+    fn test_run_app_terms_from_stdin() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'x' is deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 2, "message": "'y' is never used" },
+            { "resource": "src/a.ts", "startLineNumber": 3, "message": "'z' is deprecated in tests" },
+            { "resource": "src/b.ts", "startLineNumber": 4, "message": "other" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_min: Some(1),
+                format: Some(OutputFormat::Csv),
+                ..cli
+            };
+            let read_fn = |p: &PathBuf| match p.to_str() {
+                Some("x.json") => Ok(json.to_string()),
+                Some("-") => Ok("deprecated\n\nnever used\n".to_string()),
+                Some("exclude.txt") => Ok("tests\n".to_string()),
+                _ => anyhow::bail!("unexpected path {p:?}"),
+            };
+            let mut out = Vec::new();
+            run_app(&cli, read_fn, &mut out, &mut std::io::sink()).map(|_| String::from_utf8(out).unwrap())
+        };
+
+        let s = run(CliProblemApp { include_from: Some(PathBuf::from("-")), ..Default::default() }).unwrap();
+        assert_eq!(s.lines().count(), 4, "unexpected output: {s}");
+
+        // Les listes se combinent avec les termes de la ligne de commande
+        let s = run(CliProblemApp {
+            include_terms: vec!["other".to_string()],
+            include_from: Some(PathBuf::from("-")),
+            exclude_from: Some(PathBuf::from("exclude.txt")),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            s.lines().skip(1).collect::<Vec<_>>(),
+            ["src/a.ts,'x' is deprecated,1", "src/a.ts,'y' is never used,2", "src/b.ts,other,4"]
+        );
+
+        let err = run(CliProblemApp {
+            files_from: Some(PathBuf::from("-")),
+            include_from: Some(PathBuf::from("-")),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(
+            format!("{err}"),
+            "L'entrée standard (-) ne peut être lue que par une seule option: --files-from, --include-from"
+        );
+    }
+
+    #[test]
+    fn test_run_app_input_dir() {
+        let dir = std::env::temp_dir().join(format!("vpf-input-dir-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        let problem = |message: &str| format!(r#"[{{ "resource": "src/a.ts", "startLineNumber": 1, "message": "{message}" }}]"#);
+        std::fs::write(dir.join("front.json"), problem("front is deprecated")).unwrap();
+        std::fs::write(dir.join("back.json"), problem("back is deprecated")).unwrap();
+        std::fs::write(dir.join("nested/deep.json"), problem("deep is deprecated")).unwrap();
+
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                input_dir: Some(dir.clone()),
+                include_terms: vec!["deprecated".to_string()],
+                format: Some(OutputFormat::Csv),
+                show_file: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            let mut err = Vec::new();
+            run_app(&cli, read_input, &mut out, &mut err)
+                .map(|_| (String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap()))
+        };
+
+        let (s, _) = run(CliProblemApp::default()).unwrap();
+        assert_eq!(s.lines().count(), 3, "unexpected output: {s}");
+        assert!(s.lines().nth(1).unwrap().starts_with("src/a.ts,back is deprecated,1,"), "unexpected output: {s}");
+        assert!(s.contains("front.json"), "unexpected output: {s}");
+
+        let (s, _) = run(CliProblemApp { recursive: true, ..Default::default() }).unwrap();
+        assert!(s.contains("deep is deprecated"), "unexpected output: {s}");
+
+        // Un fichier invalide arrête la lecture, sauf avec --skip-invalid
+        std::fs::write(dir.join("broken.json"), "[{").unwrap();
+        assert!(run(CliProblemApp::default()).is_err());
+        let (s, err) = run(CliProblemApp { skip_invalid: true, ..Default::default() }).unwrap();
+        assert_eq!(s.lines().count(), 3, "unexpected output: {s}");
+        assert!(err.contains("Fichier ignoré: Erreur lors du parsing du JSON"), "unexpected stderr: {err}");
+        assert!(err.contains("broken.json"), "unexpected stderr: {err}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_app_files_from_null_data() {
+        let cli = CliProblemApp {
+            files_from: Some(PathBuf::from("list.txt")),
+            null_data: true,
+            include_terms: vec!["deprecated".to_string()],
+            count_only: true,
+            ..Default::default()
+        };
+
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("list.txt") => Ok("a.json\0b.json\0".to_string()),
+            Some("a.json") | Some("b.json") => {
+                Ok(r#"[{ "resource": "x", "startLineNumber": 1, "message": "deprecated" }]"#.to_string())
+            }
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre total de problèmes: 2"));
+    }
+
+    #[test]
+    fn test_run_app_per_message_limit() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            per_message_limit: Some(1),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a.java", "startLineNumber": 1, "message": "foo is deprecated" },
+            { "resource": "src/b.java", "startLineNumber": 2, "message": "foo is deprecated" },
+            { "resource": "src/c.java", "startLineNumber": 3, "message": "foo is deprecated" },
+            { "resource": "src/d.java", "startLineNumber": 4, "message": "bar is deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de problèmes filtrés: 2"));
+        assert!(s.contains("Problèmes masqués par la limite par message: 2"));
+        assert!(s.contains("a.java"));
+        assert!(!s.contains("b.java"));
+    }
+
+    #[test]
+    fn test_run_app_per_message_limit_normalized() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            per_message_limit: Some(1),
+            normalize: true,
+            count_only: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "'Foo' is deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "'Bar' is deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de problèmes filtrés: 1"));
+    }
+
+    #[test]
+    fn test_run_app_unique_messages_min_occurrences() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            unique_messages: true,
+            min_occurrences: Some(2),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "foo is deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "foo is deprecated" },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "bar is deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de messages distincts: 1"));
+        assert!(s.contains("foo is deprecated"));
+        assert!(!s.contains("bar is deprecated"));
+    }
+
+    #[test]
+    fn test_run_app_unique_messages_json_min_occurrences() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            unique_messages: true,
+            min_occurrences: Some(2),
+            json: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "foo is deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "foo is deprecated" },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "bar is deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+        assert_eq!(value, serde_json::json!([{ "message": "foo is deprecated", "count": 2 }]));
+    }
+
+    #[test]
+    fn test_run_app_list_resources() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            list_resources: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/b/test.java", "startLineNumber": 1, "message": "foo is deprecated" },
+            { "resource": "src/a/test.java", "startLineNumber": 2, "message": "foo is deprecated" },
+            { "resource": "src/b/test.java", "startLineNumber": 3, "message": "bar is deprecated" },
+            { "resource": "src/c/test.java", "startLineNumber": 4, "message": "unused import" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert_eq!(s, "src/a/test.java\nsrc/b/test.java\n");
+    }
+
+    #[test]
+    fn test_run_app_list_resources_path_transforms() {
+        let json = r#"[
+            { "resource": "src\\b\\test.java", "startLineNumber": 1, "message": "foo is deprecated" },
+            { "resource": "lib/b/test.java", "startLineNumber": 2, "message": "foo is deprecated" },
+            { "resource": "src/a/test.java", "startLineNumber": 3, "message": "bar is deprecated" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                list_resources: true,
+                ..cli
+            };
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            String::from_utf8(out).unwrap()
+        };
+
+        let normalized = run(CliProblemApp { normalize_paths: true, ..Default::default() });
+        assert_eq!(normalized, "lib/b/test.java\nsrc/a/test.java\nsrc/b/test.java\n");
+        // Les fichiers dont le chemin raccourci est identique ne sont listés qu'une fois
+        let shortened = run(CliProblemApp { normalize_paths: true, path_depth: Some(2), ..Default::default() });
+        assert_eq!(shortened, "a/test.java\nb/test.java\n");
+    }
+
+    #[test]
+    fn test_run_app_group_by_source() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            group_by: Some(GroupBy::Source),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "deprecated", "source": "sonarlint" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "deprecated", "source": "sonarlint" },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de groupes: 2"));
+        assert!(s.contains("Source"));
+        let sonarlint = s.find("sonarlint").expect("missing sonarlint group");
+        let none = s.find("(none)").expect("missing (none) group");
+        assert!(sonarlint < none, "groups should be sorted by count: {s}");
+    }
+
+    #[test]
+    fn test_run_app_group_by_source_json() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            group_by: Some(GroupBy::Source),
+            json: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "deprecated", "source": "sonarlint" },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+        assert_eq!(value, serde_json::json!({ "sonarlint": 1, "(none)": 1 }));
+    }
+
+    #[test]
+    fn test_run_app_group_by_basename_json() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            group_by: Some(GroupBy::Basename),
+            json: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/front/index.ts", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/back/index.ts", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "src/back/main.ts", "startLineNumber": 3, "message": "deprecated" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+        assert_eq!(value, serde_json::json!({ "index.ts": 2, "main.ts": 1 }));
+    }
+
+    #[test]
+    fn test_run_app_group_by_kind() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'x' is never used" },
+            { "resource": "src/b.ts", "startLineNumber": 2, "message": "'y' is deprecated" },
+            { "resource": "src/c.ts", "startLineNumber": 3, "message": "'z' is declared but never read" },
+            { "resource": "src/d.ts", "startLineNumber": 4, "message": "Missing semicolon" }
+        ]"#;
+        let run = |cli: CliProblemApp| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                exclude_terms: vec!["generated".to_string()],
+                group_by: Some(GroupBy::Kind),
+                json: true,
+                ..cli
+            };
+            let read_fn = |p: &PathBuf| match p.to_str() {
+                Some("rules.toml") => Ok("[[rule]]\nkind = \"style\"\nkeywords = [\"semicolon\"]\n".to_string()),
+                _ => Ok(json.to_string()),
+            };
+            let mut out = Vec::new();
+            run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+            serde_json::from_slice::<serde_json::Value>(&out).expect("invalid json")
+        };
+
+        // Sans --classify, les règles par défaut donnent tout de même la catégorie
+        assert_eq!(run(CliProblemApp::default()), serde_json::json!({ "unused": 2, "deprecated": 1, "other": 1 }));
+        let with_rules = run(CliProblemApp { kind_rules: Some(PathBuf::from("rules.toml")), ..Default::default() });
+        assert_eq!(with_rules["style"], 1);
+    }
+
+    #[test]
+    fn test_run_app_group_by_line_bucket() {
+        let run = |bucket_size| {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["deprecated".to_string()],
+                group_by: Some(GroupBy::LineBucket),
+                bucket_size,
+                json: true,
+                ..Default::default()
+            };
+            let problems: Vec<serde_json::Value> = [1, 2, 5, 12, 40, 60, 130]
+                .iter()
+                .map(|line| serde_json::json!({ "resource": "a.ts", "startLineNumber": line, "message": "deprecated" }))
+                .collect();
+            let json = serde_json::to_string(&problems).unwrap();
+            let mut out = Vec::new();
+            run_app(&cli, |_p: &PathBuf| Ok(json.clone()), &mut out, &mut std::io::sink()).expect("run_app failed");
+            serde_json::from_slice::<serde_json::Value>(&out).expect("invalid json")
+        };
+
+        assert_eq!(run(None), serde_json::json!({ "1-50": 5, "51-100": 1, "101-150": 1 }));
+        assert_eq!(run(Some(10)), serde_json::json!({ "1-10": 3, "11-20": 1, "31-40": 1, "51-60": 1, "121-130": 1 }));
+    }
+
+    #[test]
+    fn test_run_app_ignore_file() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            ignore_file: Some(PathBuf::from(".vpfignore")),
+            format: Some(OutputFormat::Csv),
+            path_depth: Some(usize::MAX),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/gen/b.ts", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "test/smoke.spec.ts", "startLineNumber": 3, "message": "deprecated" },
+            { "resource": "test/c.spec.ts", "startLineNumber": 4, "message": "deprecated" }
+        ]"#;
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("x.json") => Ok(json.to_string()),
+            Some(".vpfignore") => Ok("gen/\n*.spec.ts\n!smoke.spec.ts\n".to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).unwrap();
+        let resources: Vec<&str> = s.lines().skip(1).filter_map(|line| line.split(',').next()).collect();
+        assert_eq!(resources, ["src/a.ts", "test/smoke.spec.ts"]);
+    }
+
+    #[test]
+    fn test_run_app_tree_collapse_paths() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            tree: true,
+            collapse_paths: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/main/java/A.java", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/main/java/B.java", "startLineNumber": 2, "message": "deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("src/main/java/ (2)\n  A.java (1)\n  B.java (1)\n"), "unexpected tree: {s}");
+    }
+
+    #[test]
+    fn test_run_app_tree_min() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            tree: true,
+            tree_min: Some(2),
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/A.java", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "src/A.java", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "src/B.java", "startLineNumber": 3, "message": "deprecated" },
+            { "resource": "test/C.java", "startLineNumber": 4, "message": "deprecated" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(
+            s.contains("src/ (3)\n  A.java (2)\n  (… 1 autre fichier avec moins de 2 problèmes)\n(… 1 autre fichier avec moins de 2 problèmes)\n"),
+            "unexpected tree: {s}"
+        );
+    }
+
+    fn run_sample(sample: usize, seed: u64) -> String {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            sample: Some(sample),
+            seed: Some(seed),
+            json: true,
+            ..Default::default()
+        };
+
+        let problems: Vec<serde_json::Value> = (1..=20)
+            .map(|line| serde_json::json!({ "resource": "a.java", "startLineNumber": line, "message": "deprecated" }))
+            .collect();
+        let json = serde_json::to_string(&problems).unwrap();
+
+        let read_fn = |_p: &PathBuf| Ok(json.clone());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        String::from_utf8(out).expect("invalid utf8")
+    }
+
+    #[test]
+    fn test_run_app_sample_is_deterministic_with_seed() {
+        let first = run_sample(5, 42);
+        assert_eq!(first, run_sample(5, 42));
+
+        let lines: Vec<u64> = serde_json::from_str::<Vec<serde_json::Value>>(&first)
+            .unwrap()
+            .iter()
+            .map(|p| p["line"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines.windows(2).all(|w| w[0] < w[1]), "input order should be kept: {lines:?}");
+    }
+
+    #[test]
+    fn test_run_app_sample_larger_than_results() {
+        let all: Vec<serde_json::Value> = serde_json::from_str(&run_sample(50, 1)).unwrap();
+        assert_eq!(all.len(), 20);
+    }
+
+    #[test]
+    fn test_run_app_sample_header() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            sample: Some(1),
+            seed: Some(7),
+            count_only: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Nombre de problèmes filtrés: 2 (échantillon aléatoire de 1)"));
+    }
+
+    #[test]
+    fn test_run_app_term_stats() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string(), "type".to_string()],
+            ignore_case: true,
+            term_stats: true,
+            json: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "The type A is deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "TYPE B is DEPRECATED" },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "deprecated method" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+        assert_eq!(value["problems"].as_array().unwrap().len(), 2);
+        assert_eq!(value["term_stats"], serde_json::json!({ "deprecated": 2, "type": 2 }));
+    }
+
+    fn run_prevalence(json_output: bool) -> String {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string(), "type".to_string()],
+            prevalence: true,
+            json: json_output,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "type A is deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "type B" },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "type C" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        String::from_utf8(out).expect("invalid utf8")
+    }
+
+    #[test]
+    fn test_run_app_prevalence_counts_all_problems() {
+        let value: serde_json::Value = serde_json::from_str(&run_prevalence(true)).expect("invalid json");
+        assert_eq!(value["problems"].as_array().unwrap().len(), 1);
+        assert_eq!(value["prevalence"], serde_json::json!({ "deprecated": 1, "type": 3 }));
+    }
+
+    #[test]
+    fn test_run_app_prevalence_table_sorted() {
+        let s = run_prevalence(false);
+        assert!(s.contains("Prévalence des termes (sur les 3 problèmes lus):"));
+        let type_pos = s.find("| type ").expect("missing type row");
+        let deprecated_pos = s.find("| deprecated ").expect("missing deprecated row");
+        assert!(type_pos < deprecated_pos, "terms not sorted by count: {s}");
+    }
+
+    #[test]
+    fn test_run_app_term_stats_table() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            term_stats: true,
+            count_only: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert!(s.contains("Statistiques par terme:"));
+        assert!(s.contains("Terme"));
+    }
+
+    #[test]
+    fn test_run_app_csv_no_header() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            csv: true,
+            no_header: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "src/a/test.java", "startLineNumber": 1, "message": "deprecated" }
+        ]"#;
+
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut err).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert_eq!(s, "a/test.java,deprecated,1\n");
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    fn test_run_app_no_header_ignored_without_csv() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            no_header: true,
+            count_only: true,
+            ..Default::default()
+        };
+
+        let read_fn = |_p: &PathBuf| Ok("[]".to_string());
 
-    if !cli.include_terms.is_empty() {
-        writeln!(out, "Termes à inclure: {}", cli.include_terms.join(", "))?;
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut err).expect("run_app failed");
+        let warnings = String::from_utf8(err).expect("invalid utf8");
+        assert!(warnings.contains("--no-header est ignoré"), "unexpected warnings: {warnings}");
     }
 
-    if !cli.exclude_terms.is_empty() {
-        writeln!(out, "Termes à exclure: {}", cli.exclude_terms.join(", "))?;
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(";"), Ok(';'));
+        assert_eq!(parse_delimiter("\\t"), Ok('\t'));
+        assert_eq!(parse_delimiter("tab"), Ok('\t'));
+        assert!(parse_delimiter("").is_err());
+        assert!(parse_delimiter(";;").is_err());
+        assert!(parse_delimiter("\"").is_err());
     }
 
-    if cli.ignore_case {
-        writeln!(out, "Mode insensible à la casse activé")?;
-    }
+    #[test]
+    fn test_run_app_csv_semicolon_delimiter() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            csv: true,
+            delimiter: Some(';'),
+            ..Default::default()
+        };
 
-    writeln!(out)?;
+        let json = r#"[
+            { "resource": "src/a/test.java", "startLineNumber": 1, "message": "deprecated" }
+        ]"#;
 
-    writeln!(out, "Nombre de problèmes filtrés: {}", filtered_problems.len())?;
+        let read_fn = |_p: &PathBuf| Ok(json.to_string());
 
-    if cli.count_only {
-        return Ok(());
+        let mut out = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).expect("invalid utf8");
+        assert_eq!(s, "Resource;Message;Line\na/test.java;deprecated;1\n");
     }
 
-    writeln!(out)?;
+    #[test]
+    fn test_run_app_profile_filter() {
+        let json = r#"[
+            { "resource": "src/a.ts", "startLineNumber": 1, "message": "'a' is deprecated", "severity": 8 },
+            { "resource": "src/b.ts", "startLineNumber": 1, "message": "'b' is deprecated", "severity": 4 },
+            { "resource": "src/c.ts", "startLineNumber": 1, "message": "'c' is deprecated in tests", "severity": 8 },
+            { "resource": "src/d.ts", "startLineNumber": 1, "message": "Expected semicolon", "severity": 8 },
+            { "resource": "src/e.ts", "startLineNumber": 1, "message": "'e' is unused", "severity": 8 }
+        ]"#;
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["is".to_string()],
+            exclude_terms: vec!["tests".to_string()],
+            severities: vec![Severity::Error],
+            profile_filter: true,
+            count_only: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut err).expect("run_app failed");
+        let stderr = String::from_utf8(err).unwrap();
+        assert!(stderr.contains("  Problèmes évalués: 5\n"), "unexpected stderr: {stderr}");
+        assert!(stderr.contains("  - termes d'inclusion: 1 rejetés, 4 restants\n"), "unexpected stderr: {stderr}");
+        assert!(stderr.contains("  - termes d'exclusion: 1 rejetés, 3 restants\n"), "unexpected stderr: {stderr}");
+        assert!(stderr.contains("  - longueur du message: 0 rejetés, 3 restants\n"), "unexpected stderr: {stderr}");
+        assert!(stderr.contains("  - sévérité: 1 rejetés, 2 restants\n"), "unexpected stderr: {stderr}");
+        assert!(stderr.contains("  - catégorie: 0 rejetés, 2 restants\n"), "unexpected stderr: {stderr}");
+        assert!(String::from_utf8(out).unwrap().contains("2"));
 
-    // Affichage du tableau
-    if filtered_problems.is_empty() {
-        writeln!(out, "Aucun problème ne correspond aux critères de filtrage.")?;
-    } else {
-        let table = Table::new(&filtered_problems);
-        writeln!(out, "{table}")?;
+        // Sans --profile-filter, rien n'est affiché
+        let mut err = Vec::new();
+        let cli = CliProblemApp { profile_filter: false, ..cli };
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut std::io::sink(), &mut err).expect("run_app failed");
+        assert!(!String::from_utf8(err).unwrap().contains("Entonnoir"));
     }
 
-    Ok(())
-
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
     #[test]
-    fn test_run_app_json_output() {
+    fn test_run_app_timing_on_stderr() {
         let cli = CliProblemApp {
-            input: PathBuf::from("does_not_matter.json"),
+            inputs: vec![PathBuf::from("x.json")],
             include_terms: vec!["deprecated".to_string()],
-            exclude_terms: vec![],
-            ignore_case: false,
-            count_only: false,
             json: true,
+            timing: true,
+            ..Default::default()
         };
 
-        // JSON in-memory with one problem matching
+        let read_fn = |_p: &PathBuf| Ok("[]".to_string());
+
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run_app(&cli, read_fn, &mut out, &mut err).expect("run_app failed");
+        let stdout = String::from_utf8(out).expect("invalid utf8");
+        let stderr = String::from_utf8(err).expect("invalid utf8");
+        assert_eq!(stdout.trim(), "[]");
+        assert!(stderr.contains("Durée de lecture: "));
+        assert!(stderr.contains("Durée du parsing: "));
+        assert!(stderr.contains("Durée du filtrage: "));
+    }
+
+    #[test]
+    fn test_run_app_merge_same_line() {
         let json = r#"[
-            { "resource": "a/test.java", "startLineNumber": 1, "message": "This is deprecated" }
+            { "resource": "src/a.ts", "startLineNumber": 3, "message": "'x' is deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 3, "message": "'y' is deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 4, "message": "'z' is deprecated" },
+            { "resource": "src/b.ts", "startLineNumber": 3, "message": "'w' is deprecated" },
+            { "resource": "src/a.ts", "startLineNumber": 3, "message": "'v' is deprecated" }
         ]"#;
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            merge_same_line: true,
+            ..Default::default()
+        };
 
-        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.contains("Nombre de problèmes filtrés: 3"), "unexpected output: {s}");
+        assert!(s.contains("Problèmes fusionnés sur une même ligne: 2"), "unexpected output: {s}");
+        assert!(
+            s.contains("| src/a.ts | 'x' is deprecated; 'y' is deprecated; 'v' is deprecated | 3    |"),
+            "unexpected output: {s}"
+        );
+    }
+
+    fn run_dedup(keep_duplicates_across_files: bool) -> String {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("a.json"), PathBuf::from("b.json")],
+            include_terms: vec!["deprecated".to_string()],
+            dedup: true,
+            keep_duplicates_across_files,
+            count_only: true,
+            ..Default::default()
+        };
+
+        // Le même problème apparaît deux fois dans a.json et une fois dans b.json
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("a.json") => Ok(r#"[{ "resource": "x", "startLineNumber": 1, "message": "deprecated" },
+                                     { "resource": "x", "startLineNumber": 1, "message": "deprecated" }]"#.to_string()),
+            Some("b.json") => Ok(r#"[{ "resource": "x", "startLineNumber": 1, "message": "deprecated" }]"#.to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
 
         let mut out = Vec::new();
-        run_app(&cli, read_fn, &mut out).expect("run_app failed");
-        let s = String::from_utf8(out).expect("invalid utf8");
-        assert!(s.contains("This is deprecated"));
-        assert!(s.trim_start().starts_with('['));
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        String::from_utf8(out).expect("invalid utf8")
     }
 
     #[test]
-    fn test_run_app_count_only() {
+    fn test_run_app_dedup_report() {
         let cli = CliProblemApp {
-            input: PathBuf::from("x.json"),
-            include_terms: vec!["foo".to_string()],
-            exclude_terms: vec![],
-            ignore_case: false,
-            count_only: true,
-            json: false,
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            dedup: true,
+            dedup_report: true,
+            ..Default::default()
         };
 
         let json = r#"[
-            { "resource": "a/test.java", "startLineNumber": 1, "message": "foo bar" },
-            { "resource": "b/test.java", "startLineNumber": 2, "message": "no match" }
+            { "resource": "x", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "x", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "x", "startLineNumber": 2, "message": "deprecated" }
         ]"#;
 
-        let read_fn = |_p: &PathBuf| Ok(json.to_string());
-
         let mut out = Vec::new();
-        run_app(&cli, read_fn, &mut out).expect("run_app failed");
-        let s = String::from_utf8(out).expect("invalid utf8");
-        assert!(s.contains("Nombre total de problèmes: 2"));
+        let mut err = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut err).expect("run_app failed");
+        assert!(String::from_utf8(out).unwrap().contains("Nombre de problèmes filtrés: 2"));
+        assert_eq!(
+            String::from_utf8(err).unwrap(),
+            "Groupes de doublons supprimés: 1\n  2 occurrences: x:1: deprecated\n"
+        );
+    }
+
+    #[test]
+    fn test_run_app_dedup() {
+        let s = run_dedup(false);
         assert!(s.contains("Nombre de problèmes filtrés: 1"));
+        assert!(s.contains("Doublons supprimés: 2"));
     }
 
     #[test]
-    fn test_run_app_validation_error() {
+    fn test_run_app_dedup_keep_duplicates_across_files() {
+        let s = run_dedup(true);
+        assert!(s.contains("Nombre de problèmes filtrés: 2"));
+        assert!(s.contains("Doublons supprimés: 1"));
+    }
+
+    #[test]
+    fn test_run_app_show_file_provenance() {
         let cli = CliProblemApp {
-            input: PathBuf::from("x.json"),
-            include_terms: vec![],
-            exclude_terms: vec![],
-            ignore_case: false,
-            count_only: false,
-            json: false,
+            inputs: vec![PathBuf::from("front.json"), PathBuf::from("back.json")],
+            include_terms: vec!["deprecated".to_string()],
+            show_file: true,
+            json: true,
+            ..Default::default()
         };
 
-        let read_fn = |_p: &PathBuf| Ok("[]".to_string());
+        let read_fn = |p: &PathBuf| match p.to_str() {
+            Some("front.json") => Ok(r#"[{ "resource": "a.ts", "startLineNumber": 1, "message": "deprecated" }]"#.to_string()),
+            Some("back.json") => Ok(r#"[{ "resource": "b.rs", "startLineNumber": 2, "message": "deprecated" }]"#.to_string()),
+            _ => anyhow::bail!("unexpected path {p:?}"),
+        };
 
         let mut out = Vec::new();
-        let res = run_app(&cli, read_fn, &mut out);
-        assert!(res.is_err());
-        // Vérifier que le message d'erreur correspond à la validation des arguments
-        if let Err(e) = res {
-            let msg = format!("{e}");
-            assert!(msg.contains("Au moins un terme d'inclusion ou d'exclusion doit être spécifié"), "unexpected error message: {msg}");
-        }
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
+        let value: serde_json::Value = serde_json::from_slice(&out).expect("invalid json");
+        assert_eq!(value[0]["resource"], "a.ts");
+        assert_eq!(value[0]["file"], "front.json");
+        assert_eq!(value[1]["resource"], "b.rs");
+        assert_eq!(value[1]["file"], "back.json");
     }
 
     #[test]
-    fn test_run_app_table_output() {
+    fn test_run_app_show_file_table() {
         let cli = CliProblemApp {
-            input: PathBuf::from("x.json"),
-            include_terms: vec!["matchme".to_string()],
-            exclude_terms: vec![],
-            ignore_case: false,
-            count_only: false,
-            json: false,
+            inputs: vec![PathBuf::from("front.json")],
+            include_terms: vec!["deprecated".to_string()],
+            show_file: true,
+            ..Default::default()
         };
 
-        let json = r#"[
-            { "resource": "a/test.java", "startLineNumber": 3, "message": "matchme here" }
-        ]"#;
-
-        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+        let read_fn = |_p: &PathBuf| Ok(r#"[{ "resource": "a.ts", "startLineNumber": 1, "message": "deprecated" }]"#.to_string());
 
         let mut out = Vec::new();
-        run_app(&cli, read_fn, &mut out).expect("run_app failed");
+        run_app(&cli, read_fn, &mut out, &mut std::io::sink()).expect("run_app failed");
         let s = String::from_utf8(out).expect("invalid utf8");
-        // Table should contain header Resource and Message (tabled derives these names)
-        assert!(s.contains("Resource"));
-        assert!(s.contains("Message"));
-        assert!(s.contains("matchme here"));
+        assert!(s.contains("File"));
+        assert!(s.contains("front.json"));
     }
 
-    #[test]
-    fn test_run_app_ignore_case_include() {
+    fn run_with_thresholds(max_errors: Option<usize>, max_warnings: Option<usize>) -> (u8, String) {
         let cli = CliProblemApp {
-            input: PathBuf::from("x.json"),
-            include_terms: vec!["DEPRECATED".to_string()],
-            exclude_terms: vec![],
-            ignore_case: true,
-            count_only: false,
-            json: false,
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            count_only: true,
+            max_errors,
+            max_warnings,
+            ..Default::default()
         };
 
         let json = r#"[
-            { "resource": "a/test.java", "startLineNumber": 4, "message": "this is deprecated" }
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "deprecated", "severity": 8 },
+            { "resource": "b/test.java", "startLineNumber": 2, "message": "deprecated", "severity": 8 },
+            { "resource": "c/test.java", "startLineNumber": 3, "message": "deprecated", "severity": 4 },
+            { "resource": "d/test.java", "startLineNumber": 4, "message": "unused", "severity": 8 }
         ]"#;
 
         let read_fn = |_p: &PathBuf| Ok(json.to_string());
 
         let mut out = Vec::new();
-        run_app(&cli, read_fn, &mut out).expect("run_app failed");
-        let s = String::from_utf8(out).expect("invalid utf8");
-        assert!(s.contains("Nombre de problèmes filtrés: 1"));
+        let mut err = Vec::new();
+        let code = run_app(&cli, read_fn, &mut out, &mut err).expect("run_app failed");
+        (code, String::from_utf8(err).expect("invalid utf8"))
     }
 
     #[test]
-    fn test_run_app_ignore_case_exclude() {
+    fn test_run_app_max_errors_below_threshold() {
+        let (code, err) = run_with_thresholds(Some(3), None);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    fn test_run_app_max_errors_at_threshold() {
+        let (code, err) = run_with_thresholds(Some(2), None);
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    fn test_run_app_max_errors_above_threshold() {
+        let (code, err) = run_with_thresholds(Some(1), Some(1));
+        assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+        assert!(err.contains("2 erreur(s) (maximum autorisé: 1)"), "unexpected stderr: {err}");
+        assert!(!err.contains("avertissement"), "unexpected stderr: {err}");
+    }
+
+    #[test]
+    fn test_run_app_max_warnings_above_threshold() {
+        let (code, err) = run_with_thresholds(None, Some(0));
+        assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+        assert!(err.contains("1 avertissement(s) (maximum autorisé: 0)"), "unexpected stderr: {err}");
+    }
+
+    fn run_with_resource_bounds(below: Option<usize>, above: Option<usize>) -> (u8, String) {
         let cli = CliProblemApp {
-            input: PathBuf::from("x.json"),
-            include_terms: vec![],
-            exclude_terms: vec!["WARNING".to_string()],
-            ignore_case: true,
-            count_only: false,
-            json: false,
+            inputs: vec![PathBuf::from("x.json")],
+            include_terms: vec!["deprecated".to_string()],
+            count_only: true,
+            fail_if_resources_below: below,
+            fail_if_resources_above: above,
+            ..Default::default()
         };
 
+        // 3 problèmes filtrés répartis sur 2 fichiers
         let json = r#"[
-            { "resource": "a/test.java", "startLineNumber": 4, "message": "this is a warning" }
+            { "resource": "a/test.java", "startLineNumber": 1, "message": "deprecated" },
+            { "resource": "a/test.java", "startLineNumber": 2, "message": "deprecated" },
+            { "resource": "b/test.java", "startLineNumber": 3, "message": "deprecated" },
+            { "resource": "c/test.java", "startLineNumber": 4, "message": "unused" }
         ]"#;
 
-        let read_fn = |_p: &PathBuf| Ok(json.to_string());
+        let mut err = Vec::new();
+        let code = run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut std::io::sink(), &mut err).expect("run_app failed");
+        (code, String::from_utf8(err).expect("invalid utf8"))
+    }
 
-        let mut out = Vec::new();
-        run_app(&cli, read_fn, &mut out).expect("run_app failed");
-        let s = String::from_utf8(out).expect("invalid utf8");
-        // Since the only problem matches the exclude term, filtered count should be 0
-        assert!(s.contains("Nombre de problèmes filtrés: 0"));
+    #[test]
+    fn test_run_app_resources_below_bound() {
+        let (code, err) = run_with_resource_bounds(Some(3), None);
+        assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+        assert!(err.contains("2 fichier(s) concerné(s) (minimum requis: 3)"), "unexpected stderr: {err}");
+    }
+
+    #[test]
+    fn test_run_app_resources_within_bounds() {
+        let (code, err) = run_with_resource_bounds(Some(2), Some(2));
+        assert_eq!(code, EXIT_SUCCESS);
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    fn test_run_app_resources_above_bound() {
+        let (code, err) = run_with_resource_bounds(None, Some(1));
+        assert_eq!(code, EXIT_THRESHOLD_EXCEEDED);
+        assert!(err.contains("2 fichier(s) concerné(s) (maximum autorisé: 1)"), "unexpected stderr: {err}");
     }
 
     #[test]
     fn test_filter_problem_include() {
         let cli = CliProblemApp {
-            input: PathBuf::new(),
+            inputs: vec![],
             include_terms: vec!["deprecated".to_string()],
             exclude_terms: vec![],
             ignore_case: false,
             count_only: false,
             json: false,
+            ..Default::default()
         };
 
         let problem = Problem {
@@ -323,20 +4977,22 @@ mod tests {
             start_line_number: 10,
             message: "The type ActionError is deprecated".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        assert!(cli.filter_problem(&problem));
+        assert!(cli.problem_filter().matches(&problem));
     }
 
     #[test]
     fn test_filter_problem_exclude() {
         let cli = CliProblemApp {
-            input: PathBuf::new(),
+            inputs: vec![],
             include_terms: vec![],
             exclude_terms: vec!["warning".to_string()],
             ignore_case: false,
             count_only: false,
             json: false,
+            ..Default::default()
         };
 
         let problem = Problem {
@@ -344,20 +5000,22 @@ mod tests {
             start_line_number: 10,
             message: "This is a warning message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        assert!(!cli.filter_problem(&problem));
+        assert!(!cli.problem_filter().matches(&problem));
     }
 
     #[test]
     fn test_filter_problem_case_insensitive() {
         let cli = CliProblemApp {
-            input: PathBuf::new(),
+            inputs: vec![],
             include_terms: vec!["DEPRECATED".to_string()],
             exclude_terms: vec![],
             ignore_case: true,
             count_only: false,
             json: false,
+            ..Default::default()
         };
 
         let problem = Problem {
@@ -365,20 +5023,22 @@ mod tests {
             start_line_number: 10,
             message: "The type ActionError is deprecated".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        assert!(cli.filter_problem(&problem));
+        assert!(cli.problem_filter().matches(&problem));
     }
 
     #[test]
     fn test_filter_problem_case_sensitive() {
         let cli = CliProblemApp {
-            input: PathBuf::new(),
+            inputs: vec![],
             include_terms: vec!["DEPRECATED".to_string()],
             exclude_terms: vec![],
             ignore_case: false,
             count_only: false,
             json: false,
+            ..Default::default()
         };
 
         let problem = Problem {
@@ -386,20 +5046,22 @@ mod tests {
             start_line_number: 10,
             message: "The type ActionError is deprecated".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        assert!(!cli.filter_problem(&problem));
+        assert!(!cli.problem_filter().matches(&problem));
     }
 
     #[test]
     fn test_filter_problem_exclude_case_insensitive() {
         let cli = CliProblemApp {
-            input: PathBuf::new(),
+            inputs: vec![],
             include_terms: vec![],
             exclude_terms: vec!["WARNING".to_string()],
             ignore_case: true,
             count_only: false,
             json: false,
+            ..Default::default()
         };
 
         let problem = Problem {
@@ -407,20 +5069,225 @@ mod tests {
             start_line_number: 10,
             message: "This is a warning message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
+        };
+
+        assert!(!cli.problem_filter().matches(&problem));
+    }
+
+    #[test]
+    fn test_filter_problem_normalize_whitespace() {
+        let mut cli = CliProblemApp {
+            include_terms: vec!["cannot find  symbol".to_string()],
+            exclude_terms: vec!["in\ttest".to_string()],
+            ..Default::default()
+        };
+
+        let problem = Problem {
+            resource: "test.java".to_string(),
+            start_line_number: 10,
+            message: "cannot\tfind\n  symbol Foo".to_string(),
+            ..Default::default()
+        };
+        let excluded = Problem {
+            message: "cannot find symbol Foo in \n test".to_string(),
+            ..Default::default()
+        };
+
+        assert!(!cli.problem_filter().matches(&problem));
+
+        cli.normalize_whitespace = true;
+        assert!(cli.problem_filter().matches(&problem));
+        assert!(!cli.problem_filter().matches(&excluded));
+    }
+
+    fn problem_with_message_len(len: usize) -> Problem {
+        Problem {
+            resource: "test.java".to_string(),
+            start_line_number: 1,
+            message: "é".repeat(len),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_problem_min_msg_len() {
+        let cli = CliProblemApp {
+            min_msg_len: Some(10),
+            ..Default::default()
+        };
+
+        assert!(!cli.problem_filter().matches(&problem_with_message_len(9)));
+        assert!(cli.problem_filter().matches(&problem_with_message_len(10)));
+        assert!(cli.problem_filter().matches(&problem_with_message_len(200)));
+    }
+
+    #[test]
+    fn test_filter_problem_max_msg_len() {
+        let cli = CliProblemApp {
+            max_msg_len: Some(10),
+            ..Default::default()
+        };
+
+        assert!(cli.problem_filter().matches(&problem_with_message_len(0)));
+        // La limite s'applique au nombre de caractères, pas d'octets
+        assert!(cli.problem_filter().matches(&problem_with_message_len(10)));
+        assert!(!cli.problem_filter().matches(&problem_with_message_len(11)));
+    }
+
+    fn problem_in(resource: &str) -> Problem {
+        Problem {
+            resource: resource.to_string(),
+            start_line_number: 1,
+            message: "deprecated".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_problem_loose_phrase() {
+        let problem = |message: &str| Problem { message: message.to_string(), ..Default::default() };
+        let mut cli = CliProblemApp {
+            include_terms: vec!["Cannot symbol".to_string()],
+            ignore_case: true,
+            ..Default::default()
+        };
+
+        assert!(!cli.problem_filter().matches(&problem("cannot find symbol")));
+
+        cli.loose_phrase = true;
+        assert!(cli.problem_filter().matches(&problem("cannot find symbol")));
+        assert!(!cli.problem_filter().matches(&problem("symbol: cannot resolve")));
+    }
+
+    #[test]
+    fn test_filter_problem_include_min() {
+        let terms = ["deprecated", "removal", "since"].map(String::from).to_vec();
+        let mut cli = CliProblemApp { include_terms: terms, include_min: Some(2), ..Default::default() };
+        let problem = |message: &str| Problem { message: message.to_string(), ..Default::default() };
+
+        assert!(!cli.problem_filter().matches(&problem("deprecated")));
+        assert!(cli.problem_filter().matches(&problem("deprecated for removal")));
+        assert!(cli.problem_filter().matches(&problem("deprecated for removal since 9")));
+
+        cli.include_min = Some(1);
+        assert!(cli.problem_filter().matches(&problem("since 9")));
+        assert!(!cli.problem_filter().matches(&problem("other")));
+
+        cli.include_min = Some(3);
+        assert!(!cli.problem_filter().matches(&problem("deprecated for removal")));
+        assert!(cli.problem_filter().matches(&problem("deprecated for removal since 9")));
+    }
+
+    #[test]
+    fn test_run_app_include_min_out_of_range() {
+        for min in [0, 3] {
+            let cli = CliProblemApp {
+                inputs: vec![PathBuf::from("x.json")],
+                include_terms: vec!["a".to_string(), "b".to_string()],
+                include_min: Some(min),
+                ..Default::default()
+            };
+
+            let err = run_app(&cli, |_p: &PathBuf| Ok("[]".to_string()), &mut std::io::sink(), &mut std::io::sink())
+                .unwrap_err();
+            assert!(format!("{err}").contains("entre 1 et le nombre de termes d'inclusion (2)"));
+        }
+    }
+
+    #[test]
+    fn test_filter_problem_empty_messages() {
+        let problem = |message: &str| Problem { message: message.to_string(), ..Default::default() };
+        let (empty, blank, normal) = (problem(""), problem(" \t\n"), problem("deprecated"));
+
+        let cli = CliProblemApp { exclude_terms: vec!["x".to_string()], ..Default::default() };
+        assert!(cli.problem_filter().matches(&empty));
+
+        let cli = CliProblemApp { exclude_terms: vec!["x".to_string()], drop_empty: true, ..Default::default() };
+        assert!(!cli.problem_filter().matches(&empty));
+        assert!(!cli.problem_filter().matches(&blank));
+        assert!(cli.problem_filter().matches(&normal));
+
+        let cli = CliProblemApp { only_empty: true, ..Default::default() };
+        assert!(cli.problem_filter().matches(&empty));
+        assert!(cli.problem_filter().matches(&blank));
+        assert!(!cli.problem_filter().matches(&normal));
+    }
+
+    #[test]
+    fn test_run_app_only_empty_without_terms() {
+        let cli = CliProblemApp {
+            inputs: vec![PathBuf::from("x.json")],
+            only_empty: true,
+            count_only: true,
+            ..Default::default()
+        };
+
+        let json = r#"[
+            { "resource": "a", "startLineNumber": 1, "message": "  " },
+            { "resource": "b", "startLineNumber": 2, "message": "deprecated" }
+        ]"#;
+
+        let mut out = Vec::new();
+        run_app(&cli, |_p: &PathBuf| Ok(json.to_string()), &mut out, &mut std::io::sink()).expect("run_app failed");
+        assert!(String::from_utf8(out).unwrap().contains("Nombre de problèmes filtrés: 1"));
+    }
+
+    #[test]
+    fn test_filter_problem_ignore_generated() {
+        let cli = CliProblemApp {
+            ignore_generated: true,
+            ..Default::default()
+        };
+
+        assert!(cli.problem_filter().matches(&problem_in("/project/src/main.ts")));
+        assert!(!cli.problem_filter().matches(&problem_in("/project/node_modules/lib/index.js")));
+        assert!(!cli.problem_filter().matches(&problem_in("/project/target/debug/build.rs")));
+        assert!(!cli.problem_filter().matches(&problem_in("/project/src/types.d.ts")));
+        assert!(!cli.problem_filter().matches(&problem_in("/project/src/generated/api.ts")));
+    }
+
+    #[test]
+    fn test_filter_problem_generated_patterns() {
+        let mut cli = CliProblemApp {
+            ignore_generated: true,
+            generated_pattern: vec!["/vendor/".to_string()],
+            ..Default::default()
+        };
+
+        assert!(!cli.problem_filter().matches(&problem_in("/project/vendor/lib.php")));
+        assert!(!cli.problem_filter().matches(&problem_in("/project/node_modules/lib/index.js")));
+
+        cli.no_default_generated = true;
+        assert!(!cli.problem_filter().matches(&problem_in("/project/vendor/lib.php")));
+        assert!(cli.problem_filter().matches(&problem_in("/project/node_modules/lib/index.js")));
+    }
+
+    #[test]
+    fn test_message_key_normalize_whitespace() {
+        let cli = CliProblemApp {
+            normalize_whitespace: true,
+            ..Default::default()
+        };
+
+        let problem = Problem {
+            message: "unused\n\tvariable  'x'".to_string(),
+            ..Default::default()
         };
 
-        assert!(!cli.filter_problem(&problem));
+        assert_eq!(cli.message_key(&problem), "unused variable 'x'");
     }
 
     #[test]
     fn test_filter_problem_exclude_case_sensitive() {
         let cli = CliProblemApp {
-            input: PathBuf::new(),
+            inputs: vec![],
             include_terms: vec![],
             exclude_terms: vec!["WARNING".to_string()],
             ignore_case: false,
             count_only: false,
             json: false,
+            ..Default::default()
         };
 
         let problem = Problem {
@@ -428,9 +5295,10 @@ mod tests {
             start_line_number: 10,
             message: "This is a warning message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        assert!(cli.filter_problem(&problem));
+        assert!(cli.problem_filter().matches(&problem));
     }
 
 }
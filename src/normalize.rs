@@ -0,0 +1,128 @@
+/// Normalise un message pour rapprocher les diagnostics de même nature :
+/// le contenu des segments entre guillemets est remplacé par `*` et les
+/// nombres par `#`, de sorte que `'Foo' is deprecated (line 3)` et
+/// `'Bar' is deprecated (line 12)` donnent la même clé.
+pub fn normalize_message(message: &str) -> String {
+    let mut normalized = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    let mut previous: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            // Un guillemet collé à un mot (apostrophe de "can't") n'ouvre pas de segment
+            '\'' | '"' | '`' if !previous.is_some_and(char::is_alphanumeric) => {
+                // Ne remplacer que les segments effectivement refermés
+                let rest: String = chars.clone().collect();
+                if let Some(end) = rest.find(c) {
+                    normalized.push(c);
+                    normalized.push('*');
+                    normalized.push(c);
+                    for _ in 0..rest[..end].chars().count() + 1 {
+                        chars.next();
+                    }
+                } else {
+                    normalized.push(c);
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+                    chars.next();
+                }
+                normalized.push('#');
+            }
+            c => normalized.push(c),
+        }
+        previous = normalized.chars().next_back();
+    }
+
+    normalized
+}
+
+/// Réduit chaque suite de caractères blancs (espaces, tabulations, retours à la ligne)
+/// à un seul espace, et retire ceux de début et de fin
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Supprime les séquences d'échappement ANSI (couleurs, déplacements du curseur, titres OSC)
+pub fn strip_ansi(message: &str) -> String {
+    let mut stripped = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            stripped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI : ESC [ paramètres... octet final entre '@' et '~'
+            Some('[') => {
+                for next in chars.by_ref() {
+                    if ('@'..='~').contains(&next) {
+                        break;
+                    }
+                }
+            }
+            // OSC : ESC ] ... terminé par BEL ou ESC \
+            Some(']') => {
+                while let Some(next) = chars.next() {
+                    if next == '\u{7}' {
+                        break;
+                    }
+                    if next == '\u{1b}' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Séquence à deux caractères (ESC suivi d'un seul caractère)
+            _ => {}
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_quoted_segments() {
+        assert_eq!(normalize_message("The type 'Foo' is deprecated"), "The type '*' is deprecated");
+        assert_eq!(
+            normalize_message("The type \"Bar\" is deprecated"),
+            "The type \"*\" is deprecated"
+        );
+    }
+
+    #[test]
+    fn test_normalize_numbers() {
+        assert_eq!(normalize_message("expected 2 arguments, got 13"), "expected # arguments, got #");
+    }
+
+    #[test]
+    fn test_normalize_unterminated_quote() {
+        assert_eq!(normalize_message("can't resolve 'x'"), "can't resolve '*'");
+        assert_eq!(normalize_message("don't"), "don't");
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        assert_eq!(collapse_whitespace("  a\t\tb\n\r\nc  "), "a b c");
+        assert_eq!(collapse_whitespace("single"), "single");
+    }
+
+    #[test]
+    fn test_strip_ansi_colors() {
+        assert_eq!(strip_ansi("\u{1b}[31merror\u{1b}[0m: unused variable"), "error: unused variable");
+        assert_eq!(strip_ansi("\u{1b}[1;38;5;208mwarning\u{1b}[m"), "warning");
+    }
+
+    #[test]
+    fn test_strip_ansi_osc_and_plain_text() {
+        assert_eq!(strip_ansi("\u{1b}]8;;https://x\u{7}link\u{1b}]8;;\u{1b}\\"), "link");
+        assert_eq!(strip_ansi("no escape codes"), "no escape codes");
+    }
+}
@@ -0,0 +1,554 @@
+use crate::problem::{OutputOptions, ProblemOutput};
+use clap::ValueEnum;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use tabled::Table;
+use tabled::builder::Builder;
+use tabled::settings::peaker::Priority;
+use tabled::settings::style::HorizontalLine;
+use tabled::settings::{Style, Width};
+
+/// Format de sortie sélectionné par `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Rapport texte avec tableaux
+    #[default]
+    Table,
+    /// Document JSON indenté
+    Json,
+    /// Problèmes au format CSV
+    Csv,
+    /// Rapport texte avec tableaux Markdown
+    Markdown,
+    /// Un objet JSON par ligne
+    Ndjson,
+    /// Document TOML
+    Toml,
+    /// Tableau HTML des problèmes
+    Html,
+    /// Document SARIF 2.1.0 (analyse de code en intégration continue)
+    Sarif,
+    /// Rapport JUnit XML (une suite par fichier, un cas en échec par problème)
+    Junit,
+    /// Une ligne `resource(line): message` par problème, relue par un problem matcher VS Code
+    VscodeText,
+}
+
+impl OutputFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Html => "html",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Junit => "junit",
+            OutputFormat::VscodeText => "vscode-text",
+        }
+    }
+
+    /// Indique si le format ne représente que la liste des problèmes (pas de regroupement ni de compteurs)
+    pub fn is_problems_only(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Csv | OutputFormat::Html | OutputFormat::Sarif | OutputFormat::Junit | OutputFormat::VscodeText
+        )
+    }
+
+    /// Indique si le format produit le rapport texte (en-tête, compteurs, tableaux)
+    pub fn is_text(&self) -> bool {
+        matches!(self, OutputFormat::Table | OutputFormat::Markdown)
+    }
+
+    /// Applique le style de tableau correspondant au format
+    pub fn style(&self, mut table: Table) -> Table {
+        if *self == OutputFormat::Markdown {
+            table.with(Style::markdown());
+        }
+        table
+    }
+}
+
+/// Limite la largeur totale du tableau à `width` colonnes. Les colonnes les plus
+/// larges (en pratique le message) sont réduites en premier, et leur contenu est
+/// renvoyé à la ligne entre les mots.
+pub fn fit_width(mut table: Table, width: usize) -> Table {
+    table.with(Width::wrap(width).priority(Priority::max(true)).keep_words(true));
+    table
+}
+
+/// Style compact : aucune ligne de séparation entre les lignes de données, seul
+/// l'en-tête reste séparé
+pub fn compact(mut table: Table) -> Table {
+    table.with(
+        Style::ascii()
+            .remove_horizontal()
+            .horizontals([(1, HorizontalLine::inherit(Style::ascii()))]),
+    );
+    table
+}
+
+/// Colonne du tableau et de la sortie CSV
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Resource,
+    Message,
+    Line,
+    File,
+    Code,
+    Offset,
+    Kind,
+    Preview,
+    Exists,
+}
+
+impl Column {
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Resource => "Resource",
+            Column::Message => "Message",
+            Column::Line => "Line",
+            Column::File => "File",
+            Column::Code => "Code",
+            Column::Offset => "Offset",
+            Column::Kind => "Kind",
+            Column::Preview => "Preview",
+            Column::Exists => "Exists",
+        }
+    }
+
+    /// Retrouve une colonne d'après son en-tête, sans tenir compte de la casse
+    pub fn from_header(name: &str) -> Option<Self> {
+        [
+            Column::Resource,
+            Column::Message,
+            Column::Line,
+            Column::File,
+            Column::Code,
+            Column::Offset,
+            Column::Kind,
+            Column::Preview,
+            Column::Exists,
+        ]
+        .into_iter()
+        .find(|column| column.header().eq_ignore_ascii_case(name))
+    }
+
+    fn value(&self, problem: &ProblemOutput) -> String {
+        match self {
+            Column::Resource => problem.resource.clone(),
+            Column::Message => problem.message.clone(),
+            Column::Line => problem.line.to_string(),
+            Column::File => problem.file.clone().unwrap_or_default(),
+            Column::Code => problem.code.clone().unwrap_or_default(),
+            Column::Offset => problem.offset.clone().unwrap_or_default(),
+            Column::Kind => problem.kind.clone().unwrap_or_default(),
+            Column::Preview => problem.preview.clone().unwrap_or_default(),
+            Column::Exists => problem.exists.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Colonnes à afficher selon les options de sortie
+pub fn columns(options: &OutputOptions) -> Vec<Column> {
+    let mut columns = vec![Column::Resource, Column::Message, Column::Line];
+    if options.show_file {
+        columns.push(Column::File);
+    }
+    if options.show_code {
+        columns.push(Column::Code);
+    }
+    if options.show_offsets {
+        columns.push(Column::Offset);
+    }
+    if options.classifier.is_some() {
+        columns.push(Column::Kind);
+    }
+    columns
+}
+
+/// Retire les colonnes vides pour tous les problèmes (`--auto-hide-empty`).
+/// Sans problème, les colonnes sont toutes conservées.
+pub fn non_empty_columns(columns: &[Column], problems: &[ProblemOutput]) -> Vec<Column> {
+    if problems.is_empty() {
+        return columns.to_vec();
+    }
+    columns
+        .iter()
+        .copied()
+        .filter(|column| problems.iter().any(|problem| !column.value(problem).is_empty()))
+        .collect()
+}
+
+/// En-têtes des colonnes, en appliquant les libellés personnalisés de `--rename`
+pub fn headers(columns: &[Column], renames: &[(Column, String)]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| match renames.iter().rev().find(|(renamed, _)| renamed == column) {
+            Some((_, label)) => label.clone(),
+            None => column.header().to_string(),
+        })
+        .collect()
+}
+
+/// Construit le tableau d'affichage des problèmes avec les colonnes et en-têtes donnés
+pub fn problems_table(problems: &[ProblemOutput], columns: &[Column], headers: &[String]) -> Table {
+    let mut builder = Builder::default();
+    builder.push_record(headers.iter().cloned());
+    for problem in problems {
+        builder.push_record(columns.iter().map(|column| column.value(problem)));
+    }
+    builder.build()
+}
+
+/// Séparateur de champs CSV par défaut
+pub const DEFAULT_DELIMITER: char = ',';
+
+/// Écrit les problèmes au format CSV (RFC 4180), avec le séparateur donné.
+/// La ligne d'en-tête n'est écrite que si `headers` est renseigné.
+pub fn write_csv<W: Write>(
+    out: &mut W,
+    problems: &[ProblemOutput],
+    columns: &[Column],
+    headers: Option<&[String]>,
+    delimiter: char,
+) -> io::Result<()> {
+    if let Some(headers) = headers {
+        write_csv_record(out, headers, delimiter)?;
+    }
+    for problem in problems {
+        let fields: Vec<String> = columns.iter().map(|column| column.value(problem)).collect();
+        write_csv_record(out, &fields, delimiter)?;
+    }
+    Ok(())
+}
+
+/// Écrit une ligne `resource(line): message` par problème, au format reconnu par
+/// l'expression `^(.*)\((\d+)\): (.*)$` d'un problem matcher VS Code. Les retours
+/// à la ligne des messages sont remplacés par des espaces.
+pub fn write_vscode_text<W: Write>(out: &mut W, problems: &[ProblemOutput]) -> io::Result<()> {
+    for problem in problems {
+        let message = problem.message.replace(['\r', '\n'], " ");
+        writeln!(out, "{}({}): {message}", problem.resource, problem.line)?;
+    }
+    Ok(())
+}
+
+/// Écrit les problèmes sous forme de tableau HTML. Chaque ligne porte la
+/// classe `severity-<sévérité>` (`severity-none` sans sévérité) ; avec `full`,
+/// le tableau est inclus dans un document HTML complet.
+pub fn write_html<W: Write>(
+    out: &mut W,
+    problems: &[ProblemOutput],
+    columns: &[Column],
+    headers: &[String],
+    full: bool,
+) -> io::Result<()> {
+    if full {
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html>")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>Problèmes VS Code</title>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+    }
+
+    writeln!(out, "<table>")?;
+    writeln!(out, "<thead>")?;
+    write!(out, "<tr>")?;
+    for header in headers {
+        write!(out, "<th>{}</th>", escape_html(header))?;
+    }
+    writeln!(out, "</tr>")?;
+    writeln!(out, "</thead>")?;
+    writeln!(out, "<tbody>")?;
+    for problem in problems {
+        let severity = problem.severity.map_or("none", |severity| severity.name());
+        write!(out, "<tr class=\"severity-{severity}\">")?;
+        for column in columns {
+            write!(out, "<td>{}</td>", escape_html(&column.value(problem)))?;
+        }
+        writeln!(out, "</tr>")?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")?;
+
+    if full {
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+    }
+    Ok(())
+}
+
+/// Échappe les caractères spéciaux HTML (et XML)
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fichier `--output` ouvert (et vidé, sans `--append`) uniquement à la première
+/// écriture ou par `finish` : une exécution qui échoue avant d'écrire laisse le
+/// fichier existant intact
+pub struct LazyFile {
+    path: PathBuf,
+    append: bool,
+    file: Option<BufWriter<File>>,
+}
+
+impl LazyFile {
+    pub fn new(path: PathBuf, append: bool) -> Self {
+        Self { path, append, file: None }
+    }
+
+    fn file(&mut self) -> io::Result<&mut BufWriter<File>> {
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .append(self.append)
+                .truncate(!self.append)
+                .open(&self.path)
+                .map_err(|error| {
+                    io::Error::new(error.kind(), format!("Impossible d'écrire le fichier: {:?}: {error}", self.path))
+                })?;
+            self.file = Some(BufWriter::new(file));
+        }
+        Ok(self.file.as_mut().expect("fichier ouvert"))
+    }
+
+    /// Crée le fichier même si rien n'a été écrit, puis vide le tampon
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.file()?.flush()
+    }
+}
+
+impl Write for LazyFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn write_csv_record<W: Write>(out: &mut W, fields: &[String], delimiter: char) -> io::Result<()> {
+    let record: Vec<String> = fields.iter().map(|field| escape_csv_field(field, delimiter)).collect();
+    writeln!(out, "{}", record.join(&delimiter.to_string()))
+}
+
+/// Entoure le champ de guillemets s'il contient le séparateur, un guillemet ou un retour à la ligne
+fn escape_csv_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(message: &str) -> ProblemOutput {
+        ProblemOutput {
+            resource: "src/file.txt".to_string(),
+            message: message.to_string(),
+            line: 3,
+            file: None,
+            code: None,
+            offset: None,
+            kind: None,
+            preview: None,
+            exists: None,
+            severity: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_lazy_file() {
+        let path = std::env::temp_dir().join(format!("vpf-lazy-{}.txt", std::process::id()));
+        std::fs::write(&path, "old\n").unwrap();
+
+        // Sans écriture, le fichier existant n'est pas vidé
+        drop(LazyFile::new(path.clone(), false));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old\n");
+
+        let mut file = LazyFile::new(path.clone(), true);
+        writeln!(file, "appended").unwrap();
+        file.finish().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old\nappended\n");
+
+        let mut file = LazyFile::new(path.clone(), false);
+        writeln!(file, "new").unwrap();
+        file.finish().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+
+        // Un fichier vide est tout de même créé par finish
+        LazyFile::new(path.clone(), false).finish().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    const COLUMNS: [Column; 3] = [Column::Resource, Column::Message, Column::Line];
+
+    fn default_headers() -> Vec<String> {
+        headers(&COLUMNS, &[])
+    }
+
+    #[test]
+    fn test_write_csv_with_header() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[output("simple message")], &COLUMNS, Some(&default_headers()), DEFAULT_DELIMITER).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Resource,Message,Line\nsrc/file.txt,simple message,3\n");
+    }
+
+    #[test]
+    fn test_write_csv_without_header() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[output("simple message")], &COLUMNS, None, DEFAULT_DELIMITER).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        assert!(!s.contains("Resource,Message,Line"));
+        assert_eq!(s, "src/file.txt,simple message,3\n");
+    }
+
+    #[test]
+    fn test_write_csv_tab_delimiter() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[output("a, b\tc")], &COLUMNS, Some(&default_headers()), '\t').unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Resource\tMessage\tLine\nsrc/file.txt\t\"a, b\tc\"\t3\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_escapes_fields() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[output("expected \"a\", got b")], &COLUMNS, None, DEFAULT_DELIMITER).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "src/file.txt,\"expected \"\"a\"\", got b\",3\n");
+    }
+
+    #[test]
+    fn test_markdown_style() {
+        let table = OutputFormat::Markdown.style(problems_table(&[output("simple message")], &COLUMNS, &default_headers())).to_string();
+        let mut lines = table.lines();
+        assert_eq!(lines.next(), Some("| Resource     | Message        | Line |"));
+        assert_eq!(lines.next(), Some("|--------------|----------------|------|"));
+        assert_eq!(lines.next(), Some("| src/file.txt | simple message | 3    |"));
+    }
+
+    #[test]
+    fn test_problems_table_with_file_column() {
+        let mut problem = output("simple message");
+        problem.file = Some("front.json".to_string());
+
+        let with_file = columns(&OutputOptions { show_file: true, ..Default::default() });
+        let table = problems_table(&[problem], &with_file, &headers(&with_file, &[])).to_string();
+        assert!(table.contains("File"));
+        assert!(table.contains("front.json"));
+
+        let table = problems_table(&[output("simple message")], &COLUMNS, &default_headers()).to_string();
+        assert!(!table.contains("File"));
+    }
+
+    #[test]
+    fn test_write_html_escapes_and_classes() {
+        let mut problem = output("expected <T> & \"U\"");
+        problem.severity = Some(crate::problem::Severity::Error);
+
+        let mut out = Vec::new();
+        write_html(&mut out, &[problem, output("plain")], &COLUMNS, &default_headers(), false).unwrap();
+        let html = String::from_utf8(out).unwrap();
+        assert!(html.starts_with("<table>\n<thead>\n<tr><th>Resource</th><th>Message</th><th>Line</th></tr>\n"));
+        assert!(html.contains(
+            "<tr class=\"severity-error\"><td>src/file.txt</td><td>expected &lt;T&gt; &amp; &quot;U&quot;</td><td>3</td></tr>"
+        ));
+        assert!(html.contains("<tr class=\"severity-none\">"));
+        assert!(!html.contains("<html>"));
+    }
+
+    #[test]
+    fn test_write_html_full_document() {
+        let mut out = Vec::new();
+        write_html(&mut out, &[], &COLUMNS, &default_headers(), true).unwrap();
+        let html = String::from_utf8(out).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n<html>\n"));
+        assert!(html.ends_with("</table>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_fit_width_wraps_message() {
+        let problem = output("a rather long message that does not fit in a narrow terminal");
+        let table = fit_width(problems_table(&[problem], &COLUMNS, &default_headers()), 40).to_string();
+        assert!(table.lines().all(|line| line.chars().count() <= 40), "unexpected table:\n{table}");
+        assert!(table.contains("src/file.txt"), "unexpected table:\n{table}");
+        assert!(table.contains("narrow"), "unexpected table:\n{table}");
+    }
+
+    #[test]
+    fn test_non_empty_columns_hides_empty_code() {
+        let columns = [Column::Resource, Column::Message, Column::Line, Column::Code];
+        let problems = [output("a"), output("b")];
+        assert_eq!(non_empty_columns(&columns, &problems), COLUMNS);
+
+        let mut with_code = output("c");
+        with_code.code = Some("TS2304".to_string());
+        assert_eq!(non_empty_columns(&columns, &[output("a"), with_code]), columns);
+
+        assert_eq!(non_empty_columns(&columns, &[]), columns);
+    }
+
+    #[test]
+    fn test_renamed_headers() {
+        let renames = [(Column::Resource, "Fichier".to_string()), (Column::Line, "Row".to_string())];
+        let headers = headers(&COLUMNS, &renames);
+        assert_eq!(headers, ["Fichier", "Message", "Row"]);
+
+        let mut out = Vec::new();
+        write_csv(&mut out, &[output("simple message")], &COLUMNS, Some(&headers), DEFAULT_DELIMITER).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("Fichier,Message,Row\n"));
+
+        let table = problems_table(&[output("simple message")], &COLUMNS, &headers).to_string();
+        assert!(table.contains("Fichier"));
+        assert!(!table.contains("Resource"));
+    }
+
+    #[test]
+    fn test_write_vscode_text() {
+        let problems = [output("'x' is deprecated"), output("first line\nsecond line")];
+        let mut out = Vec::new();
+        write_vscode_text(&mut out, &problems).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "src/file.txt(3): 'x' is deprecated\nsrc/file.txt(3): first line second line\n"
+        );
+    }
+
+    #[test]
+    fn test_column_from_header() {
+        assert_eq!(Column::from_header("line"), Some(Column::Line));
+        assert_eq!(Column::from_header("Resource"), Some(Column::Resource));
+        assert_eq!(Column::from_header("Severity"), None);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Dossier utilisé par défaut pour `--source-root`
+pub const DEFAULT_SOURCE_ROOT: &str = ".";
+
+/// Indique si la ressource désigne une URI d'un autre schéma que `file:`
+/// (`https://...`, `untitled:Untitled-1`), sans fichier sur le disque. Une lettre
+/// de lecteur Windows (`C:\...`) n'est pas un schéma.
+fn is_non_file_uri(resource: &str) -> bool {
+    match resource.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.len() > 1
+                && scheme != "file"
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// Chemin du fichier d'une ressource, résolu par rapport au dossier `root`
+/// (une ressource absolue, par exemple `file:///...`, est conservée telle quelle).
+/// `None` pour les ressources qui ne sont pas des fichiers.
+pub fn resolve(root: &Path, resource: &str) -> Option<PathBuf> {
+    if is_non_file_uri(resource) {
+        return None;
+    }
+    let resource = resource.strip_prefix("file://").unwrap_or(resource);
+    Some(root.join(resource))
+}
+
+/// Indique si le fichier d'une ressource existe sur le disque (`None` pour les
+/// ressources qui ne sont pas des fichiers)
+pub fn exists(root: &Path, resource: &str) -> Option<bool> {
+    resolve(root, resource).map(|path| path.exists())
+}
+
+/// Chemins canoniques (liens symboliques résolus) des ressources distinctes, pour
+/// l'affichage avec `--resolve-symlinks`. Chaque ressource n'est résolue qu'une fois ;
+/// celles qui ne sont pas des fichiers existants sont absentes de la table.
+pub fn canonical_paths<'a>(root: &Path, resources: impl IntoIterator<Item = &'a str>) -> HashMap<String, String> {
+    let mut canonical = HashMap::new();
+    let mut seen = std::collections::HashSet::new();
+    for resource in resources {
+        if !seen.insert(resource) {
+            continue;
+        }
+        if let Some(path) = resolve(root, resource).and_then(|path| fs::canonicalize(path).ok()) {
+            canonical.insert(resource.to_string(), path.to_string_lossy().into_owned());
+        }
+    }
+    canonical
+}
+
+/// Lecture des fichiers sources référencés par les problèmes (`--source-preview`).
+/// Chaque fichier n'est lu qu'une fois ; un fichier illisible est mémorisé comme tel.
+pub struct SourcePreview {
+    root: PathBuf,
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl SourcePreview {
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf(), files: HashMap::new() }
+    }
+
+    /// Texte de la ligne `line` (numérotée à partir de 1) de la ressource, sans les
+    /// espaces qui l'entourent. `None` si le fichier ne peut pas être lu ou si la
+    /// ligne est au-delà de la fin du fichier.
+    pub fn line(&mut self, resource: &str, line: u32) -> Option<String> {
+        let path = resolve(&self.root, resource)?;
+        let lines = self.files.entry(path).or_insert_with_key(|path| {
+            let content = fs::read(path).ok()?;
+            Some(String::from_utf8_lossy(&content).lines().map(str::to_string).collect())
+        });
+        let index = usize::try_from(line.checked_sub(1)?).ok()?;
+        lines.as_ref()?.get(index).map(|text| text.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let root = Path::new("/project");
+        assert_eq!(resolve(root, "src/a.ts"), Some(PathBuf::from("/project/src/a.ts")));
+        assert_eq!(resolve(root, "file:///home/a.ts"), Some(PathBuf::from("/home/a.ts")));
+        assert_eq!(resolve(root, r"C:\work\a.ts"), Some(root.join(r"C:\work\a.ts")));
+        assert_eq!(resolve(root, "https://example.com/a.ts"), None);
+        assert_eq!(resolve(root, "untitled:Untitled-1"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_canonical_paths() {
+        let root = std::env::temp_dir().join(format!("vpf-canonical-{}", std::process::id()));
+        fs::create_dir_all(root.join("real")).unwrap();
+        fs::write(root.join("real/file.rs"), "").unwrap();
+        std::os::unix::fs::symlink("real", root.join("link")).unwrap();
+
+        let absolute = root.join("link/file.rs").to_string_lossy().into_owned();
+        let canonical = canonical_paths(&root, ["link/file.rs", "link/file.rs", absolute.as_str(), "missing.rs", "untitled:1"]);
+        let expected = fs::canonicalize(root.join("real/file.rs")).unwrap().to_string_lossy().into_owned();
+        // Les ressources relatives sont résolues par rapport au dossier racine
+        assert_eq!(canonical.get("link/file.rs"), Some(&expected));
+        assert_eq!(canonical.get(absolute.as_str()), Some(&expected));
+        assert_eq!(canonical.len(), 2);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_source_preview_line() {
+        let root = std::env::temp_dir().join(format!("vpf-preview-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.ts"), "const a = 1;\n\tlet b = a;\r\n").unwrap();
+
+        let mut preview = SourcePreview::new(&root);
+        assert_eq!(preview.line("src/a.ts", 1).as_deref(), Some("const a = 1;"));
+        assert_eq!(preview.line("src/a.ts", 2).as_deref(), Some("let b = a;"));
+        let absolute = format!("file://{}", root.join("src/a.ts").display());
+        assert_eq!(preview.line(&absolute, 1).as_deref(), Some("const a = 1;"));
+        // Au-delà de la fin du fichier, ligne 0 et fichier absent
+        assert_eq!(preview.line("src/a.ts", 3), None);
+        assert_eq!(preview.line("src/a.ts", 0), None);
+        assert_eq!(preview.line("src/missing.ts", 1), None);
+        assert_eq!(preview.line("https://example.com/src/a.ts", 1), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,8 +1,119 @@
-use serde::{Deserialize, Serialize};
-use tabled::Tabled;
+use crate::classify::Classifier;
+use crate::normalize;
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Sévérité d'un problème, selon les valeurs de `MarkerSeverity` de VS Code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Hint,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Sévérités de la plus grave à la moins grave
+    pub const ALL: [Severity; 4] = [Severity::Error, Severity::Warning, Severity::Info, Severity::Hint];
+
+    /// Convertit la valeur numérique exportée par VS Code (1, 2, 4, 8)
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            8 => Some(Severity::Error),
+            4 => Some(Severity::Warning),
+            2 => Some(Severity::Info),
+            1 => Some(Severity::Hint),
+            _ => None,
+        }
+    }
+
+    /// Valeur numérique correspondante dans l'export VS Code
+    pub fn code(&self) -> u64 {
+        match self {
+            Severity::Error => 8,
+            Severity::Warning => 4,
+            Severity::Info => 2,
+            Severity::Hint => 1,
+        }
+    }
+
+    /// Reconnaît un nom de sévérité (`error`, `warning`/`warn`, `info`/`information`, `hint`), sans tenir compte de la casse
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "error" => Some(Severity::Error),
+            "warning" | "warn" => Some(Severity::Warning),
+            "info" | "information" => Some(Severity::Info),
+            "hint" => Some(Severity::Hint),
+            _ => None,
+        }
+    }
+
+    /// Convertit une valeur de l'export, numérique ou textuelle
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        match value {
+            serde_json::Value::Number(n) => n.as_u64().and_then(Severity::from_code),
+            serde_json::Value::String(s) => Severity::from_name(s),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+}
+
+fn deserialize_severity<'de, D>(deserializer: D) -> Result<Option<Severity>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(Severity::from_value))
+}
+
+/// Lecture de la sévérité pour les exports qui n'utilisent pas l'encodage de VS Code
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeverityOptions<'a> {
+    /// Champ contenant la sévérité, à la place de `severity`
+    pub field: Option<&'a str>,
+    /// Correspondances `valeur=sévérité`, prioritaires sur les valeurs reconnues par défaut
+    pub map: &'a [(String, Severity)],
+}
+
+impl SeverityOptions<'_> {
+    /// Indique si la sévérité est lue comme dans un export VS Code standard
+    pub fn is_default(&self) -> bool {
+        self.field.is_none() && self.map.is_empty()
+    }
+
+    /// Champ contenant la sévérité
+    pub fn field(&self) -> &str {
+        self.field.unwrap_or("severity")
+    }
+
+    /// Sévérité correspondant à une valeur de l'export, en appliquant d'abord les correspondances personnalisées
+    pub fn resolve(&self, value: &serde_json::Value) -> Option<Severity> {
+        let raw = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            _ => return None,
+        };
+        self.map
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&raw))
+            .map(|(_, severity)| *severity)
+            .or_else(|| Severity::from_value(value))
+    }
+}
 
 /// Structure représentant un problème VS Code
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Problem {
 
     pub resource: String,
@@ -12,54 +123,320 @@ pub struct Problem {
 
     pub message: String,
 
+    #[serde(default, deserialize_with = "deserialize_severity")]
+    pub severity: Option<Severity>,
+
     // Autres champs optionnels que nous ignorons pour le filtrage
     #[serde(flatten)]
     pub _other: serde_json::Value,
+
+    /// Fichier d'entrée dont provient le problème (renseigné après le parsing)
+    #[serde(skip)]
+    pub origin: Option<PathBuf>,
+
+    /// Position du problème dans l'entrée, tous fichiers confondus (renseignée
+    /// après la lecture), utilisée pour départager les tris
+    #[serde(skip)]
+    pub index: usize,
+}
+
+impl Problem {
+    /// Outil à l'origine du problème (champ `source` de l'export)
+    pub fn source(&self) -> Option<&str> {
+        self._other.get("source").and_then(|v| v.as_str())
+    }
+
+    /// Code du problème, qui peut être une chaîne, un nombre ou un objet `{ value, target }`
+    pub fn code(&self) -> Option<String> {
+        let code = self._other.get("code")?;
+        let code = code.get("value").unwrap_or(code);
+        match code {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Position du problème en caractères ou en octets depuis le début du fichier,
+    /// lorsque l'export la fournit (`offset`, ou `startOffset` et `endOffset`) :
+    /// `120`, ou `120-135` pour un intervalle
+    pub fn offset(&self) -> Option<String> {
+        let field = |name| self._other.get(name).and_then(|v| v.as_u64());
+        let start = field("startOffset").or_else(|| field("offset"))?;
+        match field("endOffset") {
+            Some(end) if end != start => Some(format!("{start}-{end}")),
+            _ => Some(start.to_string()),
+        }
+    }
+
+    /// Informations associées (`relatedInformation`), sous forme de problèmes
+    /// sans sévérité, rattachés au même fichier d'entrée
+    pub fn related(&self) -> Vec<Problem> {
+        let Some(entries) = self._other.get("relatedInformation").and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+
+        entries
+            .iter()
+            .map(|entry| Problem {
+                resource: entry
+                    .get("resource")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&self.resource)
+                    .to_string(),
+                start_line_number: entry
+                    .get("startLineNumber")
+                    .and_then(|v| v.as_u64())
+                    .and_then(|line| u32::try_from(line).ok())
+                    .unwrap_or(self.start_line_number),
+                message: entry.get("message").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                origin: self.origin.clone(),
+                index: self.index,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Nombre de composants du chemin affichés par défaut : dossier parent et nom du fichier
+pub const DEFAULT_PATH_DEPTH: usize = 2;
+
+/// Longueur maximale d'un message affiché, points de suspension compris
+const MAX_MESSAGE_LEN: usize = 150;
+
+/// Manière de tronquer les messages trop longs
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum TruncateMode {
+    /// Couper à la limite exacte
+    #[default]
+    Char,
+    /// Couper au dernier espace avant la limite, sans séparer le dernier mot
+    Word,
+}
+
+/// Options de construction de `ProblemOutput`
+#[derive(Debug, Clone, Copy)]
+pub struct OutputOptions<'a> {
+    /// Nombre de composants conservés à la fin du chemin affiché
+    pub path_depth: usize,
+    /// Convertir les séparateurs `\` des chemins Windows en `/` avant de les raccourcir
+    pub normalize_paths: bool,
+    /// Renseigner le fichier d'entrée dont provient le problème
+    pub show_file: bool,
+    /// Supprimer les séquences d'échappement ANSI des messages
+    pub strip_ansi: bool,
+    /// Remplacer chaque tabulation des messages par ce nombre d'espaces
+    pub expand_tabs: Option<usize>,
+    /// Remplacer les retours à la ligne et tabulations des messages par une seule espace
+    pub single_line: bool,
+    /// Ajouter au message les emplacements des informations associées, `(voir fichier:ligne)`
+    pub inline_related: bool,
+    /// Troncature des messages trop longs
+    pub truncate_mode: TruncateMode,
+    /// Champs supplémentaires de l'export à recopier tels quels dans les sorties structurées
+    pub keep_fields: &'a [String],
+    /// Renseigner le code du diagnostic
+    pub show_code: bool,
+    /// Renseigner la position du problème en caractères ou en octets (`--show-offsets`)
+    pub show_offsets: bool,
+    /// Valeur ajoutée aux numéros de ligne affichés (1 pour une source numérotée à partir de 0)
+    pub line_offset: u32,
+    /// Renseigner la catégorie de chaque problème (`--classify`)
+    pub classifier: Option<&'a Classifier>,
+    /// Chemins canoniques affichés à la place des ressources (`--resolve-symlinks`)
+    pub canonical: Option<&'a HashMap<String, String>>,
+}
+
+impl Default for OutputOptions<'_> {
+    fn default() -> Self {
+        Self {
+            path_depth: DEFAULT_PATH_DEPTH,
+            normalize_paths: false,
+            show_file: false,
+            strip_ansi: false,
+            expand_tabs: None,
+            single_line: false,
+            inline_related: false,
+            truncate_mode: TruncateMode::default(),
+            keep_fields: &[],
+            show_code: false,
+            show_offsets: false,
+            line_offset: 0,
+            classifier: None,
+            canonical: None,
+        }
+    }
 }
 
 /// Structure pour l'affichage en tableau
-#[derive(Tabled, Serialize)]
+#[derive(Serialize)]
 pub struct ProblemOutput {
 
-    #[tabled(rename = "Resource")]
     pub resource: String,
 
-    #[tabled(rename = "Message")]
     pub message: String,
 
-    #[tabled(rename = "Line")]
     pub line: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
+    /// Ligne de code concernée (`--source-preview`), renseignée par l'appelant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+
+    /// Présence du fichier sur le disque (`--verify-resources`) : `yes`, `no`, ou `n/a`
+    /// pour les ressources qui ne sont pas des fichiers, renseignée par l'appelant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exists: Option<String>,
+
+    /// Sévérité, utilisée pour la mise en forme (classe CSS de la sortie HTML)
+    #[serde(skip)]
+    pub severity: Option<Severity>,
+
+    /// Champs conservés par `--keep-field`, absents lorsque le problème ne les définit pas
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl ProblemOutput {
-    pub fn new(problem: &Problem) -> Self {
-        // Tronquer le chemin pour l'affichage (garder seulement le nom du fichier et le dossier parent)
-        let resource = if let Some(pos) = problem.resource.rfind('/') {
-            let filename = &problem.resource[pos + 1..];
-            // Essayer de garder aussi le dossier parent
-            if let Some(parent_pos) = problem.resource[..pos].rfind('/') {
-                let parent = &problem.resource[parent_pos + 1..pos];
-                format!("{parent}/{filename}")
-            } else {
-                filename.to_string()
-            }
+    pub fn new(problem: &Problem, options: &OutputOptions) -> Self {
+        let resource = display_resource(&problem.resource, options);
+
+        let message = if options.strip_ansi {
+            normalize::strip_ansi(&problem.message)
+        } else {
+            problem.message.clone()
+        };
+
+        let message = if options.single_line { single_line(&message) } else { message };
+
+        let message = match options.expand_tabs {
+            Some(width) => message.replace('\t', &" ".repeat(width)),
+            None => message,
+        };
+
+        let message = truncate_message(message, options.truncate_mode);
+
+        // Ajouté après la troncature pour que les emplacements restent visibles
+        let related = if options.inline_related { problem.related() } else { Vec::new() };
+        let message = if related.is_empty() {
+            message
         } else {
-            problem.resource.clone()
+            let locations: Vec<String> = related
+                .iter()
+                .map(|related| {
+                    let resource = display_resource(&related.resource, options);
+                    format!("{resource}:{}", related.start_line_number.saturating_add(options.line_offset))
+                })
+                .collect();
+            format!("{message} (voir {})", locations.join(", "))
         };
 
-        // Tronquer le message s'il est trop long
-        let message = if problem.message.len() > 150 {
-            format!("{}...", &problem.message[..147])
+        let file = if options.show_file {
+            problem.origin.as_ref().map(|origin| origin.display().to_string())
         } else {
-            problem.message.clone()
+            None
         };
 
+        let extra = options
+            .keep_fields
+            .iter()
+            .filter_map(|name| problem._other.get(name).map(|value| (name.clone(), value.clone())))
+            .collect();
+
         Self {
             resource,
             message,
-            line: problem.start_line_number,
+            line: problem.start_line_number.saturating_add(options.line_offset),
+            file,
+            code: if options.show_code { problem.code() } else { None },
+            offset: if options.show_offsets { problem.offset() } else { None },
+            kind: options.classifier.map(|classifier| classifier.classify(problem).to_string()),
+            preview: None,
+            exists: None,
+            severity: problem.severity,
+            extra,
+        }
+    }
+}
+
+/// Chemin affiché d'une ressource : chemin canonique avec `--resolve-symlinks`,
+/// séparateurs `\` remplacés par `/` avec `--normalize-paths`, puis chemin
+/// raccourci à `path_depth` composants
+pub fn display_resource(resource: &str, options: &OutputOptions) -> String {
+    let resource = options
+        .canonical
+        .and_then(|canonical| canonical.get(resource))
+        .map_or(resource, String::as_str);
+    if options.normalize_paths {
+        shorten_path(&resource.replace('\\', "/"), options.path_depth)
+    } else {
+        shorten_path(resource, options.path_depth)
+    }
+}
+
+/// Tronque le chemin pour l'affichage en gardant ses `depth` derniers composants
+/// (par défaut le dossier parent et le nom du fichier). Le chemin complet est
+/// conservé s'il a au plus `depth` composants. `/` et `\` sont tous deux des
+/// séparateurs (chemins Windows), conservés tels quels.
+fn shorten_path(resource: &str, depth: usize) -> String {
+    const SEPARATORS: [char; 2] = ['/', '\\'];
+    if resource.split(SEPARATORS).count() <= depth {
+        return resource.to_string();
+    }
+
+    match resource.rmatch_indices(SEPARATORS).take(depth.max(1)).last() {
+        Some((pos, _)) => resource[pos + 1..].to_string(),
+        None => resource.to_string(),
+    }
+}
+
+/// Remplace chaque suite de retours à la ligne et de tabulations, avec les
+/// espaces qui l'entourent, par une seule espace
+fn single_line(message: &str) -> String {
+    let mut line = String::with_capacity(message.len());
+    let mut chars = message.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if matches!(c, '\n' | '\r' | '\t') {
+            line.truncate(line.trim_end_matches(' ').len());
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            line.push(' ');
+        } else {
+            line.push(c);
         }
     }
+    line
+}
+
+/// Tronque le message s'il est trop long (en caractères), en ajoutant `...`
+fn truncate_message(message: String, mode: TruncateMode) -> String {
+    if message.chars().count() <= MAX_MESSAGE_LEN {
+        return message;
+    }
+
+    // Position en octets du premier caractère au-delà de la limite, pour ne pas couper un caractère
+    let limit = message.char_indices().nth(MAX_MESSAGE_LEN - 3).map_or(message.len(), |(pos, _)| pos);
+    let head = &message[..limit];
+    let head = match mode {
+        TruncateMode::Char => head,
+        // La coupure tombe déjà entre deux mots : garder tout le début
+        TruncateMode::Word if message[limit..].starts_with(char::is_whitespace) => head.trim_end(),
+        TruncateMode::Word => match head.rfind(char::is_whitespace) {
+            Some(pos) => head[..pos].trim_end(),
+            None => head,
+        },
+    };
+    format!("{head}...")
 }
 
 #[cfg(test)]
@@ -73,9 +450,10 @@ mod tests {
             start_line_number: 1,
             message: "test message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        let output = ProblemOutput::new(&problem);
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
         assert_eq!(output.resource, "file.txt");
         assert_eq!(output.message, "test message");
         assert_eq!(output.line, 1);
@@ -88,9 +466,10 @@ mod tests {
             start_line_number: 1,
             message: "test message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        let output = ProblemOutput::new(&problem);
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
         assert_eq!(output.resource, "nested/file.txt");
     }
 
@@ -101,12 +480,60 @@ mod tests {
             start_line_number: 1,
             message: "test message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        let output = ProblemOutput::new(&problem);
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
         assert_eq!(output.resource, "segments/file.txt");
     }
 
+    fn shortened(resource: &str, path_depth: usize) -> String {
+        let problem = Problem { resource: resource.to_string(), ..Default::default() };
+        ProblemOutput::new(&problem, &OutputOptions { path_depth, ..Default::default() }).resource
+    }
+
+    #[test]
+    fn test_problem_output_path_depth() {
+        let resource = "/home/dev/project/src/main/file.rs";
+        assert_eq!(shortened(resource, 1), "file.rs");
+        assert_eq!(shortened(resource, 2), "main/file.rs");
+        assert_eq!(shortened(resource, 3), "src/main/file.rs");
+        assert_eq!(shortened(resource, DEFAULT_PATH_DEPTH), "main/file.rs");
+    }
+
+    #[test]
+    fn test_problem_output_path_depth_short_path() {
+        assert_eq!(shortened("src/file.rs", 1), "file.rs");
+        assert_eq!(shortened("src/file.rs", 2), "src/file.rs");
+        assert_eq!(shortened("src/file.rs", 3), "src/file.rs");
+        assert_eq!(shortened("file.rs", 3), "file.rs");
+    }
+
+    #[test]
+    fn test_problem_output_backslash_path() {
+        assert_eq!(shortened(r"src\nested\file.txt", DEFAULT_PATH_DEPTH), r"nested\file.txt");
+        assert_eq!(shortened(r"C:\work\src\nested\file.txt", 1), "file.txt");
+        assert_eq!(shortened(r"C:\work\src\nested\file.txt", 3), r"src\nested\file.txt");
+        assert_eq!(shortened(r"nested\file.txt", 3), r"nested\file.txt");
+    }
+
+    #[test]
+    fn test_problem_output_mixed_separators_path() {
+        assert_eq!(shortened(r"C:\work\src/nested\file.txt", DEFAULT_PATH_DEPTH), r"nested\file.txt");
+        assert_eq!(shortened(r"/work/src\nested/file.txt", DEFAULT_PATH_DEPTH), "nested/file.txt");
+        assert_eq!(shortened(r"/work/src\nested/file.txt", 3), r"src\nested/file.txt");
+    }
+
+    #[test]
+    fn test_problem_output_normalize_paths() {
+        let problem = Problem { resource: r"C:\work\src\nested\file.txt".to_string(), ..Default::default() };
+        let output = ProblemOutput::new(&problem, &OutputOptions { normalize_paths: true, ..Default::default() });
+        assert_eq!(output.resource, "nested/file.txt");
+
+        let options = OutputOptions { normalize_paths: true, path_depth: 1, ..Default::default() };
+        assert_eq!(ProblemOutput::new(&problem, &options).resource, "file.txt");
+    }
+
     #[test]
     fn test_problem_output_short_message() {
         let problem = Problem {
@@ -114,9 +541,10 @@ mod tests {
             start_line_number: 1,
             message: "short message".to_string(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        let output = ProblemOutput::new(&problem);
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
         assert_eq!(output.message, "short message");
     }
 
@@ -128,9 +556,10 @@ mod tests {
             start_line_number: 1,
             message,
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        let output = ProblemOutput::new(&problem);
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
         assert_eq!(output.message.len(), 150);
         assert!(output.message.ends_with("..."));
     }
@@ -143,10 +572,248 @@ mod tests {
             start_line_number: 1,
             message: message.clone(),
             _other: serde_json::Value::Null,
+            ..Default::default()
         };
 
-        let output = ProblemOutput::new(&problem);
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
         assert_eq!(output.message, message);
         assert!(!output.message.ends_with("..."));
     }
+
+    #[test]
+    fn test_problem_output_single_line() {
+        let message = format!("Type 'A' is not assignable to type 'B'.\n  Property 'x' is missing.\r\n\n\tSee {}", "x".repeat(150));
+        let problem = Problem { message, ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &OutputOptions { single_line: true, ..Default::default() });
+        assert!(!output.message.contains(['\n', '\r', '\t']), "unexpected message: {}", output.message);
+        assert!(output.message.starts_with("Type 'A' is not assignable to type 'B'. Property 'x' is missing. See xxx"));
+        assert!(output.message.ends_with("..."));
+        assert_eq!(output.message.len(), MAX_MESSAGE_LEN);
+
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
+        assert!(output.message.contains("'B'.\n  Property"));
+    }
+
+    #[test]
+    fn test_problem_output_inline_related() {
+        let problem: Problem = serde_json::from_str(
+            r#"{ "resource": "src/a.ts", "startLineNumber": 3, "message": "Duplicate identifier 'x'",
+                 "relatedInformation": [
+                     { "resource": "/project/src/b.ts", "startLineNumber": 7, "message": "First declared here" },
+                     { "message": "No location" }
+                 ] }"#,
+        )
+        .unwrap();
+        let options = OutputOptions { inline_related: true, ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &options);
+        assert_eq!(output.message, "Duplicate identifier 'x' (voir src/b.ts:7, src/a.ts:3)");
+
+        // Les emplacements associés sont affichés comme le fichier du problème
+        let windows: Problem = serde_json::from_str(
+            r#"{ "resource": "C:\\work\\src\\a.ts", "startLineNumber": 3, "message": "Duplicate identifier 'x'",
+                 "relatedInformation": [{ "resource": "C:\\work\\lib\\b.ts", "startLineNumber": 7, "message": "here" }] }"#,
+        )
+        .unwrap();
+        let normalized = OutputOptions { normalize_paths: true, ..options };
+        let output = ProblemOutput::new(&windows, &normalized);
+        assert_eq!(output.resource, "src/a.ts");
+        assert_eq!(output.message, "Duplicate identifier 'x' (voir lib/b.ts:7)");
+        let output = ProblemOutput::new(&windows, &options);
+        assert_eq!(output.message, r"Duplicate identifier 'x' (voir lib\b.ts:7)");
+        assert_eq!(ProblemOutput::new(&problem, &OutputOptions::default()).message, "Duplicate identifier 'x'");
+
+        // Sans information associée, ou avec une liste vide, le message est inchangé
+        let plain = Problem { message: "m".to_string(), ..Default::default() };
+        assert_eq!(ProblemOutput::new(&plain, &options).message, "m");
+        let empty = r#"{ "resource": "a", "startLineNumber": 1, "message": "m", "relatedInformation": [] }"#;
+        let empty: Problem = serde_json::from_str(empty).unwrap();
+        assert_eq!(ProblemOutput::new(&empty, &options).message, "m");
+    }
+
+    #[test]
+    fn test_problem_output_expand_tabs() {
+        let problem = Problem { message: "\u{1b}[31mexpected\tgot\u{1b}[0m".to_string(), ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &OutputOptions { expand_tabs: Some(2), strip_ansi: true, ..Default::default() });
+        assert_eq!(output.message, "expected  got");
+
+        let output = ProblemOutput::new(&problem, &OutputOptions { strip_ansi: true, ..Default::default() });
+        assert_eq!(output.message, "expected\tgot");
+    }
+
+    #[test]
+    fn test_truncate_word_boundary_before_limit() {
+        let message = format!("{} deprecated API", "a".repeat(140));
+        let words = OutputOptions { truncate_mode: TruncateMode::Word, ..Default::default() };
+        let problem = Problem { message: message.clone(), ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &words);
+        assert_eq!(output.message, format!("{}...", "a".repeat(140)));
+
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
+        assert_eq!(output.message, format!("{} deprec...", "a".repeat(140)));
+    }
+
+    #[test]
+    fn test_truncate_word_boundary_at_limit() {
+        let message = format!("{} {}", "a".repeat(147), "b".repeat(10));
+        let words = OutputOptions { truncate_mode: TruncateMode::Word, ..Default::default() };
+        let problem = Problem { message, ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &words);
+        assert_eq!(output.message, format!("{}...", "a".repeat(147)));
+    }
+
+    #[test]
+    fn test_truncate_word_without_whitespace() {
+        let words = OutputOptions { truncate_mode: TruncateMode::Word, ..Default::default() };
+        let problem = Problem { message: "a".repeat(200), ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &words);
+        assert_eq!(output.message.len(), 150);
+    }
+
+    #[test]
+    fn test_truncate_non_ascii() {
+        let problem = Problem { message: "é".repeat(100) + &" à".repeat(30), ..Default::default() };
+
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
+        assert_eq!(output.message.chars().count(), MAX_MESSAGE_LEN);
+        assert!(output.message.starts_with(&"é".repeat(100)));
+        assert!(output.message.ends_with(" à ..."));
+
+        let words = OutputOptions { truncate_mode: TruncateMode::Word, ..Default::default() };
+        let output = ProblemOutput::new(&Problem { message: "é".repeat(200), ..Default::default() }, &words);
+        assert_eq!(output.message, format!("{}...", "é".repeat(147)));
+
+        // Pas de troncature en dessous de la limite, même au-delà de 150 octets
+        let output = ProblemOutput::new(&Problem { message: "é".repeat(100), ..Default::default() }, &words);
+        assert_eq!(output.message, "é".repeat(100));
+    }
+
+    #[test]
+    fn test_problem_deserialize_severity_and_code() {
+        let json = r#"[
+            { "resource": "a", "startLineNumber": 1, "message": "m", "severity": 8, "code": "E001", "source": "ts" },
+            { "resource": "b", "startLineNumber": 2, "message": "m", "code": { "value": 42, "target": "x" } },
+            { "resource": "c", "startLineNumber": 3, "message": "m", "severity": 3 }
+        ]"#;
+
+        let problems: Vec<Problem> = serde_json::from_str(json).unwrap();
+        assert_eq!(problems[0].severity, Some(Severity::Error));
+        assert_eq!(problems[0].code().as_deref(), Some("E001"));
+        assert_eq!(problems[0].source(), Some("ts"));
+        assert_eq!(problems[1].severity, None);
+        assert_eq!(problems[1].code().as_deref(), Some("42"));
+        assert_eq!(problems[2].severity, None);
+        assert_eq!(problems[2].code(), None);
+    }
+
+    #[test]
+    fn test_problem_deserialize_string_severity() {
+        let json = r#"[
+            { "resource": "a", "startLineNumber": 1, "message": "m", "severity": "Error" },
+            { "resource": "b", "startLineNumber": 2, "message": "m", "severity": "warn" },
+            { "resource": "c", "startLineNumber": 3, "message": "m", "severity": "fatal" }
+        ]"#;
+
+        let problems: Vec<Problem> = serde_json::from_str(json).unwrap();
+        assert_eq!(problems[0].severity, Some(Severity::Error));
+        assert_eq!(problems[1].severity, Some(Severity::Warning));
+        assert_eq!(problems[2].severity, None);
+    }
+
+    #[test]
+    fn test_severity_options_resolve_with_map() {
+        let map = [("3".to_string(), Severity::Error), ("fatal".to_string(), Severity::Error)];
+        let options = SeverityOptions { field: Some("level"), map: &map };
+
+        assert_eq!(options.field(), "level");
+        assert_eq!(options.resolve(&serde_json::json!(3)), Some(Severity::Error));
+        assert_eq!(options.resolve(&serde_json::json!("FATAL")), Some(Severity::Error));
+        assert_eq!(options.resolve(&serde_json::json!(4)), Some(Severity::Warning));
+        assert_eq!(options.resolve(&serde_json::json!(5)), None);
+        assert_eq!(options.resolve(&serde_json::Value::Null), None);
+    }
+
+    #[test]
+    fn test_problem_output_show_file() {
+        let problem = Problem {
+            resource: "file.txt".to_string(),
+            start_line_number: 1,
+            message: "test message".to_string(),
+            origin: Some(PathBuf::from("exports/front.json")),
+            ..Default::default()
+        };
+
+        assert_eq!(ProblemOutput::new(&problem, &OutputOptions::default()).file, None);
+        let output = ProblemOutput::new(&problem, &OutputOptions { show_file: true, ..Default::default() });
+        assert_eq!(output.file.as_deref(), Some("exports/front.json"));
+    }
+
+    #[test]
+    fn test_problem_output_strip_ansi() {
+        let problem = Problem {
+            resource: "file.txt".to_string(),
+            start_line_number: 1,
+            message: "\u{1b}[33mwarning\u{1b}[0m: unused".to_string(),
+            ..Default::default()
+        };
+
+        let output = ProblemOutput::new(&problem, &OutputOptions::default());
+        assert_eq!(output.message, problem.message);
+
+        let output = ProblemOutput::new(&problem, &OutputOptions { strip_ansi: true, ..Default::default() });
+        assert_eq!(output.message, "warning: unused");
+    }
+
+    #[test]
+    fn test_problem_output_keep_fields() {
+        let problems: Vec<Problem> = serde_json::from_str(
+            r#"[
+                { "resource": "a", "startLineNumber": 1, "message": "m", "owner": "team-a", "ticket": 42 },
+                { "resource": "b", "startLineNumber": 2, "message": "m", "owner": "team-b" }
+            ]"#,
+        )
+        .unwrap();
+        let keep_fields = ["ticket".to_string(), "owner".to_string()];
+        let options = OutputOptions { keep_fields: &keep_fields, ..Default::default() };
+
+        let output = serde_json::to_value(ProblemOutput::new(&problems[0], &options)).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!({ "resource": "a", "message": "m", "line": 1, "ticket": 42, "owner": "team-a" })
+        );
+
+        let output = serde_json::to_value(ProblemOutput::new(&problems[1], &options)).unwrap();
+        assert_eq!(output, serde_json::json!({ "resource": "b", "message": "m", "line": 2, "owner": "team-b" }));
+
+        let output = serde_json::to_value(ProblemOutput::new(&problems[1], &OutputOptions::default())).unwrap();
+        assert_eq!(output.get("owner"), None);
+    }
+
+    #[test]
+    fn test_related_information() {
+        let problem: Problem = serde_json::from_str(
+            r#"{ "resource": "src/a.ts", "startLineNumber": 3, "message": "Duplicate identifier", "severity": 8,
+                 "relatedInformation": [
+                     { "resource": "src/b.ts", "startLineNumber": 7, "startColumn": 1, "message": "First declared here" },
+                     { "message": "No location" }
+                 ] }"#,
+        )
+        .unwrap();
+
+        let related = problem.related();
+        assert_eq!(related.len(), 2);
+        assert_eq!(related[0].resource, "src/b.ts");
+        assert_eq!(related[0].start_line_number, 7);
+        assert_eq!(related[0].message, "First declared here");
+        assert_eq!(related[0].severity, None);
+        assert_eq!(related[1].resource, "src/a.ts");
+        assert_eq!(related[1].start_line_number, 3);
+
+        assert!(Problem::default().related().is_empty());
+    }
 }
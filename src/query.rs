@@ -0,0 +1,383 @@
+use crate::problem::Problem;
+use anyhow::{Result, bail};
+
+/// Champ d'un problème sur lequel porte une comparaison
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Message,
+    Resource,
+    Severity,
+    Source,
+    Code,
+    Line,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "message" => Some(Field::Message),
+            "resource" | "path" => Some(Field::Resource),
+            "severity" => Some(Field::Severity),
+            "source" => Some(Field::Source),
+            "code" => Some(Field::Code),
+            "line" => Some(Field::Line),
+            _ => None,
+        }
+    }
+
+    fn value(&self, problem: &Problem) -> Option<String> {
+        match self {
+            Field::Message => Some(problem.message.clone()),
+            Field::Resource => Some(problem.resource.clone()),
+            Field::Severity => problem.severity.map(|s| s.name().to_string()),
+            Field::Source => problem.source().map(str::to_string),
+            Field::Code => problem.code(),
+            Field::Line => Some(problem.start_line_number.to_string()),
+        }
+    }
+}
+
+/// Opérateur de comparaison : `~` (contient) ou `=` (égal)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Contains,
+    Equals,
+}
+
+/// Arbre syntaxique d'une requête
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Compare { field: Field, op: Op, value: String },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Analyse une requête du type `message~"deprecated" AND NOT path~"test"`
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0, end: input.chars().count() + 1 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            bail!("Requête invalide à la position {}: élément inattendu '{}'", token.pos, token.kind);
+        }
+        Ok(expr)
+    }
+
+    /// Évalue la requête sur un problème. La casse est ignorée pour le champ
+    /// `resource` avec `ignore_case_resource`, pour les autres avec `ignore_case`.
+    pub fn evaluate(&self, problem: &Problem, ignore_case: bool, ignore_case_resource: bool) -> bool {
+        match self {
+            Expr::Compare { field, op, value } => {
+                let Some(actual) = field.value(problem) else {
+                    return false;
+                };
+                let fold = if *field == Field::Resource { ignore_case_resource } else { ignore_case };
+                let (actual, value) = if fold {
+                    (actual.to_lowercase(), value.to_lowercase())
+                } else {
+                    (actual, value.clone())
+                };
+                match op {
+                    Op::Contains => actual.contains(&value),
+                    Op::Equals => actual == value,
+                }
+            }
+            Expr::Not(inner) => !inner.evaluate(problem, ignore_case, ignore_case_resource),
+            Expr::And(left, right) => {
+                left.evaluate(problem, ignore_case, ignore_case_resource)
+                    && right.evaluate(problem, ignore_case, ignore_case_resource)
+            }
+            Expr::Or(left, right) => {
+                left.evaluate(problem, ignore_case, ignore_case_resource)
+                    || right.evaluate(problem, ignore_case, ignore_case_resource)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Tilde,
+    Equals,
+    Word(String),
+    Quoted(String),
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::LParen => write!(f, "("),
+            TokenKind::RParen => write!(f, ")"),
+            TokenKind::Tilde => write!(f, "~"),
+            TokenKind::Equals => write!(f, "="),
+            TokenKind::Word(w) => write!(f, "{w}"),
+            TokenKind::Quoted(q) => write!(f, "\"{q}\""),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: TokenKind,
+    /// Position (en caractères, à partir de 1) du début du token
+    pos: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().enumerate().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let pos = i + 1;
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '~' => TokenKind::Tilde,
+            '=' => TokenKind::Equals,
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => value.push(escaped),
+                            None => bail!("Requête invalide à la position {pos}: chaîne non terminée"),
+                        },
+                        Some((_, other)) => value.push(other),
+                        None => bail!("Requête invalide à la position {pos}: chaîne non terminée"),
+                    }
+                }
+                TokenKind::Quoted(value)
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(&(_, next)) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '(' | ')' | '~' | '=' | '"') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                TokenKind::Word(word)
+            }
+        };
+        tokens.push(Token { kind, pos });
+    }
+
+    Ok(tokens)
+}
+
+/// Analyseur descendant récursif :
+///
+/// ```text
+/// or      := and ("OR" and)*
+/// and     := unary ("AND" unary)*
+/// unary   := "NOT" unary | primary
+/// primary := "(" or ")" | FIELD ("~" | "=") VALUE
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Position rapportée lorsque la requête se termine prématurément
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Word(w), .. }) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.peek_keyword("OR") {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            expr = Expr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while self.peek_keyword("AND") {
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            expr = Expr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.peek_keyword("NOT") {
+            self.pos += 1;
+            let inner = self.parse_unary()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let end = self.end;
+        let Some(token) = self.next() else {
+            bail!("Requête invalide à la position {end}: expression attendue");
+        };
+        let pos = token.pos;
+
+        let field = match &token.kind {
+            TokenKind::LParen => {
+                let expr = self.parse_or()?;
+                return match self.next() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => Ok(expr),
+                    Some(token) => bail!(
+                        "Requête invalide à la position {}: ')' attendu au lieu de '{}'",
+                        token.pos,
+                        token.kind
+                    ),
+                    None => bail!("Requête invalide à la position {end}: ')' attendu"),
+                };
+            }
+            TokenKind::Word(name) => match Field::from_name(name) {
+                Some(field) => field,
+                None => bail!(
+                    "Requête invalide à la position {pos}: champ inconnu '{name}' (champs disponibles: message, path, severity, source, code, line)"
+                ),
+            },
+            other => bail!("Requête invalide à la position {pos}: nom de champ attendu au lieu de '{other}'"),
+        };
+
+        let op = match self.next() {
+            Some(Token { kind: TokenKind::Tilde, .. }) => Op::Contains,
+            Some(Token { kind: TokenKind::Equals, .. }) => Op::Equals,
+            Some(token) => bail!(
+                "Requête invalide à la position {}: opérateur '~' ou '=' attendu au lieu de '{}'",
+                token.pos,
+                token.kind
+            ),
+            None => bail!("Requête invalide à la position {end}: opérateur '~' ou '=' attendu"),
+        };
+
+        let value = match self.next() {
+            Some(Token { kind: TokenKind::Word(v) | TokenKind::Quoted(v), .. }) => v.clone(),
+            Some(token) => bail!(
+                "Requête invalide à la position {}: valeur attendue au lieu de '{}'",
+                token.pos,
+                token.kind
+            ),
+            None => bail!("Requête invalide à la position {end}: valeur attendue"),
+        };
+
+        Ok(Expr::Compare { field, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Severity;
+
+    fn problem(resource: &str, message: &str, severity: Option<Severity>) -> Problem {
+        Problem {
+            resource: resource.to_string(),
+            start_line_number: 1,
+            message: message.to_string(),
+            severity,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr = Expr::parse(r#"message~a OR message~b AND NOT path~"c""#).unwrap();
+        let compare = |field, value: &str| Expr::Compare { field, op: Op::Contains, value: value.to_string() };
+        assert_eq!(
+            expr,
+            Expr::Or(
+                Box::new(compare(Field::Message, "a")),
+                Box::new(Expr::And(
+                    Box::new(compare(Field::Message, "b")),
+                    Box::new(Expr::Not(Box::new(compare(Field::Resource, "c")))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn test_evaluate_full_expression() {
+        let expr = Expr::parse(r#"message~"deprecated" AND severity=error AND NOT path~"test""#).unwrap();
+
+        assert!(expr.evaluate(&problem("src/main.java", "X is deprecated", Some(Severity::Error)), false, false));
+        assert!(!expr.evaluate(&problem("src/test/main.java", "X is deprecated", Some(Severity::Error)), false, false));
+        assert!(!expr.evaluate(&problem("src/main.java", "X is deprecated", Some(Severity::Warning)), false, false));
+        assert!(!expr.evaluate(&problem("src/main.java", "X is deprecated", None), false, false));
+    }
+
+    #[test]
+    fn test_evaluate_parentheses() {
+        let expr = Expr::parse(r#"(message~foo OR message~bar) AND NOT message~baz"#).unwrap();
+
+        assert!(expr.evaluate(&problem("a", "foo", None), false, false));
+        assert!(expr.evaluate(&problem("a", "bar", None), false, false));
+        assert!(!expr.evaluate(&problem("a", "foo baz", None), false, false));
+        assert!(!expr.evaluate(&problem("a", "qux", None), false, false));
+    }
+
+    #[test]
+    fn test_evaluate_ignore_case() {
+        let expr = Expr::parse(r#"message~"DEPRECATED""#).unwrap();
+        let p = problem("a", "this is deprecated", None);
+
+        assert!(!expr.evaluate(&p, false, false));
+        assert!(expr.evaluate(&p, true, true));
+    }
+
+    #[test]
+    fn test_evaluate_ignore_case_resource() {
+        let expr = Expr::parse(r#"path~"src/Test" AND message~"Deprecated""#).unwrap();
+        let p = problem("SRC/test/a.java", "X is deprecated", None);
+        assert!(!expr.evaluate(&p, false, true));
+        assert!(!expr.evaluate(&p, true, false));
+        assert!(expr.evaluate(&p, true, true));
+
+        let expr = Expr::parse(r#"path~"src/Test""#).unwrap();
+        assert!(expr.evaluate(&p, false, true));
+    }
+
+    #[test]
+    fn test_parse_error_unknown_field() {
+        let err = Expr::parse(r#"message~a AND foo=b"#).unwrap_err();
+        let msg = format!("{err}");
+        assert!(msg.contains("position 15"), "unexpected error message: {msg}");
+        assert!(msg.contains("champ inconnu 'foo'"), "unexpected error message: {msg}");
+    }
+
+    #[test]
+    fn test_parse_error_missing_paren() {
+        let err = Expr::parse(r#"(message~a"#).unwrap_err();
+        let msg = format!("{err}");
+        assert!(msg.contains("position 11"), "unexpected error message: {msg}");
+        assert!(msg.contains("')' attendu"), "unexpected error message: {msg}");
+    }
+
+    #[test]
+    fn test_parse_error_unterminated_string() {
+        let err = Expr::parse(r#"message~"abc"#).unwrap_err();
+        let msg = format!("{err}");
+        assert!(msg.contains("position 9"), "unexpected error message: {msg}");
+        assert!(msg.contains("chaîne non terminée"), "unexpected error message: {msg}");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::problem::{Problem, Severity};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// Version du format SARIF produit par `--format sarif`
+const SARIF_VERSION: &str = "2.1.0";
+
+/// Schéma JSON de la version produite
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Niveau SARIF correspondant à une sévérité (`warning` par défaut, niveau
+/// implicite des résultats sans sévérité)
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "note",
+    }
+}
+
+/// Zone du fichier concernée : la ligne (décalée de `line_offset`, au moins 1),
+/// et les colonnes et la ligne de fin lorsque l'export les fournit
+fn region(problem: &Problem, line_offset: u32) -> Value {
+    let mut region = serde_json::Map::new();
+    region.insert("startLine".to_string(), problem.start_line_number.saturating_add(line_offset).max(1).into());
+    for (field, name) in [("startColumn", "startColumn"), ("endLineNumber", "endLine"), ("endColumn", "endColumn")] {
+        if let Some(value) = problem._other.get(field).and_then(Value::as_u64).filter(|&value| value >= 1) {
+            let value = if field == "endLineNumber" { value.saturating_add(line_offset.into()) } else { value };
+            region.insert(name.to_string(), value.into());
+        }
+    }
+    region.into()
+}
+
+/// Résultat SARIF d'un problème : règle (code du diagnostic), niveau, message et emplacement
+fn result(problem: &Problem, line_offset: u32) -> Value {
+    let mut result = serde_json::Map::new();
+    if let Some(code) = problem.code() {
+        result.insert("ruleId".to_string(), code.into());
+    }
+    if let Some(severity) = problem.severity {
+        result.insert("level".to_string(), level(severity).into());
+    }
+    result.insert("message".to_string(), json!({ "text": problem.message }));
+    result.insert(
+        "locations".to_string(),
+        json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": problem.resource },
+                "region": region(problem, line_offset),
+            }
+        }]),
+    );
+    result.into()
+}
+
+/// Document SARIF minimal : une exécution de l'outil, dont les règles sont les
+/// codes distincts des problèmes (dans l'ordre de première apparition)
+pub fn document(problems: &[&Problem], line_offset: u32) -> Value {
+    let mut seen = HashSet::new();
+    let mut rule_ids: Vec<String> = Vec::new();
+    for code in problems.iter().filter_map(|problem| problem.code()) {
+        if seen.insert(code.clone()) {
+            rule_ids.push(code);
+        }
+    }
+    let rules: Vec<Value> = rule_ids.into_iter().map(|id| json!({ "id": id })).collect();
+    let results: Vec<Value> = problems.iter().map(|problem| result(problem, line_offset)).collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_document() {
+        let problems: Vec<Problem> = [
+            json!({ "resource": "src/a.ts", "startLineNumber": 3, "startColumn": 5, "message": "'x' is deprecated",
+                    "severity": 4, "code": { "value": "TS6385", "target": "https://example.com" } }),
+            json!({ "resource": "src/b.ts", "startLineNumber": 0, "message": "Missing semicolon", "severity": 2 }),
+        ]
+        .into_iter()
+        .map(|value| serde_json::from_value(value).unwrap())
+        .collect();
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let document = document(&refs, 0);
+        assert_eq!(document["version"], "2.1.0");
+        let run = &document["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "vscode-problems-filtering");
+        assert_eq!(run["tool"]["driver"]["rules"], json!([{ "id": "TS6385" }]));
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(
+            results[0],
+            json!({
+                "ruleId": "TS6385",
+                "level": "warning",
+                "message": { "text": "'x' is deprecated" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/a.ts" },
+                        "region": { "startLine": 3, "startColumn": 5 }
+                    }
+                }]
+            })
+        );
+        assert!(results[1].get("ruleId").is_none());
+        assert_eq!(results[1]["level"], "note");
+        assert_eq!(results[1]["locations"][0]["physicalLocation"]["region"]["startLine"], 1);
+    }
+}
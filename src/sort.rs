@@ -0,0 +1,117 @@
+use crate::problem::Problem;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+/// Critère de tri des problèmes utilisé par `--sort-by`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SortBy {
+    /// Erreurs, puis avertissements, informations et indications ; les problèmes sans sévérité en dernier
+    Severity,
+    /// Par fichier (sans tenir compte de la casse), puis par numéro de ligne croissant
+    Location,
+}
+
+impl SortBy {
+    /// Compare deux problèmes selon le critère
+    pub fn compare(&self, a: &Problem, b: &Problem) -> Ordering {
+        match self {
+            // `None` est inférieur à toute sévérité : l'ordre décroissant le place en dernier
+            SortBy::Severity => b.severity.cmp(&a.severity),
+            SortBy::Location => a
+                .resource
+                .to_lowercase()
+                .cmp(&b.resource.to_lowercase())
+                .then_with(|| a.start_line_number.cmp(&b.start_line_number)),
+        }
+    }
+}
+
+/// Trie les problèmes selon le critère. À égalité, les problèmes sont départagés
+/// par leur position dans l'entrée (`Problem::index`), y compris avec `reverse` :
+/// l'ordre obtenu ne dépend ni de l'algorithme de tri ni de la plateforme.
+pub fn sort_problems(problems: &mut [&Problem], sort_by: SortBy, reverse: bool) {
+    problems.sort_by(|a, b| {
+        let ordering = sort_by.compare(a, b);
+        let ordering = if reverse { ordering.reverse() } else { ordering };
+        ordering.then_with(|| a.index.cmp(&b.index))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::Severity;
+
+    fn problem(message: &str, severity: Option<Severity>) -> Problem {
+        Problem {
+            resource: "file.ts".to_string(),
+            start_line_number: 1,
+            message: message.to_string(),
+            severity,
+            ..Default::default()
+        }
+    }
+
+    fn messages(problems: &[&Problem]) -> Vec<String> {
+        problems.iter().map(|problem| problem.message.clone()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_severity() {
+        let problems = [
+            problem("none", None),
+            problem("warning 1", Some(Severity::Warning)),
+            problem("hint", Some(Severity::Hint)),
+            problem("error", Some(Severity::Error)),
+            problem("info", Some(Severity::Info)),
+            problem("warning 2", Some(Severity::Warning)),
+        ];
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+
+        sort_problems(&mut refs, SortBy::Severity, false);
+        assert_eq!(messages(&refs), ["error", "warning 1", "warning 2", "info", "hint", "none"]);
+
+        sort_problems(&mut refs, SortBy::Severity, true);
+        assert_eq!(messages(&refs), ["none", "hint", "info", "warning 1", "warning 2", "error"]);
+    }
+
+    #[test]
+    fn test_sort_ties_broken_by_input_index() {
+        let problems: Vec<Problem> = (0..200)
+            .map(|index| Problem {
+                index,
+                ..problem(&index.to_string(), Some(if index % 3 == 0 { Severity::Error } else { Severity::Warning }))
+            })
+            .collect();
+        // Ordre de départ différent de l'ordre d'entrée : seul l'index départage les égalités
+        let mut refs: Vec<&Problem> = problems.iter().rev().collect();
+
+        sort_problems(&mut refs, SortBy::Severity, false);
+        let errors: Vec<usize> = (0..200).filter(|index| index % 3 == 0).collect();
+        let warnings: Vec<usize> = (0..200).filter(|index| index % 3 != 0).collect();
+        let expected: Vec<usize> = errors.iter().chain(&warnings).copied().collect();
+        assert_eq!(refs.iter().map(|problem| problem.index).collect::<Vec<_>>(), expected);
+
+        sort_problems(&mut refs, SortBy::Severity, true);
+        let expected: Vec<usize> = warnings.iter().chain(&errors).copied().collect();
+        assert_eq!(refs.iter().map(|problem| problem.index).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_sort_by_location() {
+        let at = |resource: &str, line: u32| Problem {
+            resource: resource.to_string(),
+            start_line_number: line,
+            message: format!("{resource}:{line}"),
+            ..Default::default()
+        };
+        let problems = [at("src/b.ts", 10), at("src/B.ts", 2), at("src/a.ts", 30), at("src/b.ts", 9), at("src/a.ts", 4)];
+        let mut refs: Vec<&Problem> = problems.iter().collect();
+
+        sort_problems(&mut refs, SortBy::Location, false);
+        assert_eq!(messages(&refs), ["src/a.ts:4", "src/a.ts:30", "src/B.ts:2", "src/b.ts:9", "src/b.ts:10"]);
+
+        sort_problems(&mut refs, SortBy::Location, true);
+        assert_eq!(messages(&refs), ["src/b.ts:10", "src/b.ts:9", "src/B.ts:2", "src/a.ts:30", "src/a.ts:4"]);
+    }
+}
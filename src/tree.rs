@@ -0,0 +1,223 @@
+use crate::problem::Problem;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Segments non vides d'un chemin (`/a/b/c.rs` donne `a`, `b`, `c.rs`), `/` et `\`
+/// étant tous deux des séparateurs (chemins Windows)
+pub fn path_segments(resource: &str) -> Vec<&str> {
+    resource.split(['/', '\\']).filter(|s| !s.is_empty()).collect()
+}
+
+/// Nom du fichier, dernier segment du chemin (`src/a/mod.rs` donne `mod.rs`)
+pub fn basename(resource: &str) -> Option<&str> {
+    path_segments(resource).pop()
+}
+
+/// Nœud de l'arborescence des fichiers concernés
+#[derive(Debug, Default, PartialEq)]
+pub struct TreeNode {
+    /// Nombre de problèmes dans ce nœud et ses descendants
+    pub count: usize,
+    /// Enfants indexés par nom (les dossiers se terminent par `/`)
+    pub children: BTreeMap<String, TreeNode>,
+    /// Enfants retirés par `prune`, résumés sur une ligne
+    pub omitted: Option<Omitted>,
+}
+
+/// Résumé des enfants d'un nœud retirés de l'arborescence (`--tree-min`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Omitted {
+    /// Nombre de fichiers des enfants retirés
+    pub files: usize,
+    /// Seuil sous lequel les enfants ont été retirés
+    pub min: usize,
+}
+
+impl TreeNode {
+    /// Construit l'arborescence à partir des chemins des problèmes
+    pub fn build(problems: &[&Problem]) -> Self {
+        let mut root = TreeNode::default();
+        for problem in problems {
+            root.insert(&problem.resource);
+        }
+        root
+    }
+
+    fn insert(&mut self, resource: &str) {
+        self.count += 1;
+
+        let segments = path_segments(resource);
+        let mut node = self;
+        for (i, segment) in segments.iter().enumerate() {
+            let name = if i + 1 < segments.len() {
+                format!("{segment}/")
+            } else {
+                segment.to_string()
+            };
+            node = node.children.entry(name).or_default();
+            node.count += 1;
+        }
+    }
+
+    /// Fusionne les chaînes de dossiers n'ayant qu'un seul sous-dossier
+    /// (`a/` > `b/` > `c/` devient `a/b/c/`)
+    pub fn collapse(self) -> Self {
+        let children = self
+            .children
+            .into_iter()
+            .map(|(mut name, mut child)| {
+                while name.ends_with('/') && child.children.len() == 1 && child.omitted.is_none() {
+                    let (only_name, only_child) = child.children.pop_first().expect("one child");
+                    if !only_name.ends_with('/') {
+                        child.children.insert(only_name, only_child);
+                        break;
+                    }
+                    name.push_str(&only_name);
+                    child = only_child;
+                }
+                (name, child.collapse())
+            })
+            .collect();
+
+        TreeNode { count: self.count, children, omitted: self.omitted }
+    }
+
+    /// Nombre de fichiers du nœud et de ses descendants, retirés compris
+    fn files(&self) -> usize {
+        if self.children.is_empty() && self.omitted.is_none() {
+            return 1;
+        }
+        self.children.values().map(TreeNode::files).sum::<usize>() + self.omitted.map_or(0, |omitted| omitted.files)
+    }
+
+    /// Retire les nœuds (et leurs sous-arborescences) ayant moins de `min` problèmes.
+    /// Les fichiers retirés sont comptés dans le résumé `omitted` de leur parent.
+    pub fn prune(self, min: usize) -> Self {
+        let mut files = 0;
+        let children = self
+            .children
+            .into_iter()
+            .filter_map(|(name, child)| {
+                if child.count < min {
+                    files += child.files();
+                    None
+                } else {
+                    Some((name, child.prune(min)))
+                }
+            })
+            .collect();
+
+        let omitted = (files > 0).then_some(Omitted { files, min });
+        TreeNode { count: self.count, children, omitted }
+    }
+
+    /// Représentation textuelle indentée, un nœud par ligne avec son nombre de problèmes
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        self.render_children(&mut output, 0);
+        output
+    }
+
+    fn render_children(&self, output: &mut String, depth: usize) {
+        for (name, child) in &self.children {
+            let _ = writeln!(output, "{}{name} ({})", "  ".repeat(depth), child.count);
+            child.render_children(output, depth + 1);
+        }
+        if let Some(Omitted { files, min }) = self.omitted {
+            let plural = if files == 1 { "autre fichier" } else { "autres fichiers" };
+            let _ = writeln!(output, "{}(… {files} {plural} avec moins de {min} problèmes)", "  ".repeat(depth));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(resources: &[&str]) -> Vec<Problem> {
+        resources
+            .iter()
+            .map(|resource| Problem {
+                resource: resource.to_string(),
+                start_line_number: 1,
+                message: "m".to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_counts() {
+        let problems = problems(&["src/a.rs", "src/a.rs", "src/b/c.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs);
+        assert_eq!(tree.count, 3);
+        assert_eq!(tree.render(), "src/ (3)\n  a.rs (2)\n  b/ (1)\n    c.rs (1)\n");
+    }
+
+    #[test]
+    fn test_build_backslash_paths() {
+        assert_eq!(path_segments(r"C:\work\src/a.rs"), ["C:", "work", "src", "a.rs"]);
+        assert_eq!(basename(r"src\b\c.rs"), Some("c.rs"));
+
+        let problems = problems(&[r"src\a.rs", "src/a.rs", r"src\b\c.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs);
+        assert_eq!(tree.render(), "src/ (3)\n  a.rs (2)\n  b/ (1)\n    c.rs (1)\n");
+    }
+
+    #[test]
+    fn test_collapse_single_child_chain() {
+        let problems = problems(&["/a/b/c/file.rs", "/a/b/c/other.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs).collapse();
+        assert_eq!(tree.render(), "a/b/c/ (2)\n  file.rs (1)\n  other.rs (1)\n");
+    }
+
+    #[test]
+    fn test_collapse_keeps_branches() {
+        let problems = problems(&["a/b/x/file.rs", "a/b/y/file.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs).collapse();
+        assert_eq!(
+            tree.render(),
+            "a/b/ (2)\n  x/ (1)\n    file.rs (1)\n  y/ (1)\n    file.rs (1)\n"
+        );
+    }
+
+    #[test]
+    fn test_prune_small_nodes() {
+        let problems = problems(&["src/a.rs", "src/a.rs", "src/a.rs", "src/b.rs", "src/c/d.rs", "src/c/e.rs", "lib/f.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs).prune(3);
+        assert_eq!(tree.count, 7);
+        assert_eq!(
+            tree.render(),
+            "src/ (6)\n  a.rs (3)\n  (… 3 autres fichiers avec moins de 3 problèmes)\n(… 1 autre fichier avec moins de 3 problèmes)\n"
+        );
+        assert_eq!(TreeNode::build(&refs).prune(1), TreeNode::build(&refs));
+    }
+
+    #[test]
+    fn test_prune_then_collapse_keeps_summary() {
+        let problems = problems(&["a/b/x.rs", "a/b/x.rs", "a/y.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs).prune(2).collapse();
+        assert_eq!(tree.render(), "a/ (3)\n  b/ (2)\n    x.rs (2)\n  (… 1 autre fichier avec moins de 2 problèmes)\n");
+    }
+
+    #[test]
+    fn test_collapse_keeps_single_file() {
+        let problems = problems(&["a/file.rs"]);
+        let refs: Vec<&Problem> = problems.iter().collect();
+
+        let tree = TreeNode::build(&refs).collapse();
+        assert_eq!(tree.render(), "a/ (1)\n  file.rs (1)\n");
+    }
+}